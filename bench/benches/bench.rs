@@ -15,7 +15,10 @@
 
 use std::{
     hint::black_box,
-    sync::{atomic::Ordering, Barrier},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Barrier,
+    },
     thread,
 };
 
@@ -33,6 +36,9 @@ mod tests {
     pub(crate) mod helper;
 }
 
+#[allow(dead_code)]
+#[path = "../../src/cache_padded.rs"]
+mod cache_padded;
 #[allow(dead_code, unused_imports)]
 #[path = "../../src/imp/mod.rs"]
 mod imp;
@@ -51,6 +57,12 @@ use seqlock_fallback as fallback;
 #[allow(dead_code, unused_imports)]
 #[path = "imp/spinlock_fallback.rs"]
 mod spinlock_fallback;
+#[cfg(target_arch = "x86_64")]
+#[path = "imp/x86_64_store.rs"]
+mod x86_64_store;
+// Uses std's AtomicU64 and AtomicUsize imported above.
+#[path = "../../src/sharded_counter.rs"]
+mod sharded_counter;
 
 const THREADS: usize = 2;
 const N: u32 = 5000;
@@ -285,6 +297,62 @@ fn bench_concurrent_fetch_add<A: AtomicInt<T>, T: Copy + From<u32>>() -> A {
     a
 }
 
+// Compare incrementing a single atomic counter with incrementing ShardedCounter.
+fn bench_sharded_counter(c: &mut Criterion) {
+    use sharded_counter::ShardedCounter;
+    let mut g = c.benchmark_group("bench_sharded_counter");
+    g.bench_function("single_fetch_add", |b| {
+        let a = AtomicU64::new(black_box(0));
+        b.iter(|| a.fetch_add(black_box(1), Ordering::Relaxed));
+        black_box(a);
+    });
+    g.bench_function("sharded_add", |b| {
+        let a = ShardedCounter::new();
+        b.iter(|| a.add(black_box(1)));
+        black_box(a);
+    });
+    g.bench_function("sharded_sum", |b| {
+        let a = ShardedCounter::new();
+        a.add(black_box(1));
+        b.iter(|| a.sum());
+        black_box(a);
+    });
+    g.bench_function("single_concurrent_fetch_add", |b| {
+        b.iter(|| {
+            let a = AtomicU64::new(black_box(0));
+            let barrier = Barrier::new(THREADS * 2);
+            thread::scope(|s| {
+                for _ in 0..THREADS * 2 {
+                    s.spawn(|| {
+                        barrier.wait();
+                        for _ in 0..N {
+                            a.fetch_add(black_box(1), Ordering::Relaxed);
+                        }
+                    });
+                }
+            });
+            a
+        });
+    });
+    g.bench_function("sharded_concurrent_add", |b| {
+        b.iter(|| {
+            let a = ShardedCounter::new();
+            let barrier = Barrier::new(THREADS * 2);
+            thread::scope(|s| {
+                for _ in 0..THREADS * 2 {
+                    s.spawn(|| {
+                        barrier.wait();
+                        for _ in 0..N {
+                            a.add(black_box(1));
+                        }
+                    });
+                }
+            });
+            a
+        });
+    });
+}
+
 macro_rules! benches {
     ($name:ident, $atomic_type:path, $int_type:ident) => {
         pub(crate) fn $name(c: &mut Criterion) {
@@ -354,6 +422,43 @@ macro_rules! benches {
 mod bench {
     use super::*;
 
+    // Compare 128-bit store instruction sequences on x86_64.
+    #[cfg(target_arch = "x86_64")]
+    pub(crate) fn bench_x86_64_store(c: &mut Criterion) {
+        use super::x86_64_store::AtomicU128;
+        let mut g = c.benchmark_group("bench_x86_64_store");
+        g.bench_function("u128_store_cmpxchg16b", |b| {
+            let a = AtomicU128::new(black_box(1));
+            b.iter(|| a.store_cmpxchg16b(black_box(2)));
+            black_box(a);
+        });
+        // VMOVDQA is atomic only on Intel, AMD, and Zhaoxin CPUs with AVX.
+        // Assume the benchmark runs on such CPUs if AVX is available.
+        if !std::is_x86_feature_detected!("avx") {
+            return;
+        }
+        g.bench_function("u128_store_vmovdqa", |b| {
+            let a = AtomicU128::new(black_box(1));
+            // SAFETY: AVX is available.
+            b.iter(|| unsafe { a.store_vmovdqa(black_box(2)) });
+            black_box(a);
+        });
+        g.bench_function("u128_store_vmovdqa_mfence", |b| {
+            let a = AtomicU128::new(black_box(1));
+            // SAFETY: AVX is available.
+            b.iter(|| unsafe { a.store_vmovdqa_mfence(black_box(2)) });
+            black_box(a);
+        });
+        g.bench_function("u128_store_vmovdqa_xchg", |b| {
+            let a = AtomicU128::new(black_box(1));
+            // SAFETY: AVX is available.
+            b.iter(|| unsafe { a.store_vmovdqa_xchg(black_box(2)) });
+            black_box(a);
+        });
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    pub(crate) fn bench_x86_64_store(_c: &mut Criterion) {}
+
     #[cfg(any(
         target_arch = "x86_64",
         all(any(target_arch = "aarch64", target_arch = "arm64ec"), target_endian = "little")
@@ -382,6 +487,8 @@ mod bench {
         bench_portable_atomic_intrinsics,
         bench_portable_atomic_seqlock_fallback,
         bench_portable_atomic_spinlock_fallback,
+        bench_x86_64_store,
+        bench_sharded_counter,
         // Disable third-party implementation by default.
         // bench_atomic_cell,
         // bench_atomic_rs
@@ -408,6 +515,7 @@ mod bench {
         bench_portable_atomic_arch,
         bench_portable_atomic_seqlock_fallback,
         bench_portable_atomic_spinlock_fallback,
+        bench_sharded_counter,
         // Disable third-party implementation by default.
         // bench_atomic_cell,
         // bench_atomic_rs
@@ -0,0 +1,111 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// 128-bit atomic store implementations on x86_64 for comparing the store
+// instruction sequences that can be selected by atomic128/x86_64.rs.
+//
+// - cmpxchg16b: CAS loop by lock cmpxchg16b (always SeqCst)
+// - vmovdqa: vmovdqa (Release)
+// - vmovdqa + mfence: vmovdqa followed by mfence (SeqCst)
+// - vmovdqa + xchg: vmovdqa followed by xchg to a stack slot (SeqCst, what atomic128/x86_64.rs uses)
+//
+// This module is currently only enabled on benchmark.
+
+use core::{arch::asm, cell::UnsafeCell, mem::MaybeUninit};
+
+#[repr(C, align(16))]
+pub(crate) struct AtomicU128 {
+    v: UnsafeCell<u128>,
+}
+
+// SAFETY: all accesses to the inner value are atomic.
+unsafe impl Sync for AtomicU128 {}
+
+impl AtomicU128 {
+    #[inline]
+    pub(crate) const fn new(v: u128) -> Self {
+        Self { v: UnsafeCell::new(v) }
+    }
+
+    #[inline]
+    pub(crate) fn store_cmpxchg16b(&self, val: u128) {
+        let dst = self.v.get();
+        // SAFETY: dst is valid, 16-byte aligned, and all accesses are atomic.
+        // CMPXCHG16B is available on all CPUs that run our benchmarks.
+        unsafe {
+            asm!(
+                "xchg {rbx_tmp}, rbx",
+                "mov rax, qword ptr [rdi]",
+                "mov rdx, qword ptr [rdi + 8]",
+                "2:",
+                    "lock cmpxchg16b xmmword ptr [rdi]",
+                    "jne 2b",
+                "mov rbx, {rbx_tmp}",
+                rbx_tmp = inout(reg) val as u64 => _,
+                in("rcx") (val >> 64) as u64,
+                out("rax") _,
+                out("rdx") _,
+                in("rdi") dst,
+                options(nostack),
+            );
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX and VMOVDQA must be atomic on it.
+    #[target_feature(enable = "avx")]
+    #[inline]
+    pub(crate) unsafe fn store_vmovdqa(&self, val: u128) {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            let val: core::arch::x86_64::__m128i = core::mem::transmute(val);
+            asm!(
+                "vmovdqa xmmword ptr [{dst}], {val}",
+                dst = in(reg) self.v.get(),
+                val = in(xmm_reg) val,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX and VMOVDQA must be atomic on it.
+    #[target_feature(enable = "avx")]
+    #[inline]
+    pub(crate) unsafe fn store_vmovdqa_mfence(&self, val: u128) {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            let val: core::arch::x86_64::__m128i = core::mem::transmute(val);
+            asm!(
+                "vmovdqa xmmword ptr [{dst}], {val}",
+                "mfence",
+                dst = in(reg) self.v.get(),
+                val = in(xmm_reg) val,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+
+    /// # Safety
+    ///
+    /// The CPU must support AVX and VMOVDQA must be atomic on it.
+    #[target_feature(enable = "avx")]
+    #[inline]
+    pub(crate) unsafe fn store_vmovdqa_xchg(&self, val: u128) {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            let val: core::arch::x86_64::__m128i = core::mem::transmute(val);
+            let p = UnsafeCell::new(MaybeUninit::<u64>::uninit());
+            asm!(
+                "vmovdqa xmmword ptr [{dst}], {val}",
+                "xchg qword ptr [{p}], {tmp}",
+                dst = in(reg) self.v.get(),
+                val = in(xmm_reg) val,
+                p = inout(reg) p.get() => _,
+                tmp = lateout(reg) _,
+                options(nostack, preserves_flags),
+            );
+        }
+    }
+}
@@ -0,0 +1,132 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*!
+<!-- tidy:crate-doc:start -->
+Derive macro for [portable-atomic]'s `AtomicRepr` trait.
+
+This crate is usually used through the `derive` feature of portable-atomic,
+which re-exports the macro as `portable_atomic::AtomicRepr`.
+
+```rust
+use portable_atomic::{AtomicEnum, AtomicRepr, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtomicRepr)]
+#[repr(u8)]
+enum State {
+    Idle,
+    Running,
+    Done = 10,
+}
+
+let state = AtomicEnum::new(State::Idle);
+state.store(State::Done, Ordering::Release);
+assert_eq!(state.load(Ordering::Acquire), State::Done);
+```
+
+The enum must be field-less and have a `#[repr(u8)]`, `#[repr(u16)]`, or
+`#[repr(u32)]` attribute, and must implement `Copy`.
+
+[portable-atomic]: https://github.com/taiki-e/portable-atomic
+
+<!-- tidy:crate-doc:end -->
+*/
+
+#![doc(test(
+    no_crate_inject,
+    attr(
+        deny(warnings, rust_2018_idioms, single_use_lifetimes),
+        allow(dead_code, unused_variables)
+    )
+))]
+#![forbid(unsafe_code)]
+#![warn(missing_debug_implementations, missing_docs)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Fields, Ident, Result};
+
+/// Derives `portable_atomic::AtomicRepr` for a field-less enum with a
+/// `#[repr(u8)]`, `#[repr(u16)]`, or `#[repr(u32)]` attribute.
+///
+/// See the [crate-level documentation](crate) for an example.
+#[proc_macro_derive(AtomicRepr)]
+pub fn derive_atomic_repr(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream2> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                input,
+                "#[derive(AtomicRepr)] may only be used on field-less enums",
+            ));
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &input.generics,
+            "#[derive(AtomicRepr)] may not be used on generic enums",
+        ));
+    }
+    let repr = repr(input)?;
+
+    let ident = &input.ident;
+    let mut consts = vec![];
+    let mut arms = vec![];
+    for (i, variant) in data.variants.iter().enumerate() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "#[derive(AtomicRepr)] may only be used on field-less enums",
+            ));
+        }
+        let variant = &variant.ident;
+        let c = format_ident!("__VARIANT_{}", i);
+        consts.push(quote! { const #c: #repr = #ident::#variant as #repr; });
+        arms.push(quote! { #c => #ident::#variant, });
+    }
+
+    Ok(quote! {
+        impl ::portable_atomic::AtomicRepr for #ident {
+            type Repr = #repr;
+            #[inline]
+            fn into_repr(self) -> #repr {
+                self as #repr
+            }
+            #[inline]
+            fn from_repr(repr: #repr) -> Self {
+                #(#consts)*
+                match repr {
+                    #(#arms)*
+                    _ => unreachable!("invalid representation of {}: {}", stringify!(#ident), repr),
+                }
+            }
+        }
+    })
+}
+
+const REPR_ERROR: &str =
+    "#[derive(AtomicRepr)] requires #[repr(u8)], #[repr(u16)], or #[repr(u32)]";
+
+fn repr(input: &DeriveInput) -> Result<Ident> {
+    let mut repr = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            for ty in &["u8", "u16", "u32"] {
+                if meta.path.is_ident(ty) {
+                    repr = Some(Ident::new(ty, Span::call_site()));
+                    return Ok(());
+                }
+            }
+            Err(meta.error(REPR_ERROR))
+        })?;
+    }
+    repr.ok_or_else(|| Error::new_spanned(&input.ident, REPR_ERROR))
+}
@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![allow(dead_code)]
+
+use portable_atomic::{AtomicEnum, AtomicRepr, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtomicRepr)]
+#[repr(u8)]
+enum A {
+    X,
+    Y,
+    Z = 10,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtomicRepr)]
+#[repr(u16)]
+enum B {
+    X = 0x100,
+    Y = 0x200,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AtomicRepr)]
+#[repr(u32)]
+enum C {
+    X = 0x1_0000,
+    Y,
+}
+
+#[test]
+fn repr() {
+    assert_eq!(A::X.into_repr(), 0_u8);
+    assert_eq!(A::Y.into_repr(), 1_u8);
+    assert_eq!(A::Z.into_repr(), 10_u8);
+    assert_eq!(A::from_repr(10), A::Z);
+    assert_eq!(B::Y.into_repr(), 0x200_u16);
+    assert_eq!(B::from_repr(0x100), B::X);
+    assert_eq!(C::Y.into_repr(), 0x1_0001_u32);
+    assert_eq!(C::from_repr(0x1_0001), C::Y);
+}
+
+#[test]
+#[should_panic = "invalid representation of A: 2"]
+fn invalid_repr() {
+    let _ = A::from_repr(2);
+}
+
+#[test]
+fn atomic_enum() {
+    let a = AtomicEnum::new(A::X);
+    assert_eq!(a.load(Ordering::Relaxed), A::X);
+    a.store(A::Z, Ordering::Relaxed);
+    assert_eq!(a.swap(A::Y, Ordering::AcqRel), A::Z);
+    assert_eq!(a.compare_exchange(A::X, A::Z, Ordering::AcqRel, Ordering::Acquire), Err(A::Y));
+    assert_eq!(a.compare_exchange(A::Y, A::Z, Ordering::AcqRel, Ordering::Acquire), Ok(A::Y));
+    assert_eq!(
+        a.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| if v == A::Z {
+            Some(A::X)
+        } else {
+            None
+        }),
+        Ok(A::Z)
+    );
+    assert_eq!(format!("{a:?}"), "X");
+    assert_eq!(a.into_inner(), A::X);
+
+    let b = AtomicEnum::from(B::X);
+    assert_eq!(b.swap(B::Y, Ordering::AcqRel), B::X);
+    assert_eq!(b.into_inner(), B::Y);
+
+    let c = AtomicEnum::new(C::Y);
+    assert_eq!(c.compare_exchange_weak(C::Y, C::X, Ordering::AcqRel, Ordering::Acquire), Ok(C::Y));
+    assert_eq!(c.load(Ordering::Acquire), C::X);
+}
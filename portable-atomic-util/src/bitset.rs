@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Atomic bitmaps.
+//!
+//! All single-bit operations are implemented with [`AtomicUsize::bit_set`],
+//! [`AtomicUsize::bit_clear`], and [`AtomicUsize::bit_toggle`], so they use
+//! dedicated instructions (e.g., `lock bts` on x86) where available.
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+use core::ops::Deref;
+use core::{fmt, mem};
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+const BITS: usize = mem::size_of::<usize>() * 8;
+
+/// A slice-backed atomic bitmap.
+///
+/// This is a view of a `[AtomicUsize]` as a sequence of bits: bit `i` is bit
+/// `i % usize::BITS` of word `i / usize::BITS`.
+///
+/// Each operation on a single bit is atomic. Operations that inspect multiple
+/// bits ([`find_first_zero_and_set`](Self::find_first_zero_and_set) and
+/// [`count_ones`](Self::count_ones)) are performed word by word, so they do not
+/// observe a consistent snapshot of the whole bitmap if it is modified concurrently.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::{AtomicUsize, Ordering};
+/// use portable_atomic_util::bitset::AtomicBitSlice;
+///
+/// let words = [AtomicUsize::new(0), AtomicUsize::new(0)];
+/// let bits = AtomicBitSlice::new(&words);
+/// assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(0));
+/// assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(1));
+/// assert!(bits.clear(0, Ordering::Release));
+/// assert_eq!(bits.count_ones(Ordering::Acquire), 1);
+/// ```
+#[repr(transparent)]
+pub struct AtomicBitSlice {
+    words: [AtomicUsize],
+}
+
+impl AtomicBitSlice {
+    /// Creates a bitmap view of the given slice of words.
+    #[inline]
+    #[must_use]
+    pub fn new(words: &[AtomicUsize]) -> &Self {
+        // SAFETY: AtomicBitSlice is repr(transparent) over [AtomicUsize].
+        unsafe { &*(words as *const [AtomicUsize] as *const Self) }
+    }
+
+    /// Returns the underlying words.
+    #[inline]
+    #[must_use]
+    pub fn as_words(&self) -> &[AtomicUsize] {
+        &self.words
+    }
+
+    /// Returns the number of bits in the bitmap.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.words.len() * BITS
+    }
+
+    /// Returns `true` if the bitmap has no bits.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    fn word(&self, index: usize) -> (&AtomicUsize, u32) {
+        let len = self.len();
+        assert!(index < len, "index out of bounds: the len is {} but the index is {}", len, index);
+        #[allow(clippy::cast_possible_truncation)] // index % BITS < BITS <= u32::MAX
+        (&self.words[index / BITS], (index % BITS) as u32)
+    }
+
+    /// Sets the bit at `index` to 1, returning the previous value of the bit.
+    ///
+    /// `set` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    pub fn set(&self, index: usize, order: Ordering) -> bool {
+        let (word, bit) = self.word(index);
+        word.bit_set(bit, order)
+    }
+
+    /// Clears the bit at `index` to 0, returning the previous value of the bit.
+    ///
+    /// `clear` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    pub fn clear(&self, index: usize, order: Ordering) -> bool {
+        let (word, bit) = self.word(index);
+        word.bit_clear(bit, order)
+    }
+
+    /// Toggles the bit at `index`, returning the previous value of the bit.
+    ///
+    /// `toggle` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    pub fn toggle(&self, index: usize, order: Ordering) -> bool {
+        let (word, bit) = self.word(index);
+        word.bit_toggle(bit, order)
+    }
+
+    /// Returns the value of the bit at `index`.
+    ///
+    /// `test` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds, or if `order` is [`Release`](Ordering::Release)
+    /// or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    pub fn test(&self, index: usize, order: Ordering) -> bool {
+        let (word, bit) = self.word(index);
+        word.load(order) & (1 << bit) != 0
+    }
+
+    /// Finds the first bit that is 0, sets it to 1, and returns its index.
+    ///
+    /// Returns `None` if all bits are 1. Because the words are scanned one by one,
+    /// `None` does not mean that all bits were 1 at the same point in time.
+    ///
+    /// `find_first_zero_and_set` takes an [`Ordering`] argument which describes the
+    /// memory ordering of the operation that sets the bit. All ordering modes are possible.
+    #[inline]
+    pub fn find_first_zero_and_set(&self, order: Ordering) -> Option<usize> {
+        for (i, word) in self.words.iter().enumerate() {
+            let mut current = word.load(Ordering::Relaxed);
+            while current != !0 {
+                let bit = (!current).trailing_zeros();
+                if !word.bit_set(bit, order) {
+                    return Some(i * BITS + bit as usize);
+                }
+                // Another thread set the bit first; look at the word again.
+                current = word.load(Ordering::Relaxed);
+            }
+        }
+        None
+    }
+
+    /// Returns the number of bits that are 1.
+    ///
+    /// The words are loaded one by one, so the result is not a consistent
+    /// snapshot if the bitmap is modified concurrently.
+    ///
+    /// `count_ones` takes an [`Ordering`] argument which describes the memory ordering
+    /// of the loads. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(not(portable_atomic_no_track_caller), track_caller)]
+    pub fn count_ones(&self, order: Ordering) -> usize {
+        self.words.iter().map(|word| word.load(order).count_ones() as usize).sum()
+    }
+}
+
+impl fmt::Debug for AtomicBitSlice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicBitSlice").field("words", &&self.words).finish()
+    }
+}
+
+/// A fixed-size atomic bitmap of `WORDS` words (`WORDS * usize::BITS` bits).
+///
+/// This dereferences to [`AtomicBitSlice`], which provides all operations on the bitmap.
+///
+/// Since [`new`](Self::new) is a `const fn`, this can be used in a `static`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::Ordering;
+/// use portable_atomic_util::bitset::AtomicBitSet;
+///
+/// static SLOTS: AtomicBitSet<2> = AtomicBitSet::new();
+///
+/// let slot = SLOTS.find_first_zero_and_set(Ordering::Acquire).unwrap();
+/// assert!(SLOTS.test(slot, Ordering::Relaxed));
+/// SLOTS.clear(slot, Ordering::Release);
+/// ```
+#[cfg(not(portable_atomic_no_min_const_generics))]
+#[repr(transparent)]
+pub struct AtomicBitSet<const WORDS: usize> {
+    words: [AtomicUsize; WORDS],
+}
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+impl<const WORDS: usize> AtomicBitSet<WORDS> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new bitmap with all bits set to 0.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            #[cfg(not(portable_atomic_loom))]
+            {
+                const ZERO: AtomicUsize = AtomicUsize::new(0);
+                Self { words: [ZERO; WORDS] }
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                Self { words: [(); WORDS].map(|()| AtomicUsize::new(0)) }
+            }
+        }
+    }
+
+    /// Consumes the bitmap and returns the underlying words.
+    #[inline]
+    #[must_use]
+    pub fn into_words(self) -> [AtomicUsize; WORDS] {
+        self.words
+    }
+}
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+impl<const WORDS: usize> Default for AtomicBitSet<WORDS> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+impl<const WORDS: usize> Deref for AtomicBitSet<WORDS> {
+    type Target = AtomicBitSlice;
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        AtomicBitSlice::new(&self.words)
+    }
+}
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+impl<const WORDS: usize> fmt::Debug for AtomicBitSet<WORDS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicBitSet").field("words", &self.words).finish()
+    }
+}
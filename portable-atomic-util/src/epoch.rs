@@ -0,0 +1,581 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Epoch-based memory reclamation.
+//!
+//! Lock-free data structures often unlink a node with a CAS while other threads
+//! may still be reading it, so the node cannot be freed immediately. This
+//! module provides a minimal epoch-based scheme to defer freeing such nodes
+//! until no thread can be reading them.
+//!
+//! - A thread [pins](pin) itself before loading pointers from a shared data
+//!   structure, and gets a [`Guard`]. While the guard is alive, objects that the
+//!   thread may read are not destroyed.
+//! - After unlinking an object, a thread passes it to
+//!   [`Guard::defer_destroy`], which destroys it once all threads that were
+//!   pinned at that time have unpinned.
+//!
+//! [`pin`] uses a default collector shared by all threads, and requires the
+//! `std` feature. Without the `std` feature, create a [`Collector`] and
+//! [register](Collector::register) a [`LocalHandle`] for each thread.
+//!
+//! This is similar to [crossbeam-epoch], but works on all targets where
+//! portable-atomic provides atomic CAS, and has a smaller API that works with
+//! raw pointers loaded from [`portable_atomic::AtomicPtr`].
+//!
+//! # Examples
+//!
+//! ```
+//! use portable_atomic::{AtomicPtr, Ordering};
+//! use portable_atomic_util::epoch::Collector;
+//!
+//! // With the `std` feature, `epoch::pin()` can be used instead of `handle.pin()`.
+//! let collector = Collector::new();
+//! let handle = collector.register();
+//! let shared = AtomicPtr::new(Box::into_raw(Box::new(1)));
+//!
+//! // Readers load the pointer while pinned.
+//! {
+//!     let guard = handle.pin();
+//!     let p = shared.load(Ordering::Acquire);
+//!     // SAFETY: p is not destroyed while guard is alive.
+//!     assert_eq!(unsafe { *p }, 1);
+//! }
+//!
+//! // Writers replace the pointer and defer destroying the old value.
+//! let guard = handle.pin();
+//! let old = shared.swap(Box::into_raw(Box::new(2)), Ordering::AcqRel);
+//! // SAFETY: old was created by Box::into_raw, and is no longer reachable from shared.
+//! unsafe { guard.defer_destroy(old) };
+//! # drop(guard);
+//! # unsafe { drop(Box::from_raw(shared.load(Ordering::Relaxed))) }
+//! ```
+//!
+//! [crossbeam-epoch]: https://github.com/crossbeam-rs/crossbeam/tree/HEAD/crossbeam-epoch
+
+// The algorithm is based on crossbeam-epoch.
+//
+// The global epoch is advanced by 2, and the lowest bit of the epoch of each
+// participant indicates whether it is pinned. The global epoch can be advanced
+// only if all pinned participants have observed the current global epoch.
+// Garbage is tagged with the global epoch when it is sealed, and destroyed
+// once the global epoch has been advanced twice since then: at that point,
+// every participant that was pinned when the garbage was unlinked has unpinned.
+//
+// Participants are kept in a push-only linked list, and their entries are
+// reused by later registrations instead of being freed.
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    fmt,
+    marker::PhantomData,
+    mem,
+    ptr::{self, NonNull},
+};
+
+use portable_atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+
+use crate::Arc;
+use alloc::{boxed::Box, vec::Vec};
+
+const PINNED: usize = 1;
+const EPOCH_STEP: usize = 2;
+// Garbage sealed at epoch `e` can be destroyed once the global epoch reaches `e + 2 * EPOCH_STEP`.
+const EXPIRATION: usize = 2 * EPOCH_STEP;
+// The number of deferred functions that a participant buffers before sealing them.
+const MAX_BAG_LEN: usize = 64;
+// The number of pins between attempts to advance the epoch and collect garbage.
+const PINS_BETWEEN_COLLECT: usize = 128;
+
+/// A type-erased deferred function.
+struct Deferred {
+    data: *mut (),
+    call: unsafe fn(*mut ()),
+}
+
+// SAFETY: the caller of Guard::defer_unchecked guarantees that the function
+// can be called from any thread.
+unsafe impl Send for Deferred {}
+
+impl Deferred {
+    fn new<F: FnOnce()>(f: F) -> Self {
+        unsafe fn call_boxed<F: FnOnce()>(data: *mut ()) {
+            // SAFETY: the caller guarantees that data was created by Box::into_raw in Deferred::new.
+            let f = unsafe { Box::from_raw(data as *mut F) };
+            f();
+        }
+        Self { data: Box::into_raw(Box::new(f)) as *mut (), call: call_boxed::<F> }
+    }
+
+    fn call(self) {
+        // SAFETY: self.call is called only once with the data created together.
+        unsafe { (self.call)(self.data) }
+    }
+}
+
+struct SealedBag {
+    epoch: usize,
+    deferreds: Vec<Deferred>,
+    next: *mut SealedBag,
+}
+
+struct Global {
+    epoch: AtomicUsize,
+    participants: AtomicPtr<Local>,
+    garbage: AtomicPtr<SealedBag>,
+}
+
+impl Global {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        const fn new() -> Self {
+            Self {
+                epoch: AtomicUsize::new(0),
+                participants: AtomicPtr::new(ptr::null_mut()),
+                garbage: AtomicPtr::new(ptr::null_mut()),
+            }
+        }
+    }
+
+    fn register(&self) -> NonNull<Local> {
+        let mut p = self.participants.load(Ordering::Acquire);
+        // SAFETY: participants are never freed while the global is alive.
+        while let Some(local) = unsafe { p.as_ref() } {
+            if !local.in_use.load(Ordering::Relaxed)
+                && local
+                    .in_use
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                local.handle_count.set(1);
+                return NonNull::from(local);
+            }
+            p = local.next;
+        }
+
+        let local = Box::into_raw(Box::new(Local {
+            next: ptr::null_mut(),
+            global: self,
+            in_use: AtomicBool::new(true),
+            epoch: AtomicUsize::new(0),
+            guard_count: Cell::new(0),
+            handle_count: Cell::new(1),
+            pin_count: Cell::new(0),
+            bag: UnsafeCell::new(Vec::new()),
+        }));
+        let mut head = self.participants.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: local is not published yet, so we have a unique access to it.
+            unsafe { (*local).next = head }
+            match self.participants.compare_exchange_weak(
+                head,
+                local,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(h) => head = h,
+            }
+        }
+        // SAFETY: local was created by Box::into_raw.
+        unsafe { NonNull::new_unchecked(local) }
+    }
+
+    fn push_bag(&self, deferreds: Vec<Deferred>) {
+        // Objects in the bag have been unlinked before this point.
+        fence(Ordering::SeqCst);
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        self.push_sealed(Box::into_raw(Box::new(SealedBag {
+            epoch,
+            deferreds,
+            next: ptr::null_mut(),
+        })));
+    }
+
+    fn push_sealed(&self, bag: *mut SealedBag) {
+        let mut head = self.garbage.load(Ordering::Relaxed);
+        loop {
+            // SAFETY: bag is owned by the caller until it is published.
+            unsafe { (*bag).next = head }
+            match self.garbage.compare_exchange_weak(
+                head,
+                bag,
+                Ordering::Release,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    // Tries to advance the global epoch, and returns the current global epoch.
+    fn try_advance(&self) -> usize {
+        let epoch = self.epoch.load(Ordering::Relaxed);
+        fence(Ordering::SeqCst);
+        let mut p = self.participants.load(Ordering::Acquire);
+        // SAFETY: participants are never freed while the global is alive.
+        while let Some(local) = unsafe { p.as_ref() } {
+            let local_epoch = local.epoch.load(Ordering::Relaxed);
+            if local_epoch & PINNED != 0 && local_epoch & !PINNED != epoch {
+                return epoch;
+            }
+            p = local.next;
+        }
+        fence(Ordering::Acquire);
+        let new_epoch = epoch.wrapping_add(EPOCH_STEP);
+        self.epoch.store(new_epoch, Ordering::Release);
+        new_epoch
+    }
+
+    fn collect(&self) {
+        let epoch = self.try_advance();
+        let mut p = self.garbage.swap(ptr::null_mut(), Ordering::Acquire);
+        while !p.is_null() {
+            // SAFETY: we took the list, so we have a unique access to its bags.
+            let next = unsafe { (*p).next };
+            // SAFETY: see above.
+            if epoch.wrapping_sub(unsafe { (*p).epoch }) >= EXPIRATION {
+                // SAFETY: the bag was created by Box::into_raw, and all
+                // participants that may access its objects have unpinned.
+                let bag = unsafe { Box::from_raw(p) };
+                for deferred in bag.deferreds {
+                    deferred.call();
+                }
+            } else {
+                self.push_sealed(p);
+            }
+            p = next;
+        }
+    }
+}
+
+impl Drop for Global {
+    fn drop(&mut self) {
+        // No participant is pinned because all handles have been dropped.
+        let mut p = *self.garbage.get_mut();
+        while !p.is_null() {
+            // SAFETY: we have a unique access to the global, and the bag was created by Box::into_raw.
+            let bag = unsafe { Box::from_raw(p) };
+            p = bag.next;
+            for deferred in bag.deferreds {
+                deferred.call();
+            }
+        }
+        let mut p = *self.participants.get_mut();
+        while !p.is_null() {
+            // SAFETY: we have a unique access to the global, and the participant was created by Box::into_raw.
+            let local = unsafe { Box::from_raw(p) };
+            p = local.next;
+            for deferred in local.bag.into_inner() {
+                deferred.call();
+            }
+        }
+    }
+}
+
+struct Local {
+    // Immutable after the participant is published.
+    next: *mut Local,
+    global: *const Global,
+    in_use: AtomicBool,
+    epoch: AtomicUsize,
+    // The following fields are only accessed by the thread that owns the participant.
+    guard_count: Cell<usize>,
+    handle_count: Cell<usize>,
+    pin_count: Cell<usize>,
+    bag: UnsafeCell<Vec<Deferred>>,
+}
+
+// SAFETY: other threads only access the atomic fields and the immutable fields.
+unsafe impl Sync for Local {}
+
+impl Local {
+    fn global(&self) -> &Global {
+        // SAFETY: the global outlives its participants.
+        unsafe { &*self.global }
+    }
+
+    fn pin(&self) -> Guard<'_> {
+        let guard_count = self.guard_count.get();
+        self.guard_count.set(guard_count.checked_add(1).expect("too many guards"));
+        if guard_count == 0 {
+            let global = self.global();
+            let epoch = global.epoch.load(Ordering::Relaxed);
+            self.epoch.store(epoch | PINNED, Ordering::Relaxed);
+            // Make the pin visible to try_advance before loading any shared pointer.
+            fence(Ordering::SeqCst);
+
+            let pin_count = self.pin_count.get().wrapping_add(1);
+            self.pin_count.set(pin_count);
+            if pin_count % PINS_BETWEEN_COLLECT == 0 {
+                global.collect();
+            }
+        }
+        Guard { local: self, _marker: PhantomData }
+    }
+
+    fn unpin(&self) {
+        let guard_count = self.guard_count.get() - 1;
+        self.guard_count.set(guard_count);
+        if guard_count == 0 {
+            self.epoch.store(0, Ordering::Release);
+            if self.handle_count.get() == 0 {
+                self.finalize();
+            }
+        }
+    }
+
+    fn defer(&self, deferred: Deferred) {
+        // SAFETY: the bag is only accessed by the owning thread, and no other
+        // reference to it is alive.
+        let bag = unsafe { &mut *self.bag.get() };
+        bag.push(deferred);
+        if bag.len() >= MAX_BAG_LEN {
+            self.flush();
+        }
+    }
+
+    fn flush(&self) {
+        // SAFETY: the bag is only accessed by the owning thread, and no other
+        // reference to it is alive.
+        let bag = unsafe { mem::replace(&mut *self.bag.get(), Vec::new()) };
+        if !bag.is_empty() {
+            self.global().push_bag(bag);
+        }
+    }
+
+    // Releases the participant after the last handle and guard are dropped.
+    fn finalize(&self) {
+        self.flush();
+        self.in_use.store(false, Ordering::Release);
+    }
+}
+
+/// A garbage collector that tracks the epochs of its participants.
+///
+/// Each thread that accesses data structures protected by this collector
+/// needs a [`LocalHandle`] returned by [`register`](Self::register).
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::epoch::Collector;
+///
+/// let collector = Collector::new();
+/// let handle = collector.register();
+/// let guard = handle.pin();
+/// // SAFETY: the closure can be called from any thread.
+/// unsafe { guard.defer_unchecked(|| println!("destroyed")) };
+/// ```
+#[derive(Clone)]
+pub struct Collector {
+    global: Arc<Global>,
+}
+
+// SAFETY: the global only contains atomics and deferred functions that can be called from any thread.
+unsafe impl Send for Global {}
+// SAFETY: see above.
+unsafe impl Sync for Global {}
+
+impl Collector {
+    /// Creates a new collector.
+    #[must_use]
+    pub fn new() -> Self {
+        Self { global: Arc::new(Global::new()) }
+    }
+
+    /// Registers a new participant and returns its handle.
+    #[must_use]
+    pub fn register(&self) -> LocalHandle {
+        LocalHandle { local: self.global.register(), _global: Some(self.global.clone()) }
+    }
+}
+
+impl Default for Collector {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Collector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Collector").finish()
+    }
+}
+
+/// A handle to a participant of a [`Collector`].
+///
+/// A handle is owned by a single thread, and cannot be sent to other threads.
+pub struct LocalHandle {
+    local: NonNull<Local>,
+    // Keeps the global alive. None for the default collector, which lives forever.
+    _global: Option<Arc<Global>>,
+}
+
+impl LocalHandle {
+    /// Pins the current thread.
+    ///
+    /// Objects that are passed to [`Guard::defer_destroy`] after this call
+    /// are not destroyed until the returned guard is dropped.
+    ///
+    /// Pinning is reentrant: if the thread is already pinned, this only
+    /// increments the number of guards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of guards overflows.
+    #[inline]
+    pub fn pin(&self) -> Guard<'_> {
+        self.local().pin()
+    }
+
+    /// Returns `true` if the participant is pinned.
+    #[inline]
+    #[must_use]
+    pub fn is_pinned(&self) -> bool {
+        self.local().guard_count.get() != 0
+    }
+
+    fn local(&self) -> &Local {
+        // SAFETY: the participant is kept alive by the global, and the global
+        // is kept alive by this handle.
+        unsafe { self.local.as_ref() }
+    }
+}
+
+impl Drop for LocalHandle {
+    fn drop(&mut self) {
+        let local = self.local();
+        let handle_count = local.handle_count.get() - 1;
+        local.handle_count.set(handle_count);
+        if handle_count == 0 && local.guard_count.get() == 0 {
+            local.finalize();
+        }
+    }
+}
+
+impl fmt::Debug for LocalHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalHandle").field("is_pinned", &self.is_pinned()).finish()
+    }
+}
+
+/// A guard that keeps the current thread pinned.
+///
+/// While a guard is alive, objects passed to [`defer_destroy`](Self::defer_destroy)
+/// by any thread after the guard was created are not destroyed, so pointers
+/// loaded from shared data structures remain valid.
+///
+/// The thread is unpinned when the last guard is dropped.
+#[must_use = "if unused the thread will immediately unpin"]
+pub struct Guard<'a> {
+    local: &'a Local,
+    // Guard is !Send and !Sync because the participant is owned by the current thread.
+    _marker: PhantomData<*mut ()>,
+}
+
+impl Guard<'_> {
+    /// Defers destroying the object that `ptr` points to until all threads
+    /// that are currently pinned have unpinned.
+    ///
+    /// # Safety
+    ///
+    /// - `ptr` must have been created by [`Box::into_raw`].
+    /// - The object must have been unlinked from all shared data structures, so
+    ///   that no thread that pins itself after this call can obtain `ptr`.
+    /// - `ptr` must not be passed to `defer_destroy` more than once.
+    /// - The object may be dropped on any thread, so if `T` is not [`Send`], it
+    ///   must be safe to drop it on other threads.
+    pub unsafe fn defer_destroy<T>(&self, ptr: *mut T) {
+        unsafe fn destroy<T>(ptr: *mut ()) {
+            // SAFETY: the caller of defer_destroy guarantees that ptr was created by Box::into_raw.
+            drop(unsafe { Box::from_raw(ptr as *mut T) });
+        }
+        self.local.defer(Deferred { data: ptr as *mut (), call: destroy::<T> });
+    }
+
+    /// Defers calling `f` until all threads that are currently pinned have unpinned.
+    ///
+    /// # Safety
+    ///
+    /// `f` may be called on any thread, so it must be safe to call it on other
+    /// threads, even if `F` is not [`Send`].
+    pub unsafe fn defer_unchecked<F>(&self, f: F)
+    where
+        F: FnOnce() + 'static,
+    {
+        self.local.defer(Deferred::new(f));
+    }
+
+    /// Defers calling `f` until all threads that are currently pinned have unpinned.
+    pub fn defer<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.local.defer(Deferred::new(f));
+    }
+
+    /// Moves the deferred functions buffered by the current thread to the
+    /// collector, and tries to advance the epoch and run expired deferred functions.
+    pub fn flush(&self) {
+        self.local.flush();
+        self.local.global().collect();
+    }
+}
+
+impl Drop for Guard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.local.unpin();
+    }
+}
+
+impl fmt::Debug for Guard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Guard").finish()
+    }
+}
+
+// The default collector is a static, which cannot be created by loom's
+// non-const atomic constructors, so pin and is_pinned are not available with
+// `--cfg portable_atomic_loom`.
+#[cfg(all(feature = "std", not(portable_atomic_loom)))]
+static DEFAULT_GLOBAL: Global = Global::new();
+
+#[cfg(all(feature = "std", not(portable_atomic_loom)))]
+std::thread_local! {
+    static HANDLE: LocalHandle = LocalHandle { local: DEFAULT_GLOBAL.register(), _global: None };
+}
+
+/// Pins the current thread using the default collector.
+///
+/// See [`LocalHandle::pin`] for details.
+///
+/// # Panics
+///
+/// Panics if the number of guards overflows.
+#[cfg(all(feature = "std", not(portable_atomic_loom)))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[inline]
+pub fn pin() -> Guard<'static> {
+    fn pin_local(handle: &LocalHandle) -> Guard<'static> {
+        // SAFETY: participants of the default collector are never freed, and a
+        // participant is not reused by other threads while it has a guard.
+        unsafe { &*handle.local.as_ptr() }.pin()
+    }
+    // If the thread-local handle has already been destroyed, use a temporary
+    // handle, which is released when the returned guard is dropped.
+    HANDLE.try_with(pin_local).unwrap_or_else(|_| {
+        pin_local(&LocalHandle { local: DEFAULT_GLOBAL.register(), _global: None })
+    })
+}
+
+/// Returns `true` if the current thread is pinned by the default collector.
+#[cfg(all(feature = "std", not(portable_atomic_loom)))]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+#[inline]
+#[must_use]
+pub fn is_pinned() -> bool {
+    HANDLE.try_with(LocalHandle::is_pinned).unwrap_or(false)
+}
@@ -6,8 +6,16 @@ Synchronization primitives built with [portable-atomic].
 
 - Provide `Arc`. (optional, requires the `std` or `alloc` feature)
 - Provide `task::Wake`. (optional, requires the `std` or `alloc` feature)
+- Provide atomic bitmaps (`bitset::{AtomicBitSlice, AtomicBitSet}`).
+- Provide lazy initialization primitives (`once_cell::{OnceCell, OnceLock, Lazy}`), including non-blocking variants (`once_cell::race`).
+- Provide spin-based locks (`spin::{Mutex, TicketMutex, RwLock, Once}`).
+- Provide bounded lock-free queues (`spsc::Queue`, `mpmc::Queue`). (requires Rust 1.51+)
+- Provide epoch-based memory reclamation (`epoch::{pin, Guard, Collector}`). (optional, requires the `std` or `alloc` feature)
+- Provide unbounded lock-free stack and queue based on tagged pointers (`tagged::{Stack, Queue}`). (optional, requires the `std` or `alloc` feature)
 <!-- - Provide generic `Atomic<T>` type. (optional, requires the `generic` feature) -->
 
+All primitives except `spsc::Queue` require atomic CAS, so they are not available on targets without atomic CAS (e.g., thumbv6m) unless portable-atomic provides atomic CAS there (via the `critical-section` feature or the `unsafe-assume-single-core` feature).
+
 See [#1] for other primitives being considered for addition to this crate.
 
 ## Optional features
@@ -61,20 +69,49 @@ See [#1] for other primitives being considered for addition to this crate.
 // docs.rs only (cfg is enabled by docs.rs, not build script)
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+// alloc is only used by the primitives that require atomic CAS.
 #[cfg(all(feature = "alloc", not(portable_atomic_no_alloc)))]
+#[allow(unused_extern_crates)]
 extern crate alloc;
 #[cfg(feature = "std")]
 extern crate std;
 #[cfg(all(feature = "std", portable_atomic_no_alloc))]
+#[allow(unused_extern_crates)]
 extern crate std as alloc;
 
-#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
-#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
-mod arc;
-#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
-pub use arc::{Arc, Weak};
+#[macro_use]
+mod utils;
+
+// The primitives other than spsc::Queue require atomic CAS.
+portable_atomic::cfg_has_atomic_cas! {
+    #[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    mod arc;
+    #[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+    pub use arc::{Arc, Weak};
+
+    #[cfg(not(portable_atomic_no_futures_api))]
+    #[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    pub mod task;
+
+    pub mod bitset;
+
+    #[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    pub mod epoch;
+
+    #[cfg(not(portable_atomic_no_min_const_generics))]
+    pub mod mpmc;
+
+    pub mod once_cell;
+
+    pub mod spin;
+
+    #[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+    #[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+    pub mod tagged;
+} // cfg_has_atomic_cas!
 
-#[cfg(not(portable_atomic_no_futures_api))]
-#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
-#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
-pub mod task;
+#[cfg(not(portable_atomic_no_min_const_generics))]
+pub mod spsc;
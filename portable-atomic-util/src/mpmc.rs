@@ -0,0 +1,282 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A bounded multi-producer multi-consumer queue.
+
+// This is based on Dmitry Vyukov's bounded MPMC queue, with the head/tail
+// encoding of crossbeam-queue's ArrayQueue.
+//
+// Refs:
+// - https://www.1024cores.net/home/lock-free-algorithms/queues/bounded-mpmc-queue
+// - https://github.com/crossbeam-rs/crossbeam/blob/crossbeam-queue-0.3.11/crossbeam-queue/src/array_queue.rs
+//
+// head and tail are split into an index (lower bits) and a lap (upper bits).
+// Each slot has a stamp, which is `tail + 1` after a push to it, and
+// `head + ONE_LAP` after a pop from it. A slot is ready to be pushed to if its
+// stamp equals tail, and ready to be popped from if its stamp equals head + 1.
+//
+// Initially, the stamp of slot `i` must be `i`. To make `new` a const fn that
+// initializes all stamps with the same value, stamps are stored relative to
+// the index of their slot.
+
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
+
+use portable_atomic::{hint, AtomicUsize, Backoff, Ordering};
+
+/// A bounded multi-producer multi-consumer lock-free queue backed by an array
+/// of `N` slots.
+///
+/// Any number of threads can push and pop elements concurrently through a
+/// shared reference. Since [`new`](Self::new) is a `const fn`, the queue can be
+/// placed in a `static`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::mpmc::Queue;
+///
+/// static QUEUE: Queue<i32, 2> = Queue::new();
+///
+/// assert_eq!(QUEUE.push(1), Ok(()));
+/// assert_eq!(QUEUE.push(2), Ok(()));
+/// assert_eq!(QUEUE.push(3), Err(3));
+/// assert_eq!(QUEUE.pop(), Some(1));
+/// assert_eq!(QUEUE.pop(), Some(2));
+/// assert_eq!(QUEUE.pop(), None);
+/// ```
+pub struct Queue<T, const N: usize> {
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    // The stamp of each slot minus the index of the slot.
+    stamps: [AtomicUsize; N],
+    buffer: UnsafeCell<MaybeUninit<[T; N]>>,
+}
+
+// SAFETY: each slot is accessed by only one thread at a time, which is
+// guaranteed by the stamps. Elements are sent between threads, so T: Send is required.
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
+// SAFETY: Queue<T, N> owns T.
+unsafe impl<T: Send, const N: usize> Send for Queue<T, N> {}
+
+impl<T, const N: usize> Queue<T, N> {
+    // The smallest power of two greater than N. The lower bits of head and
+    // tail below ONE_LAP are the index, and the remaining bits are the lap.
+    const ONE_LAP: usize = (N + 1).next_power_of_two();
+
+    /// The maximum number of elements the queue can hold, that is, `N`.
+    pub const CAPACITY: usize = N;
+
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty queue.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            #[cfg(not(portable_atomic_loom))]
+            const ZERO: AtomicUsize = AtomicUsize::new(0);
+            Self {
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+                #[cfg(not(portable_atomic_loom))]
+                stamps: [ZERO; N],
+                #[cfg(portable_atomic_loom)]
+                stamps: [(); N].map(|()| AtomicUsize::new(0)),
+                buffer: UnsafeCell::new(MaybeUninit::uninit()),
+            }
+        }
+    }
+
+    /// Returns the maximum number of elements the queue can hold, that is, `N`.
+    ///
+    /// This is the same as [`CAPACITY`](Self::CAPACITY).
+    #[allow(clippy::unused_self)] // takes self so that the capacity can be obtained without naming N
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        Self::CAPACITY
+    }
+
+    /// Adds an element to the back of the queue.
+    ///
+    /// Returns `Err(value)` if the queue is full.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        if N == 0 {
+            return Err(value);
+        }
+        let mut backoff = Backoff::new();
+        let mut tail = self.tail.load(Ordering::Relaxed);
+        loop {
+            let index = tail & (Self::ONE_LAP - 1);
+            let lap = tail & !(Self::ONE_LAP - 1);
+            let new_tail = if index + 1 < N { tail + 1 } else { lap.wrapping_add(Self::ONE_LAP) };
+
+            let stamp = self.stamp(index);
+            if tail == stamp {
+                // The slot is empty, so try to claim it.
+                match self.tail.compare_exchange_weak(
+                    tail,
+                    new_tail,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: we claimed the slot, and its previous value
+                        // has been popped (Acquire load of the stamp above).
+                        unsafe { self.slot(index).write(value) }
+                        self.set_stamp(index, tail + 1);
+                        return Ok(());
+                    }
+                    Err(t) => {
+                        tail = t;
+                        hint::spin_loop();
+                    }
+                }
+            } else if stamp.wrapping_add(Self::ONE_LAP) == tail + 1 {
+                // The slot still holds an element of the previous lap, so the
+                // queue may be full.
+                portable_atomic::fence(Ordering::SeqCst);
+                let head = self.head.load(Ordering::Relaxed);
+                if head.wrapping_add(Self::ONE_LAP) == tail {
+                    return Err(value);
+                }
+                backoff.snooze();
+                tail = self.tail.load(Ordering::Relaxed);
+            } else {
+                // Another thread has claimed the slot but not finished writing to it yet.
+                backoff.snooze();
+                tail = self.tail.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Removes the element at the front of the queue.
+    ///
+    /// Returns `None` if the queue is empty.
+    pub fn pop(&self) -> Option<T> {
+        if N == 0 {
+            return None;
+        }
+        let mut backoff = Backoff::new();
+        let mut head = self.head.load(Ordering::Relaxed);
+        loop {
+            let index = head & (Self::ONE_LAP - 1);
+            let lap = head & !(Self::ONE_LAP - 1);
+            let new_head = if index + 1 < N { head + 1 } else { lap.wrapping_add(Self::ONE_LAP) };
+
+            let stamp = self.stamp(index);
+            if head + 1 == stamp {
+                // The slot is full, so try to claim it.
+                match self.head.compare_exchange_weak(
+                    head,
+                    new_head,
+                    Ordering::SeqCst,
+                    Ordering::Relaxed,
+                ) {
+                    Ok(_) => {
+                        // SAFETY: we claimed the slot, and its value has been
+                        // pushed (Acquire load of the stamp above).
+                        let value = unsafe { self.slot(index).read() };
+                        self.set_stamp(index, head.wrapping_add(Self::ONE_LAP));
+                        return Some(value);
+                    }
+                    Err(h) => {
+                        head = h;
+                        hint::spin_loop();
+                    }
+                }
+            } else if stamp == head {
+                // The slot has already been popped in the previous lap, so the
+                // queue may be empty.
+                portable_atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
+                if tail == head {
+                    return None;
+                }
+                backoff.snooze();
+                head = self.head.load(Ordering::Relaxed);
+            } else {
+                // Another thread has claimed the slot but not finished reading from it yet.
+                backoff.snooze();
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    pub fn len(&self) -> usize {
+        loop {
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            // Retry if tail changed while loading head, to get a consistent snapshot.
+            if self.tail.load(Ordering::SeqCst) == tail {
+                let head_index = head & (Self::ONE_LAP - 1);
+                let tail_index = tail & (Self::ONE_LAP - 1);
+                return if head_index < tail_index {
+                    tail_index - head_index
+                } else if head_index > tail_index {
+                    N - head_index + tail_index
+                } else if tail == head {
+                    0
+                } else {
+                    N
+                };
+            }
+        }
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        let head = self.head.load(Ordering::SeqCst);
+        let tail = self.tail.load(Ordering::SeqCst);
+        tail == head
+    }
+
+    /// Returns `true` if the queue is full.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        let tail = self.tail.load(Ordering::SeqCst);
+        let head = self.head.load(Ordering::SeqCst);
+        N == 0 || head.wrapping_add(Self::ONE_LAP) == tail
+    }
+
+    #[inline]
+    fn stamp(&self, index: usize) -> usize {
+        self.stamps[index].load(Ordering::Acquire).wrapping_add(index)
+    }
+
+    #[inline]
+    fn set_stamp(&self, index: usize, stamp: usize) {
+        self.stamps[index].store(stamp.wrapping_sub(index), Ordering::Release);
+    }
+
+    #[inline]
+    fn slot(&self, index: usize) -> *mut T {
+        debug_assert!(index < N);
+        (self.buffer.get() as *mut T).wrapping_add(index)
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Queue<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("len", &self.len()).field("capacity", &N).finish()
+    }
+}
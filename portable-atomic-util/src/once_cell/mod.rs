@@ -0,0 +1,484 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Thread-safe cells that can be written to only once, and lazily initialized values.
+//!
+//! This module provides two flavors:
+//!
+//! - [`OnceCell`] (also available as [`OnceLock`]) and [`Lazy`] in this module
+//!   are *blocking*: if multiple threads try to initialize the cell at the same
+//!   time, only one of them runs the initialization function and the others
+//!   wait until it finishes. Waiting is done by spinning with
+//!   [`portable_atomic::Backoff`], which yields the current thread to the OS
+//!   scheduler if the `std` feature of portable-atomic is enabled.
+//! - The types in the [`race`] module never block: if multiple threads try to
+//!   initialize the cell at the same time, all of them may run the
+//!   initialization function, and the value of the first one to finish is used.
+//!
+//! All types are built on `portable_atomic::AtomicU8` and `portable_atomic::AtomicPtr`,
+//! so they are available on all targets where portable-atomic provides atomic
+//! CAS, including targets without native atomic CAS (e.g., thumbv6m, riscv32imc,
+//! and msp430) when the `critical-section` or `unsafe-assume-single-core`
+//! feature of portable-atomic is enabled.
+//!
+//! # Deadlocks
+//!
+//! Calling [`OnceCell::get_or_init`] on the same cell from its own
+//! initialization function, or from an interrupt handler that interrupted the
+//! initialization function on a single-core system, never returns.
+
+pub mod race;
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    convert::Infallible,
+    fmt, mem,
+    ops::{Deref, DerefMut},
+};
+
+use portable_atomic::{AtomicU8, Backoff, Ordering};
+
+const INCOMPLETE: u8 = 0;
+const RUNNING: u8 = 1;
+const COMPLETE: u8 = 2;
+
+/// A thread-safe cell which can be written to only once.
+///
+/// Unlike [`race::OnceBox`], concurrent callers of [`get_or_init`](Self::get_or_init)
+/// wait for the initialization function of the first caller instead of
+/// running their own. See the [module-level documentation](self) for details.
+///
+/// If the initialization function panics or returns an error, the cell remains
+/// uninitialized, and a later call can try to initialize it again.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::once_cell::OnceCell;
+///
+/// static CELL: OnceCell<String> = OnceCell::new();
+/// assert!(CELL.get().is_none());
+///
+/// std::thread::spawn(|| {
+///     let value: &String = CELL.get_or_init(|| "Hello, World!".to_string());
+///     assert_eq!(value, "Hello, World!");
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let value: Option<&String> = CELL.get();
+/// assert!(value.is_some());
+/// assert_eq!(value.unwrap().as_str(), "Hello, World!");
+/// ```
+pub struct OnceCell<T> {
+    state: AtomicU8,
+    value: UnsafeCell<Option<T>>,
+}
+
+/// An alias of [`OnceCell`], named after [`std::sync::OnceLock`].
+pub type OnceLock<T> = OnceCell<T>;
+
+// SAFETY: the value is only written once by the thread that changed the state
+// from INCOMPLETE to RUNNING, and only read after the state becomes COMPLETE.
+// Sending the value between threads requires `T: Send`, and sharing it requires `T: Sync`.
+unsafe impl<T: Sync + Send> Sync for OnceCell<T> {}
+// SAFETY: see above.
+unsafe impl<T: Send> Send for OnceCell<T> {}
+
+#[cfg(not(portable_atomic_no_core_unwind_safe))]
+impl<T: core::panic::RefUnwindSafe + core::panic::UnwindSafe> core::panic::RefUnwindSafe
+    for OnceCell<T>
+{
+}
+#[cfg(all(portable_atomic_no_core_unwind_safe, feature = "std"))]
+impl<T: std::panic::RefUnwindSafe + std::panic::UnwindSafe> std::panic::RefUnwindSafe
+    for OnceCell<T>
+{
+}
+
+impl<T> Default for OnceCell<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_tuple("OnceCell");
+        match self.get() {
+            Some(v) => d.field(v),
+            None => d.field(&format_args!("<uninit>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: Clone> Clone for OnceCell<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match self.get() {
+            Some(value) => Self::from(value.clone()),
+            None => Self::new(),
+        }
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self { state: AtomicU8::new(COMPLETE), value: UnsafeCell::new(Some(value)) }
+    }
+}
+
+impl<T: PartialEq> PartialEq for OnceCell<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.get() == other.get()
+    }
+}
+
+impl<T: Eq> Eq for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty cell.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self { state: AtomicU8::new(INCOMPLETE), value: UnsafeCell::new(None) }
+        }
+    }
+
+    /// Gets the reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty, or being initialized. This method
+    /// never blocks.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Ordering::Acquire) == COMPLETE {
+            // SAFETY: the state is COMPLETE, so the value has been initialized.
+            Some(unsafe { self.get_unchecked() })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the mutable reference to the underlying value.
+    ///
+    /// Returns `None` if the cell is empty.
+    #[inline]
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: we have a unique reference to the cell.
+        unsafe { &mut *self.value.get() }.as_mut()
+    }
+
+    /// Sets the contents of this cell to `value`.
+    ///
+    /// Returns `Ok(())` if the cell was empty and `Err(value)` if it was full.
+    /// If the cell is being initialized, this blocks until the initialization finishes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic_util::once_cell::OnceCell;
+    ///
+    /// static CELL: OnceCell<i32> = OnceCell::new();
+    ///
+    /// assert!(CELL.get().is_none());
+    /// assert_eq!(CELL.set(92), Ok(()));
+    /// assert_eq!(CELL.set(62), Err(62));
+    /// assert_eq!(CELL.get(), Some(&92));
+    /// ```
+    #[inline]
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| match value.take() {
+            Some(value) => value,
+            None => unreachable!(),
+        });
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    ///
+    /// If multiple threads call this at the same time, only one of them runs `f`,
+    /// and the others block until it finishes.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell remains uninitialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic_util::once_cell::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// let value = cell.get_or_init(|| 92);
+    /// assert_eq!(value, &92);
+    /// let value = cell.get_or_init(|| unreachable!());
+    /// assert_eq!(value, &92);
+    /// ```
+    #[inline]
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        match self.get_or_try_init(|| Ok::<T, Infallible>(f())) {
+            Ok(value) => value,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    /// If the cell was empty and `f` failed, an error is returned.
+    ///
+    /// If multiple threads call this at the same time, only one of them runs `f`,
+    /// and the others block until it finishes. If `f` fails, one of the blocked
+    /// threads runs its own `f`.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the cell remains uninitialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic_util::once_cell::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    /// assert!(cell.get().is_none());
+    /// let value = cell.get_or_try_init(|| -> Result<i32, ()> { Ok(92) });
+    /// assert_eq!(value, Ok(&92));
+    /// assert_eq!(cell.get(), Some(&92))
+    /// ```
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        self.initialize(f)?;
+        // SAFETY: initialize returned Ok, so the value has been initialized.
+        Ok(unsafe { self.get_unchecked() })
+    }
+
+    /// Consumes the cell, returning the wrapped value.
+    ///
+    /// Returns `None` if the cell was empty.
+    #[inline]
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+
+    /// Takes the value out of this cell, moving it back to an uninitialized state.
+    ///
+    /// Has no effect and returns `None` if the cell hasn't been initialized.
+    #[inline]
+    pub fn take(&mut self) -> Option<T> {
+        mem::replace(self, Self::new()).into_inner()
+    }
+
+    #[cold]
+    fn initialize<F, E>(&self, f: F) -> Result<(), E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let mut backoff = Backoff::new();
+        loop {
+            match self.state.compare_exchange_weak(
+                INCOMPLETE,
+                RUNNING,
+                Ordering::Acquire,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    // Resets the state if f panics or returns an error.
+                    let guard = ResetOnDrop { state: &self.state };
+                    let value = f()?;
+                    // SAFETY: we changed the state to RUNNING, so no other thread
+                    // accesses the value until we change the state to COMPLETE.
+                    unsafe { *self.value.get() = Some(value) }
+                    mem::forget(guard);
+                    self.state.store(COMPLETE, Ordering::Release);
+                    return Ok(());
+                }
+                Err(COMPLETE) => return Ok(()),
+                // Another thread is running the initialization function, or the
+                // compare_exchange_weak failed spuriously.
+                Err(_) => backoff.snooze(),
+            }
+        }
+    }
+
+    // SAFETY: the caller must ensure that the value has been initialized.
+    unsafe fn get_unchecked(&self) -> &T {
+        // SAFETY: the caller must uphold the safety contract.
+        match unsafe { &*self.value.get() } {
+            Some(value) => value,
+            // SAFETY: the caller must uphold the safety contract.
+            None => unsafe { core::hint::unreachable_unchecked() },
+        }
+    }
+}
+
+struct ResetOnDrop<'a> {
+    state: &'a AtomicU8,
+}
+
+impl Drop for ResetOnDrop<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        self.state.store(INCOMPLETE, Ordering::Release);
+    }
+}
+
+/// A value which is initialized on the first access.
+///
+/// This uses [`OnceCell`], so if multiple threads access the value at the same
+/// time, only one of them runs the initialization function and the others block.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use portable_atomic_util::once_cell::Lazy;
+///
+/// static HASHMAP: Lazy<HashMap<i32, String>> = Lazy::new(|| {
+///     let mut m = HashMap::new();
+///     m.insert(13, "Spica".to_string());
+///     m.insert(74, "Hoyten".to_string());
+///     m
+/// });
+///
+/// assert_eq!(HASHMAP.get(&13).map(|s| s.as_str()), Some("Spica"));
+/// ```
+pub struct Lazy<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    init: Cell<Option<F>>,
+}
+
+// SAFETY: `init` is only accessed by the thread that initializes the cell.
+unsafe impl<T, F: Send> Sync for Lazy<T, F> where OnceCell<T>: Sync {}
+
+#[cfg(not(portable_atomic_no_core_unwind_safe))]
+impl<T, F: core::panic::UnwindSafe> core::panic::RefUnwindSafe for Lazy<T, F> where
+    OnceCell<T>: core::panic::RefUnwindSafe
+{
+}
+#[cfg(all(portable_atomic_no_core_unwind_safe, feature = "std"))]
+impl<T, F: std::panic::UnwindSafe> std::panic::RefUnwindSafe for Lazy<T, F> where
+    OnceCell<T>: std::panic::RefUnwindSafe
+{
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Lazy<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy").field("cell", &self.cell).field("init", &"..").finish()
+    }
+}
+
+impl<T: Default> Default for Lazy<T> {
+    /// Creates a new lazy value using `Default` as the initializing function.
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+impl<T, F> Lazy<T, F> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new lazy value with the given initializing function.
+        #[inline]
+        #[must_use]
+        pub const fn new(init: F) -> Self {
+            Self { cell: OnceCell::new(), init: Cell::new(Some(init)) }
+        }
+    }
+
+    /// Consumes this `Lazy` returning the stored value.
+    ///
+    /// Returns `Err(init)` with the initializing function if the value hasn't been initialized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the initializing function has previously panicked.
+    #[inline]
+    pub fn into_value(self) -> Result<T, F> {
+        match self.cell.into_inner() {
+            Some(value) => Ok(value),
+            None => match self.init.into_inner() {
+                Some(init) => Err(init),
+                None => panic!("Lazy instance has previously been poisoned"),
+            },
+        }
+    }
+
+    /// Gets the reference to the value, or `None` if it hasn't been initialized.
+    #[inline]
+    pub fn get(this: &Self) -> Option<&T> {
+        this.cell.get()
+    }
+
+    /// Gets the mutable reference to the value, or `None` if it hasn't been initialized.
+    #[inline]
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        this.cell.get_mut()
+    }
+}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    /// Forces the evaluation of this lazy value and returns a reference to the result.
+    ///
+    /// This is equivalent to the `Deref` impl, but is explicit.
+    ///
+    /// # Panics
+    ///
+    /// If the initializing function panics, the panic is propagated to the
+    /// caller, and all subsequent accesses panic.
+    #[inline]
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| match this.init.take() {
+            Some(f) => f(),
+            None => panic!("Lazy instance has previously been poisoned"),
+        })
+    }
+
+    /// Forces the evaluation of this lazy value and returns a mutable reference to the result.
+    ///
+    /// # Panics
+    ///
+    /// If the initializing function panics, the panic is propagated to the
+    /// caller, and all subsequent accesses panic.
+    #[inline]
+    pub fn force_mut(this: &mut Self) -> &mut T {
+        Self::force(this);
+        match this.cell.get_mut() {
+            Some(value) => value,
+            None => unreachable!(),
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
+
+impl<T, F: FnOnce() -> T> DerefMut for Lazy<T, F> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        Self::force_mut(self)
+    }
+}
@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Thread-safe cells that can be written to only once, which never block.
+//!
+//! If multiple threads try to initialize a cell in this module at the same time,
+//! all of them may run the initialization function, and the value of the first
+//! one to finish is used. The values of the others are dropped.
+//!
+//! These types store a pointer to the value in a `portable_atomic::AtomicPtr`,
+//! so they can be initialized with a single atomic CAS.
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+use core::{convert::Infallible, ops::Deref};
+use core::{fmt, marker::PhantomData, ptr, ptr::NonNull};
+
+use portable_atomic::{AtomicPtr, Ordering};
+
+/// A thread-safe cell which can be written to only once, and stores a `&'a T`.
+///
+/// This does not require `alloc`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::once_cell::race::OnceRef;
+///
+/// static VALUE: i32 = 92;
+/// static CELL: OnceRef<'static, i32> = OnceRef::new();
+///
+/// assert_eq!(CELL.get_or_init(|| &VALUE), &92);
+/// assert_eq!(CELL.set(&0), Err(()));
+/// ```
+pub struct OnceRef<'a, T> {
+    inner: AtomicPtr<T>,
+    // Makes this type Send and Sync only if `&'a T` is.
+    _marker: PhantomData<Option<&'a T>>,
+}
+
+impl<T> Default for OnceRef<'_, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceRef").field(&self.get()).finish()
+    }
+}
+
+impl<'a, T> OnceRef<'a, T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty cell.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self { inner: AtomicPtr::new(ptr::null_mut()), _marker: PhantomData }
+        }
+    }
+
+    /// Gets a reference to the underlying value.
+    #[inline]
+    pub fn get(&self) -> Option<&'a T> {
+        let ptr = self.inner.load(Ordering::Acquire);
+        // SAFETY: the pointer is either null or was created from a `&'a T`.
+        unsafe { ptr.as_ref() }
+    }
+
+    /// Sets the contents of this cell to `value`.
+    ///
+    /// Returns `Ok(())` if the cell was empty and `Err(())` if it was full.
+    #[allow(clippy::result_unit_err)]
+    #[inline]
+    pub fn set(&self, value: &'a T) -> Result<(), ()> {
+        let ptr = NonNull::from(value).as_ptr();
+        match self.inner.compare_exchange(
+            ptr::null_mut(),
+            ptr,
+            Ordering::Release,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => Ok(()),
+            Err(_) => Err(()),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    ///
+    /// If several threads concurrently run `get_or_init`, more than one `f` can be
+    /// called. However, all threads will return the same value, produced by
+    /// some `f`.
+    #[inline]
+    pub fn get_or_init<F>(&self, f: F) -> &'a T
+    where
+        F: FnOnce() -> &'a T,
+    {
+        match self.get() {
+            Some(value) => value,
+            None => self.init(f()),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    /// If the cell was empty and `f` failed, an error is returned.
+    ///
+    /// If several threads concurrently run `get_or_try_init`, more than one `f` can be
+    /// called. However, all threads will return the same value, produced by
+    /// some `f`.
+    #[inline]
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&'a T, E>
+    where
+        F: FnOnce() -> Result<&'a T, E>,
+    {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => Ok(self.init(f()?)),
+        }
+    }
+
+    #[cold]
+    fn init(&self, value: &'a T) -> &'a T {
+        let ptr = NonNull::from(value).as_ptr();
+        let res =
+            self.inner.compare_exchange(ptr::null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire);
+        match res {
+            Ok(_) => value,
+            // SAFETY: the pointer is non-null and was created from a `&'a T`.
+            Err(old) => unsafe { &*old },
+        }
+    }
+}
+
+/// A thread-safe cell which can be written to only once, and stores a `Box<T>`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::once_cell::race::OnceBox;
+///
+/// static CELL: OnceBox<String> = OnceBox::new();
+///
+/// let value = CELL.get_or_init(|| Box::new("Hello, World!".to_string()));
+/// assert_eq!(value, "Hello, World!");
+/// assert!(CELL.set(Box::new(String::new())).is_err());
+/// ```
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+pub struct OnceBox<T> {
+    inner: AtomicPtr<T>,
+    _marker: PhantomData<Option<Box<T>>>,
+}
+
+// The value may be created by one thread and dropped by another, so sharing
+// OnceBox<T> between threads requires T: Send in addition to T: Sync.
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+// SAFETY: see above.
+unsafe impl<T: Sync + Send> Sync for OnceBox<T> {}
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+// SAFETY: OnceBox<T> owns a Box<T>.
+unsafe impl<T: Send> Send for OnceBox<T> {}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T> Default for OnceBox<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T: fmt::Debug> fmt::Debug for OnceBox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("OnceBox").field(&self.get()).finish()
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T> Drop for OnceBox<T> {
+    fn drop(&mut self) {
+        let ptr = *self.inner.get_mut();
+        if !ptr.is_null() {
+            // SAFETY: the pointer is non-null and was created by Box::into_raw.
+            drop(unsafe { Box::from_raw(ptr) });
+        }
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T> OnceBox<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty cell.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self { inner: AtomicPtr::new(ptr::null_mut()), _marker: PhantomData }
+        }
+    }
+
+    /// Gets a reference to the underlying value.
+    #[inline]
+    pub fn get(&self) -> Option<&T> {
+        let ptr = self.inner.load(Ordering::Acquire);
+        // SAFETY: the pointer is either null or was created by Box::into_raw,
+        // and the box is not dropped until self is dropped.
+        unsafe { ptr.as_ref() }
+    }
+
+    /// Sets the contents of this cell to `value`.
+    ///
+    /// Returns `Ok(())` if the cell was empty and `Err(value)` if it was full.
+    #[inline]
+    pub fn set(&self, value: Box<T>) -> Result<(), Box<T>> {
+        let ptr = Box::into_raw(value);
+        match self.inner.compare_exchange(
+            ptr::null_mut(),
+            ptr,
+            Ordering::Release,
+            Ordering::Relaxed,
+        ) {
+            Ok(_) => Ok(()),
+            // SAFETY: the pointer was created by Box::into_raw above, and has not been shared.
+            Err(_) => Err(unsafe { Box::from_raw(ptr) }),
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    ///
+    /// If several threads concurrently run `get_or_init`, more than one `f` can be
+    /// called. However, all threads will return the same value, produced by
+    /// some `f`.
+    #[inline]
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> Box<T>,
+    {
+        match self.get_or_try_init(|| Ok::<Box<T>, Infallible>(f())) {
+            Ok(value) => value,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Gets the contents of the cell, initializing it with `f` if the cell was empty.
+    /// If the cell was empty and `f` failed, an error is returned.
+    ///
+    /// If several threads concurrently run `get_or_try_init`, more than one `f` can be
+    /// called. However, all threads will return the same value, produced by
+    /// some `f`.
+    #[inline]
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Result<Box<T>, E>,
+    {
+        match self.get() {
+            Some(value) => Ok(value),
+            None => Ok(self.init(f()?)),
+        }
+    }
+
+    /// Consumes the cell, returning the wrapped value.
+    ///
+    /// Returns `None` if the cell was empty.
+    #[inline]
+    pub fn into_inner(self) -> Option<Box<T>> {
+        let mut this = core::mem::ManuallyDrop::new(self);
+        let ptr = *this.inner.get_mut();
+        if ptr.is_null() {
+            None
+        } else {
+            // SAFETY: the pointer is non-null and was created by Box::into_raw,
+            // and self is not dropped.
+            Some(unsafe { Box::from_raw(ptr) })
+        }
+    }
+
+    #[cold]
+    fn init(&self, value: Box<T>) -> &T {
+        let ptr = Box::into_raw(value);
+        let res =
+            self.inner.compare_exchange(ptr::null_mut(), ptr, Ordering::AcqRel, Ordering::Acquire);
+        match res {
+            // SAFETY: the pointer was created by Box::into_raw above, and the
+            // box is not dropped until self is dropped.
+            Ok(_) => unsafe { &*ptr },
+            Err(old) => {
+                // SAFETY: the pointer was created by Box::into_raw above, and has not been shared.
+                drop(unsafe { Box::from_raw(ptr) });
+                // SAFETY: the pointer is non-null and was created by Box::into_raw,
+                // and the box is not dropped until self is dropped.
+                unsafe { &*old }
+            }
+        }
+    }
+}
+
+/// A value which is initialized on the first access.
+///
+/// This uses [`OnceBox`], so if multiple threads access the value at the same
+/// time, more than one of them may run the initialization function. Therefore,
+/// the initialization function must implement `Fn` instead of `FnOnce`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::once_cell::race::Lazy;
+///
+/// static GREETING: Lazy<String> = Lazy::new(|| "Hello, World!".to_string());
+///
+/// assert_eq!(*GREETING, "Hello, World!");
+/// ```
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+#[cfg_attr(docsrs, doc(cfg(any(feature = "alloc", feature = "std"))))]
+pub struct Lazy<T, F = fn() -> T> {
+    cell: OnceBox<T>,
+    init: F,
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T: fmt::Debug, F> fmt::Debug for Lazy<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy").field("cell", &self.cell).field("init", &"..").finish()
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T: Default> Default for Lazy<T> {
+    /// Creates a new lazy value using `Default` as the initializing function.
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default)
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T, F> Lazy<T, F> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new lazy value with the given initializing function.
+        #[inline]
+        #[must_use]
+        pub const fn new(init: F) -> Self {
+            Self { cell: OnceBox::new(), init }
+        }
+    }
+
+    /// Gets the reference to the value, or `None` if it hasn't been initialized.
+    #[inline]
+    pub fn get(this: &Self) -> Option<&T> {
+        this.cell.get()
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T, F: Fn() -> T> Lazy<T, F> {
+    /// Forces the evaluation of this lazy value and returns a reference to the result.
+    ///
+    /// This is equivalent to the `Deref` impl, but is explicit.
+    #[inline]
+    pub fn force(this: &Self) -> &T {
+        this.cell.get_or_init(|| Box::new((this.init)()))
+    }
+}
+
+#[cfg(any(all(feature = "alloc", not(portable_atomic_no_alloc)), feature = "std"))]
+impl<T, F: Fn() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        Self::force(self)
+    }
+}
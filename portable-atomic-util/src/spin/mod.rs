@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Spin-based synchronization primitives.
+//!
+//! The locks in this module wait by busy-looping with
+//! [`portable_atomic::hint::spin_loop`], and never block the thread or yield to
+//! the OS scheduler. This makes them usable in `no_std` environments, but they
+//! should only be used to protect short critical sections.
+//!
+//! Unlike the locks in the standard library, these locks are not poisoned: if a
+//! thread panics while holding a lock, the lock is released when the guard is
+//! dropped during unwinding, and other threads can acquire it as usual.
+//!
+//! - [`Mutex`]: A mutual exclusion lock. Waiting threads may acquire the lock in any order.
+//! - [`TicketMutex`]: A mutual exclusion lock that is acquired in first-come, first-served order.
+//! - [`RwLock`]: A reader-writer lock. Writers may starve if readers hold the lock continuously.
+//! - [`Once`]: A primitive to run a one-time initialization.
+//!
+//! # Deadlocks
+//!
+//! Acquiring a lock that the current thread already holds, or acquiring a lock
+//! from an interrupt handler that interrupted the holder of the lock on a
+//! single-core system, never returns.
+
+mod mutex;
+mod rw_lock;
+mod ticket;
+
+pub use self::{
+    mutex::{Mutex, MutexGuard},
+    rw_lock::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+    ticket::{TicketMutex, TicketMutexGuard},
+};
+
+use core::fmt;
+
+use crate::once_cell::OnceCell;
+
+/// A synchronization primitive which can be used to run a one-time initialization.
+///
+/// This is a spin-based equivalent of [`std::sync::Once`], built on
+/// [`OnceCell<()>`](OnceCell), except that it is not poisoned if the
+/// initialization function panics; the next call runs its initialization function instead.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::spin::Once;
+///
+/// static INIT: Once = Once::new();
+///
+/// INIT.call_once(|| {
+///     // run initialization here
+/// });
+/// assert!(INIT.is_completed());
+/// ```
+pub struct Once {
+    cell: OnceCell<()>,
+}
+
+impl Default for Once {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Once {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Once").field("completed", &self.is_completed()).finish()
+    }
+}
+
+impl Once {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new `Once` value.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self { cell: OnceCell::new() }
+        }
+    }
+
+    /// Performs an initialization routine once and only once.
+    ///
+    /// If another thread is running an initialization routine, this waits
+    /// until it finishes. When this function returns, it is guaranteed that
+    /// some initialization has run and completed.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated to the caller, and the `Once`
+    /// remains incomplete.
+    #[inline]
+    pub fn call_once<F>(&self, f: F)
+    where
+        F: FnOnce(),
+    {
+        self.cell.get_or_init(f);
+    }
+
+    /// Returns `true` if some [`call_once`](Self::call_once) call has completed successfully.
+    #[inline]
+    pub fn is_completed(&self) -> bool {
+        self.cell.get().is_some()
+    }
+}
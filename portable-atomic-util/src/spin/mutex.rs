@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use portable_atomic::{hint, AtomicBool, Ordering};
+
+/// A spin-based mutual exclusion lock.
+///
+/// If multiple threads are waiting for the lock, any of them may acquire it
+/// when it is released. Use [`TicketMutex`](super::TicketMutex) if first-come,
+/// first-served order is needed.
+///
+/// # Examples
+///
+/// ```
+/// use std::{sync::Arc, thread};
+///
+/// use portable_atomic_util::spin::Mutex;
+///
+/// let data = Arc::new(Mutex::new(0));
+/// let threads: Vec<_> = (0..4)
+///     .map(|_| {
+///         let data = data.clone();
+///         thread::spawn(move || *data.lock() += 1)
+///     })
+///     .collect();
+/// for t in threads {
+///     t.join().unwrap();
+/// }
+/// assert_eq!(*data.lock(), 4);
+/// ```
+pub struct Mutex<T: ?Sized> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the data is only accessed through a guard, and only one guard exists at a time.
+unsafe impl<T: ?Sized + Send> Sync for Mutex<T> {}
+// SAFETY: Mutex<T> owns T.
+unsafe impl<T: ?Sized + Send> Send for Mutex<T> {}
+
+/// A guard that provides mutable access to the data protected by a [`Mutex`].
+///
+/// The lock is released when the guard is dropped.
+#[must_use = "if unused the Mutex will immediately unlock"]
+pub struct MutexGuard<'a, T: ?Sized> {
+    lock: &'a Mutex<T>,
+    // Makes this type Send and Sync only if `&'a mut T` is.
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> Mutex<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new mutex in an unlocked state.
+        #[inline]
+        #[must_use]
+        pub const fn new(value: T) -> Self {
+            Self { locked: AtomicBool::new(false), data: UnsafeCell::new(value) }
+        }
+    }
+
+    /// Consumes this mutex, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> Mutex<T> {
+    /// Acquires the lock, spinning until it is available.
+    #[inline]
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+            // Wait until the lock looks unlocked before trying again, to avoid
+            // invalidating the cache line of the holder of the lock.
+            while self.is_locked() {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    /// Attempts to acquire the lock.
+    ///
+    /// Returns `None` if the lock is held by another guard. This function never spins.
+    #[inline]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        if self.locked.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            Some(MutexGuard { lock: self, _marker: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the lock is currently held.
+    ///
+    /// This is only a hint: the lock may be acquired or released by other threads
+    /// right after this function returns.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(Ordering::Relaxed)
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the mutex mutably, no actual locking needs to take place.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: we have a unique reference to the mutex.
+        unsafe { &mut *self.data.get() }
+    }
+}
+
+impl<T: Default> Default for Mutex<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for Mutex<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for Mutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Mutex");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: ?Sized> Deref for MutexGuard<'_, T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for MutexGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for MutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
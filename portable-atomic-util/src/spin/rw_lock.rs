@@ -0,0 +1,276 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use portable_atomic::{hint, AtomicUsize, Ordering};
+
+// The lowest bit of the state is set while a writer holds the lock, and the
+// remaining bits count the readers.
+const WRITER: usize = 1;
+const READER: usize = 2;
+const MAX_READERS: usize = !WRITER;
+
+/// A spin-based reader-writer lock.
+///
+/// This allows any number of readers or at most one writer to hold the lock at
+/// the same time. Readers and writers are not queued, so writers may starve if
+/// readers hold the lock continuously.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::spin::RwLock;
+///
+/// let lock = RwLock::new(5);
+/// {
+///     let r1 = lock.read();
+///     let r2 = lock.read();
+///     assert_eq!(*r1 + *r2, 10);
+///     assert!(lock.try_write().is_none());
+/// }
+/// *lock.write() += 1;
+/// assert_eq!(*lock.read(), 6);
+/// ```
+pub struct RwLock<T: ?Sized> {
+    state: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: readers share &T between threads, and writers get &mut T, so both
+// T: Send and T: Sync are required.
+unsafe impl<T: ?Sized + Send + Sync> Sync for RwLock<T> {}
+// SAFETY: RwLock<T> owns T.
+unsafe impl<T: ?Sized + Send> Send for RwLock<T> {}
+
+/// A guard that provides shared access to the data protected by a [`RwLock`].
+///
+/// The read lock is released when the guard is dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockReadGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    // Makes this type Send and Sync only if `&'a T` is.
+    _marker: PhantomData<&'a T>,
+}
+
+/// A guard that provides mutable access to the data protected by a [`RwLock`].
+///
+/// The write lock is released when the guard is dropped.
+#[must_use = "if unused the RwLock will immediately unlock"]
+pub struct RwLockWriteGuard<'a, T: ?Sized> {
+    lock: &'a RwLock<T>,
+    // Makes this type Send and Sync only if `&'a mut T` is.
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> RwLock<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new reader-writer lock in an unlocked state.
+        #[inline]
+        #[must_use]
+        pub const fn new(value: T) -> Self {
+            Self { state: AtomicUsize::new(0), data: UnsafeCell::new(value) }
+        }
+    }
+
+    /// Consumes this lock, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> RwLock<T> {
+    /// Acquires a read lock, spinning until no writer holds the lock.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of readers overflows.
+    #[inline]
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_read() {
+                return guard;
+            }
+            while self.state.load(Ordering::Relaxed) & WRITER != 0 {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    /// Attempts to acquire a read lock.
+    ///
+    /// Returns `None` if a writer holds the lock. This function never spins
+    /// while a writer holds the lock, but may retry if other readers acquire or
+    /// release the lock at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of readers overflows.
+    #[inline]
+    pub fn try_read(&self) -> Option<RwLockReadGuard<'_, T>> {
+        let mut state = self.state.load(Ordering::Relaxed);
+        loop {
+            if state & WRITER != 0 {
+                return None;
+            }
+            assert!(state < MAX_READERS, "too many readers of RwLock");
+            match self.state.compare_exchange_weak(
+                state,
+                state + READER,
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return Some(RwLockReadGuard { lock: self, _marker: PhantomData }),
+                Err(s) => state = s,
+            }
+        }
+    }
+
+    /// Acquires a write lock, spinning until no reader or writer holds the lock.
+    #[inline]
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        loop {
+            if let Some(guard) = self.try_write() {
+                return guard;
+            }
+            while self.state.load(Ordering::Relaxed) != 0 {
+                hint::spin_loop();
+            }
+        }
+    }
+
+    /// Attempts to acquire a write lock.
+    ///
+    /// Returns `None` if a reader or writer holds the lock. This function never spins.
+    #[inline]
+    pub fn try_write(&self) -> Option<RwLockWriteGuard<'_, T>> {
+        if self.state.compare_exchange(0, WRITER, Ordering::Acquire, Ordering::Relaxed).is_ok() {
+            Some(RwLockWriteGuard { lock: self, _marker: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if a writer currently holds the lock.
+    ///
+    /// This is only a hint: the lock may be acquired or released by other threads
+    /// right after this function returns.
+    #[inline]
+    pub fn is_locked_exclusive(&self) -> bool {
+        self.state.load(Ordering::Relaxed) & WRITER != 0
+    }
+
+    /// Returns the number of readers that currently hold the lock.
+    ///
+    /// This is only a hint: the lock may be acquired or released by other threads
+    /// right after this function returns.
+    #[inline]
+    pub fn reader_count(&self) -> usize {
+        self.state.load(Ordering::Relaxed) / READER
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the lock mutably, no actual locking needs to take place.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: we have a unique reference to the lock.
+        unsafe { &mut *self.data.get() }
+    }
+}
+
+impl<T: Default> Default for RwLock<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for RwLock<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("RwLock");
+        match self.try_read() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockReadGuard<'_, T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds a read lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockReadGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        self.lock.state.fetch_sub(READER, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Deref for RwLockWriteGuard<'_, T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the write lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for RwLockWriteGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the write lock.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for RwLockWriteGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // No reader can acquire the lock while the writer holds it, so the state is WRITER.
+        self.lock.state.store(0, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for RwLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
@@ -0,0 +1,189 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::{
+    cell::UnsafeCell,
+    fmt,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use portable_atomic::{hint, AtomicUsize, Ordering};
+
+/// A spin-based mutual exclusion lock that is acquired in first-come, first-served order.
+///
+/// Each call to [`lock`](Self::lock) takes a ticket, and waits until that
+/// ticket is served, so no waiting thread can starve. On the other hand, if the
+/// thread that is next in line is not running, no other thread can acquire the
+/// lock either, so this may perform worse than [`Mutex`](super::Mutex) when
+/// there are more threads than CPU cores.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::spin::TicketMutex;
+///
+/// let lock = TicketMutex::new(0);
+/// *lock.lock() += 1;
+/// assert_eq!(*lock.try_lock().unwrap(), 1);
+/// ```
+pub struct TicketMutex<T: ?Sized> {
+    next_ticket: AtomicUsize,
+    next_serving: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: the data is only accessed through a guard, and only one guard exists at a time.
+unsafe impl<T: ?Sized + Send> Sync for TicketMutex<T> {}
+// SAFETY: TicketMutex<T> owns T.
+unsafe impl<T: ?Sized + Send> Send for TicketMutex<T> {}
+
+/// A guard that provides mutable access to the data protected by a [`TicketMutex`].
+///
+/// The lock is released when the guard is dropped.
+#[must_use = "if unused the TicketMutex will immediately unlock"]
+pub struct TicketMutexGuard<'a, T: ?Sized> {
+    lock: &'a TicketMutex<T>,
+    // Makes this type Send and Sync only if `&'a mut T` is.
+    _marker: PhantomData<&'a mut T>,
+}
+
+impl<T> TicketMutex<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new mutex in an unlocked state.
+        #[inline]
+        #[must_use]
+        pub const fn new(value: T) -> Self {
+            Self {
+                next_ticket: AtomicUsize::new(0),
+                next_serving: AtomicUsize::new(0),
+                data: UnsafeCell::new(value),
+            }
+        }
+    }
+
+    /// Consumes this mutex, returning the underlying data.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.data.into_inner()
+    }
+}
+
+impl<T: ?Sized> TicketMutex<T> {
+    /// Acquires the lock, spinning until all threads that called `lock` earlier
+    /// have released it.
+    #[inline]
+    pub fn lock(&self) -> TicketMutexGuard<'_, T> {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        while self.next_serving.load(Ordering::Acquire) != ticket {
+            hint::spin_loop();
+        }
+        TicketMutexGuard { lock: self, _marker: PhantomData }
+    }
+
+    /// Attempts to acquire the lock.
+    ///
+    /// Returns `None` if the lock is held by another guard or other threads are
+    /// waiting for it. This function never spins.
+    #[inline]
+    pub fn try_lock(&self) -> Option<TicketMutexGuard<'_, T>> {
+        let ticket = self.next_ticket.load(Ordering::Relaxed);
+        if self.next_serving.load(Ordering::Acquire) == ticket
+            && self
+                .next_ticket
+                .compare_exchange(
+                    ticket,
+                    ticket.wrapping_add(1),
+                    Ordering::Acquire,
+                    Ordering::Relaxed,
+                )
+                .is_ok()
+        {
+            Some(TicketMutexGuard { lock: self, _marker: PhantomData })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the lock is currently held.
+    ///
+    /// This is only a hint: the lock may be acquired or released by other threads
+    /// right after this function returns.
+    #[inline]
+    pub fn is_locked(&self) -> bool {
+        self.next_ticket.load(Ordering::Relaxed) != self.next_serving.load(Ordering::Relaxed)
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the mutex mutably, no actual locking needs to take place.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        // SAFETY: we have a unique reference to the mutex.
+        unsafe { &mut *self.data.get() }
+    }
+}
+
+impl<T: Default> Default for TicketMutex<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(T::default())
+    }
+}
+
+impl<T> From<T> for TicketMutex<T> {
+    #[inline]
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TicketMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("TicketMutex");
+        match self.try_lock() {
+            Some(guard) => d.field("data", &&*guard),
+            None => d.field("data", &format_args!("<locked>")),
+        };
+        d.finish()
+    }
+}
+
+impl<T: ?Sized> Deref for TicketMutexGuard<'_, T> {
+    type Target = T;
+    #[inline]
+    fn deref(&self) -> &T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for TicketMutexGuard<'_, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard holds the lock.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T: ?Sized> Drop for TicketMutexGuard<'_, T> {
+    #[inline]
+    fn drop(&mut self) {
+        // Only the holder of the lock writes next_serving, so a plain load and
+        // store would also work, but fetch_add wraps around in the same way as
+        // the fetch_add in lock.
+        self.lock.next_serving.fetch_add(1, Ordering::Release);
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TicketMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for TicketMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
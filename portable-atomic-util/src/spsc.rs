@@ -0,0 +1,309 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! A bounded single-producer single-consumer queue.
+//!
+//! This queue only uses atomic load and store, so it is suitable for
+//! communication between an interrupt handler and a task on targets without
+//! atomic CAS.
+
+use core::{cell::UnsafeCell, fmt, mem::MaybeUninit};
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+/// A bounded single-producer single-consumer lock-free queue backed by an array
+/// of `N` slots.
+///
+/// One slot is always kept empty to distinguish a full queue from an empty one,
+/// so the queue can hold at most `N - 1` elements.
+///
+/// Elements are enqueued through a [`Producer`] and dequeued through a
+/// [`Consumer`], which are obtained by [`split`](Self::split) or
+/// [`split_unchecked`](Self::split_unchecked). Since [`new`](Self::new) is a
+/// `const fn`, the queue can be placed in a `static`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::spsc::Queue;
+///
+/// let mut queue: Queue<i32, 4> = Queue::new();
+/// let (mut producer, mut consumer) = queue.split();
+/// assert_eq!(producer.enqueue(1), Ok(()));
+/// assert_eq!(producer.enqueue(2), Ok(()));
+/// assert_eq!(consumer.dequeue(), Some(1));
+/// assert_eq!(consumer.dequeue(), Some(2));
+/// assert_eq!(consumer.dequeue(), None);
+/// ```
+pub struct Queue<T, const N: usize> {
+    // The index of the next slot to dequeue. Written only by the consumer.
+    head: AtomicUsize,
+    // The index of the next slot to enqueue. Written only by the producer.
+    tail: AtomicUsize,
+    buffer: UnsafeCell<MaybeUninit<[T; N]>>,
+}
+
+// SAFETY: slots between head and tail are only accessed by the consumer, and
+// the others are only accessed by the producer. Elements are sent from the
+// producer to the consumer, so T: Send is required.
+unsafe impl<T: Send, const N: usize> Sync for Queue<T, N> {}
+
+impl<T, const N: usize> Queue<T, N> {
+    /// The maximum number of elements the queue can hold, that is, `N - 1`.
+    pub const CAPACITY: usize = N.saturating_sub(1);
+
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty queue.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self {
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+                buffer: UnsafeCell::new(MaybeUninit::uninit()),
+            }
+        }
+    }
+
+    /// Returns the maximum number of elements the queue can hold, that is, `N - 1`.
+    ///
+    /// This is the same as [`CAPACITY`](Self::CAPACITY).
+    #[allow(clippy::unused_self)] // takes self so that the capacity can be obtained without naming N
+    #[inline]
+    #[must_use]
+    pub const fn capacity(&self) -> usize {
+        Self::CAPACITY
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        if tail >= head {
+            tail - head
+        } else {
+            N - head + tail
+        }
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Ordering::Acquire) == self.tail.load(Ordering::Acquire)
+    }
+
+    /// Returns `true` if the queue is full.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Splits the queue into a producer and a consumer.
+    ///
+    /// Since this borrows the queue mutably, the producer and the consumer are
+    /// guaranteed to be unique.
+    #[inline]
+    pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+        (Producer { queue: self }, Consumer { queue: self })
+    }
+
+    /// Returns a producer and a consumer of the queue, without borrowing it mutably.
+    ///
+    /// This is useful for a queue in a `static`, which is shared between, for
+    /// example, an interrupt handler and a task.
+    ///
+    /// # Safety
+    ///
+    /// At any time, at most one `Producer` and at most one `Consumer` of the
+    /// same queue may exist, including ones returned by [`split`](Self::split).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic_util::spsc::Queue;
+    ///
+    /// static QUEUE: Queue<u8, 16> = Queue::new();
+    ///
+    /// // SAFETY: this is the only producer and consumer of QUEUE.
+    /// let (mut producer, mut consumer) = unsafe { QUEUE.split_unchecked() };
+    /// producer.enqueue(1).unwrap();
+    /// assert_eq!(consumer.dequeue(), Some(1));
+    /// ```
+    #[inline]
+    pub unsafe fn split_unchecked(&self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+        (Producer { queue: self }, Consumer { queue: self })
+    }
+
+    #[inline]
+    fn slot(&self, index: usize) -> *mut T {
+        debug_assert!(index < N);
+        (self.buffer.get() as *mut T).wrapping_add(index)
+    }
+
+    #[inline]
+    fn next(index: usize) -> usize {
+        if index + 1 == N {
+            0
+        } else {
+            index + 1
+        }
+    }
+}
+
+impl<T, const N: usize> Default for Queue<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> Drop for Queue<T, N> {
+    fn drop(&mut self) {
+        let (_, mut consumer) = self.split();
+        while consumer.dequeue().is_some() {}
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Queue<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue")
+            .field("len", &self.len())
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
+/// The producer side of a [`Queue`].
+pub struct Producer<'a, T, const N: usize> {
+    queue: &'a Queue<T, N>,
+}
+
+impl<T, const N: usize> Producer<'_, T, N> {
+    /// Adds an element to the back of the queue.
+    ///
+    /// Returns `Err(value)` if the queue is full.
+    #[inline]
+    pub fn enqueue(&mut self, value: T) -> Result<(), T> {
+        if N == 0 {
+            return Err(value);
+        }
+        let tail = self.queue.tail.load(Ordering::Relaxed);
+        let next = Queue::<T, N>::next(tail);
+        if next == self.queue.head.load(Ordering::Acquire) {
+            return Err(value);
+        }
+        // SAFETY: the slot is not between head and tail, so only the producer
+        // accesses it, and the consumer has finished reading it (Acquire above).
+        unsafe { self.queue.slot(tail).write(value) }
+        self.queue.tail.store(next, Ordering::Release);
+        Ok(())
+    }
+
+    /// Returns `true` if the queue is full.
+    #[inline]
+    #[must_use]
+    pub fn is_full(&self) -> bool {
+        N == 0
+            || Queue::<T, N>::next(self.queue.tail.load(Ordering::Relaxed))
+                == self.queue.head.load(Ordering::Acquire)
+    }
+
+    /// Returns the number of elements in the queue.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the maximum number of elements the queue can hold.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Producer<'_, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Producer").field("queue", &self.queue).finish()
+    }
+}
+
+/// The consumer side of a [`Queue`].
+pub struct Consumer<'a, T, const N: usize> {
+    queue: &'a Queue<T, N>,
+}
+
+impl<T, const N: usize> Consumer<'_, T, N> {
+    /// Removes the element at the front of the queue.
+    ///
+    /// Returns `None` if the queue is empty.
+    #[inline]
+    pub fn dequeue(&mut self) -> Option<T> {
+        let head = self.queue.head.load(Ordering::Relaxed);
+        if head == self.queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot is between head and tail, so only the consumer
+        // accesses it, and the producer has finished writing it (Acquire above).
+        let value = unsafe { self.queue.slot(head).read() };
+        self.queue.head.store(Queue::<T, N>::next(head), Ordering::Release);
+        Some(value)
+    }
+
+    /// Returns a reference to the element at the front of the queue without removing it.
+    ///
+    /// Returns `None` if the queue is empty.
+    #[inline]
+    #[must_use]
+    pub fn peek(&self) -> Option<&T> {
+        let head = self.queue.head.load(Ordering::Relaxed);
+        if head == self.queue.tail.load(Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the slot is between head and tail, so only the consumer
+        // accesses it, and it is not dequeued while the returned reference is alive.
+        Some(unsafe { &*self.queue.slot(head) })
+    }
+
+    /// Returns the number of elements in the queue.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.queue.head.load(Ordering::Relaxed) == self.queue.tail.load(Ordering::Acquire)
+    }
+
+    /// Returns the maximum number of elements the queue can hold.
+    #[inline]
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.queue.capacity()
+    }
+}
+
+impl<T, const N: usize> fmt::Debug for Consumer<'_, T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Consumer").field("queue", &self.queue).finish()
+    }
+}
@@ -0,0 +1,311 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Unbounded lock-free stack and queue based on tagged pointers.
+//!
+//! - [`Stack`]: A Treiber stack.
+//! - [`Queue`]: A Michael-Scott queue.
+//!
+//! Both update a pointer and an ABA counter (tag) together with a double-width
+//! CAS on [`portable_atomic::AtomicU128`], and recycle their nodes through an
+//! internal free list instead of freeing them, so no separate memory
+//! reclamation scheme such as [`epoch`](crate::epoch) is needed. Nodes are
+//! freed when the stack or queue is dropped.
+//!
+//! If `AtomicU128` is not available (that is, the target has no native 128-bit
+//! atomic CAS and portable-atomic's `fallback` feature is disabled), a pointer
+//! and tag protected by a seqlock are used instead. If `AtomicU128` is available but
+//! not lock-free, portable-atomic's fallback implementation, which is also
+//! based on seqlocks, is used. In both cases the data structures still work
+//! correctly, but are not lock-free. Use `is_lock_free` to check which
+//! implementation is used.
+//!
+//! Note that on some targets, such as x86_64 without the `cmpxchg16b` target
+//! feature enabled at compile-time, `AtomicU128` is only available when
+//! portable-atomic's `fallback` feature is enabled, in which case it uses
+//! run-time detection to select the lock-free implementation.
+
+mod queue;
+mod stack;
+
+pub use self::{queue::Queue, stack::Stack};
+
+use core::{cell::UnsafeCell, mem::MaybeUninit, ptr};
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+use alloc::boxed::Box;
+
+// A pointer with an ABA counter.
+struct TaggedPtr<T> {
+    ptr: *mut T,
+    tag: usize,
+}
+
+impl<T> Clone for TaggedPtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for TaggedPtr<T> {}
+impl<T> PartialEq for TaggedPtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr == other.ptr && self.tag == other.tag
+    }
+}
+
+impl<T> TaggedPtr<T> {
+    #[inline]
+    fn null() -> Self {
+        Self { ptr: ptr::null_mut(), tag: 0 }
+    }
+
+    // Returns a tagged pointer with the next tag.
+    #[inline]
+    fn next(self, ptr: *mut T) -> Self {
+        Self { ptr, tag: self.tag.wrapping_add(1) }
+    }
+}
+
+portable_atomic::cfg_has_atomic_128! {
+    use core::marker::PhantomData;
+
+    use portable_atomic::AtomicU128;
+
+    // A tagged pointer stored in AtomicU128: the lower 64 bits are the pointer,
+    // and the upper 64 bits are the tag.
+    struct AtomicTaggedPtr<T> {
+        inner: AtomicU128,
+        _marker: PhantomData<*mut T>,
+    }
+
+    impl<T> AtomicTaggedPtr<T> {
+        const_fn! {
+            const_if: #[cfg(not(portable_atomic_loom))];
+            const fn null() -> Self {
+                Self { inner: AtomicU128::new(0), _marker: PhantomData }
+            }
+        }
+
+        fn is_lock_free() -> bool {
+            AtomicU128::is_lock_free()
+        }
+
+        #[inline]
+        fn pack(v: TaggedPtr<T>) -> u128 {
+            ((v.tag as u128) << 64) | (v.ptr as usize as u128)
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        #[inline]
+        fn unpack(v: u128) -> TaggedPtr<T> {
+            TaggedPtr { ptr: v as u64 as usize as *mut T, tag: (v >> 64) as u64 as usize }
+        }
+
+        #[inline]
+        fn load(&self) -> TaggedPtr<T> {
+            Self::unpack(self.inner.load(Ordering::Acquire))
+        }
+
+        #[inline]
+        fn store(&self, new: TaggedPtr<T>) {
+            self.inner.store(Self::pack(new), Ordering::Release);
+        }
+
+        #[inline]
+        fn compare_exchange(
+            &self,
+            current: TaggedPtr<T>,
+            new: TaggedPtr<T>,
+        ) -> Result<(), TaggedPtr<T>> {
+            match self.inner.compare_exchange(
+                Self::pack(current),
+                Self::pack(new),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => Ok(()),
+                Err(v) => Err(Self::unpack(v)),
+            }
+        }
+    }
+}
+
+portable_atomic::cfg_no_atomic_128! {
+    use portable_atomic::{fence, hint, AtomicPtr};
+
+    // A tagged pointer protected by a seqlock.
+    //
+    // Readers retry while the sequence number is odd or changed during the
+    // read. Writers make the sequence number odd while writing.
+    struct AtomicTaggedPtr<T> {
+        seq: AtomicUsize,
+        ptr: AtomicPtr<T>,
+        tag: AtomicUsize,
+    }
+
+    impl<T> AtomicTaggedPtr<T> {
+        const_fn! {
+            const_if: #[cfg(not(portable_atomic_loom))];
+            const fn null() -> Self {
+                Self {
+                    seq: AtomicUsize::new(0),
+                    ptr: AtomicPtr::new(ptr::null_mut()),
+                    tag: AtomicUsize::new(0),
+                }
+            }
+        }
+
+        fn is_lock_free() -> bool {
+            false
+        }
+
+        #[inline]
+        fn load(&self) -> TaggedPtr<T> {
+            loop {
+                let seq = self.seq.load(Ordering::Acquire);
+                if seq & 1 == 0 {
+                    let ptr = self.ptr.load(Ordering::Relaxed);
+                    let tag = self.tag.load(Ordering::Relaxed);
+                    fence(Ordering::Acquire);
+                    if self.seq.load(Ordering::Relaxed) == seq {
+                        return TaggedPtr { ptr, tag };
+                    }
+                }
+                hint::spin_loop();
+            }
+        }
+
+        // Acquires the write lock and returns the sequence number before locking.
+        #[inline]
+        fn lock(&self) -> usize {
+            loop {
+                let seq = self.seq.load(Ordering::Relaxed);
+                let locked = seq.wrapping_add(1);
+                if seq & 1 == 0
+                    && self
+                        .seq
+                        .compare_exchange_weak(seq, locked, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok()
+                {
+                    // Prevent the following writes from being reordered before the lock.
+                    fence(Ordering::Release);
+                    return seq;
+                }
+                hint::spin_loop();
+            }
+        }
+
+        #[inline]
+        fn store(&self, new: TaggedPtr<T>) {
+            let seq = self.lock();
+            self.ptr.store(new.ptr, Ordering::Relaxed);
+            self.tag.store(new.tag, Ordering::Relaxed);
+            self.seq.store(seq.wrapping_add(2), Ordering::Release);
+        }
+
+        #[inline]
+        fn compare_exchange(
+            &self,
+            current: TaggedPtr<T>,
+            new: TaggedPtr<T>,
+        ) -> Result<(), TaggedPtr<T>> {
+            let seq = self.lock();
+            let ptr = self.ptr.load(Ordering::Relaxed);
+            let tag = self.tag.load(Ordering::Relaxed);
+            if ptr == current.ptr && tag == current.tag {
+                self.ptr.store(new.ptr, Ordering::Relaxed);
+                self.tag.store(new.tag, Ordering::Relaxed);
+                self.seq.store(seq.wrapping_add(2), Ordering::Release);
+                Ok(())
+            } else {
+                // Nothing has been written, so restore the previous sequence number.
+                self.seq.store(seq, Ordering::Release);
+                Err(TaggedPtr { ptr, tag })
+            }
+        }
+    }
+}
+
+struct Node<T> {
+    // The next node in the stack, queue, or free list. The tag is incremented
+    // on every update, even when the node is recycled.
+    next: AtomicTaggedPtr<Node<T>>,
+    // Used by Queue: the number of remaining owners of the node. See queue.rs.
+    refs: AtomicUsize,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+impl<T> Node<T> {
+    // Sets the next pointer, incrementing the tag.
+    #[inline]
+    fn set_next(&self, next: *mut Node<T>) {
+        self.next.store(self.next.load().next(next));
+    }
+}
+
+// A Treiber stack of nodes. This is used as the stack itself and as free lists.
+struct NodeStack<T> {
+    head: AtomicTaggedPtr<Node<T>>,
+}
+
+impl<T> NodeStack<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        const fn new() -> Self {
+            Self { head: AtomicTaggedPtr::null() }
+        }
+    }
+
+    // The caller must have a unique access to the node.
+    fn push(&self, node: *mut Node<T>) {
+        let mut head = self.head.load();
+        loop {
+            // SAFETY: nodes are never freed while the stack is alive, and the
+            // caller has a unique access to the node.
+            unsafe { (*node).set_next(head.ptr) }
+            match self.head.compare_exchange(head, head.next(node)) {
+                Ok(()) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    // Returns a node that the caller has a unique access to.
+    fn pop(&self) -> Option<*mut Node<T>> {
+        let mut head = self.head.load();
+        loop {
+            if head.ptr.is_null() {
+                return None;
+            }
+            // SAFETY: nodes are never freed while the stack is alive. The node
+            // may have been popped and reused by another thread, but then the
+            // tag of the head has changed, so the CAS below fails.
+            let next = unsafe { (*head.ptr).next.load() };
+            match self.head.compare_exchange(head, head.next(next.ptr)) {
+                Ok(()) => return Some(head.ptr),
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    // Frees all nodes in the stack without dropping their values.
+    fn free_all(&mut self) {
+        let mut p = self.head.load().ptr;
+        while !p.is_null() {
+            // SAFETY: we have a unique access to the stack, and nodes are created by Box::into_raw.
+            let node = unsafe { Box::from_raw(p) };
+            p = node.next.load().ptr;
+        }
+        self.head.store(TaggedPtr::null());
+    }
+}
+
+// Returns a node from the free list, or allocates a new one.
+fn alloc_node<T>(free: &NodeStack<T>) -> *mut Node<T> {
+    free.pop().unwrap_or_else(|| {
+        Box::into_raw(Box::new(Node {
+            next: AtomicTaggedPtr::null(),
+            refs: AtomicUsize::new(0),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }))
+    })
+}
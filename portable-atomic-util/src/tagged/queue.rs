@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// The algorithm is based on the non-blocking queue in
+// "Simple, Fast, and Practical Non-Blocking and Blocking Concurrent Queue Algorithms"
+// by Maged M. Michael and Michael L. Scott.
+//
+// The queue always contains a dummy node at the head, and a dequeue moves the
+// head to the next node, which becomes the new dummy node after its value is
+// read. In the original algorithm, the value is read before the CAS on the
+// head, but in Rust, that read may race with a write to a recycled node. So
+// the value is read after the CAS, and each node has two owners: the thread
+// that reads its value, and the thread that moves the head past it. The last
+// one to release the node pushes it to the free list.
+
+use core::{fmt, mem::MaybeUninit};
+
+use portable_atomic::Ordering;
+
+use super::{alloc_node, AtomicTaggedPtr, Node, NodeStack, TaggedPtr};
+
+/// An unbounded lock-free queue (Michael-Scott queue).
+///
+/// See the [module-level documentation](super) for the implementation details.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::tagged::Queue;
+///
+/// let queue = Queue::new();
+/// queue.push(1);
+/// queue.push(2);
+/// assert_eq!(queue.pop(), Some(1));
+/// assert_eq!(queue.pop(), Some(2));
+/// assert_eq!(queue.pop(), None);
+/// ```
+pub struct Queue<T> {
+    head: AtomicTaggedPtr<Node<T>>,
+    tail: AtomicTaggedPtr<Node<T>>,
+    free: NodeStack<T>,
+}
+
+// SAFETY: each value is moved out by only one thread, so only T: Send is required.
+unsafe impl<T: Send> Send for Queue<T> {}
+// SAFETY: see above.
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    /// Creates a new empty queue.
+    #[must_use]
+    pub fn new() -> Self {
+        let queue = Self {
+            head: AtomicTaggedPtr::null(),
+            tail: AtomicTaggedPtr::null(),
+            free: NodeStack::new(),
+        };
+        let dummy = alloc_node(&queue.free);
+        // SAFETY: we have a unique access to the node returned by alloc_node.
+        // The dummy node has no value, so only the thread that moves the head
+        // past it owns it.
+        unsafe { (*dummy).refs.store(1, Ordering::Relaxed) }
+        queue.head.store(TaggedPtr::null().next(dummy));
+        queue.tail.store(TaggedPtr::null().next(dummy));
+        queue
+    }
+
+    /// Returns `true` if the operations on this queue are lock-free.
+    ///
+    /// This returns `false` if this queue uses a seqlock-based implementation.
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicTaggedPtr::<Node<T>>::is_lock_free()
+    }
+
+    /// Adds an element to the back of the queue.
+    pub fn push(&self, value: T) {
+        let node = alloc_node(&self.free);
+        // SAFETY: we have a unique access to the node returned by alloc_node.
+        unsafe {
+            *(*node).value.get() = MaybeUninit::new(value);
+            (*node).refs.store(2, Ordering::Relaxed);
+            (*node).set_next(core::ptr::null_mut());
+        }
+        loop {
+            let tail = self.tail.load();
+            // SAFETY: nodes are never freed while the queue is alive. If the
+            // node has been recycled, the tags have changed, so the CASes below fail.
+            let tail_next = unsafe { &(*tail.ptr).next };
+            let next = tail_next.load();
+            if tail != self.tail.load() {
+                continue;
+            }
+            if next.ptr.is_null() {
+                if tail_next.compare_exchange(next, next.next(node)).is_ok() {
+                    // If this fails, another thread has already moved the tail.
+                    let _ = self.tail.compare_exchange(tail, tail.next(node));
+                    return;
+                }
+            } else {
+                // The tail is lagging behind, so help to move it.
+                let _ = self.tail.compare_exchange(tail, tail.next(next.ptr));
+            }
+        }
+    }
+
+    /// Removes the element at the front of the queue.
+    ///
+    /// Returns `None` if the queue is empty.
+    pub fn pop(&self) -> Option<T> {
+        loop {
+            let head = self.head.load();
+            let tail = self.tail.load();
+            // SAFETY: nodes are never freed while the queue is alive. If the
+            // node has been recycled, the tags have changed, so the CASes below fail.
+            let next = unsafe { (*head.ptr).next.load() };
+            if head != self.head.load() {
+                continue;
+            }
+            if head.ptr == tail.ptr {
+                if next.ptr.is_null() {
+                    return None;
+                }
+                // The tail is lagging behind, so help to move it.
+                let _ = self.tail.compare_exchange(tail, tail.next(next.ptr));
+            } else if !next.ptr.is_null()
+                && self.head.compare_exchange(head, head.next(next.ptr)).is_ok()
+            {
+                // SAFETY: we moved the head to next, so we own the value of
+                // next, and the node is not recycled until we release it.
+                let value = unsafe { (*(*next.ptr).value.get()).as_ptr().read() };
+                // SAFETY: we own one reference to each node.
+                unsafe {
+                    self.release(next.ptr);
+                    self.release(head.ptr);
+                }
+                return Some(value);
+            }
+        }
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// If the queue is used concurrently, the returned value may be outdated.
+    pub fn is_empty(&self) -> bool {
+        let head = self.head.load();
+        // SAFETY: nodes are never freed while the queue is alive.
+        unsafe { (*head.ptr).next.load().ptr.is_null() }
+    }
+
+    // Releases a reference to the node, and pushes it to the free list if it is the last one.
+    unsafe fn release(&self, node: *mut Node<T>) {
+        // SAFETY: the caller guarantees that the node is alive.
+        if unsafe { (*node).refs.fetch_sub(1, Ordering::AcqRel) } == 1 {
+            self.free.push(node);
+        }
+    }
+}
+
+impl<T> Default for Queue<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+        // SAFETY: we have a unique access to the queue, so the dummy node is
+        // not in the free list, and was created by Box::into_raw.
+        unsafe { drop(alloc::boxed::Box::from_raw(self.head.load().ptr)) }
+        self.free.free_all();
+    }
+}
+
+impl<T> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Queue").field("is_empty", &self.is_empty()).finish()
+    }
+}
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::{fmt, marker::PhantomData, mem::MaybeUninit};
+
+use super::{alloc_node, AtomicTaggedPtr, Node, NodeStack};
+
+/// An unbounded lock-free stack (Treiber stack).
+///
+/// See the [module-level documentation](super) for the implementation details.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic_util::tagged::Stack;
+///
+/// let stack = Stack::new();
+/// stack.push(1);
+/// stack.push(2);
+/// assert_eq!(stack.pop(), Some(2));
+/// assert_eq!(stack.pop(), Some(1));
+/// assert_eq!(stack.pop(), None);
+/// ```
+pub struct Stack<T> {
+    items: NodeStack<T>,
+    free: NodeStack<T>,
+    _marker: PhantomData<T>,
+}
+
+// SAFETY: each value is moved out by only one thread, so only T: Send is required.
+unsafe impl<T: Send> Send for Stack<T> {}
+// SAFETY: see above.
+unsafe impl<T: Send> Sync for Stack<T> {}
+
+impl<T> Stack<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new empty stack.
+        ///
+        /// This does not allocate until the first element is pushed.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            Self { items: NodeStack::new(), free: NodeStack::new(), _marker: PhantomData }
+        }
+    }
+
+    /// Returns `true` if the operations on this stack are lock-free.
+    ///
+    /// This returns `false` if this stack uses a seqlock-based implementation.
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicTaggedPtr::<Node<T>>::is_lock_free()
+    }
+
+    /// Pushes an element to the top of the stack.
+    pub fn push(&self, value: T) {
+        let node = alloc_node(&self.free);
+        // SAFETY: we have a unique access to the node returned by alloc_node.
+        unsafe { *(*node).value.get() = MaybeUninit::new(value) }
+        self.items.push(node);
+    }
+
+    /// Removes the element at the top of the stack.
+    ///
+    /// Returns `None` if the stack is empty.
+    pub fn pop(&self) -> Option<T> {
+        let node = self.items.pop()?;
+        // SAFETY: we have a unique access to the node returned by NodeStack::pop,
+        // and its value has been initialized by push.
+        let value = unsafe { (*(*node).value.get()).as_ptr().read() };
+        self.free.push(node);
+        Some(value)
+    }
+
+    /// Returns `true` if the stack is empty.
+    ///
+    /// If the stack is used concurrently, the returned value may be outdated.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.items.head.load().ptr.is_null()
+    }
+}
+
+impl<T> Default for Stack<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for Stack<T> {
+    fn drop(&mut self) {
+        while self.pop().is_some() {}
+        self.free.free_all();
+    }
+}
+
+impl<T> fmt::Debug for Stack<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stack").field("is_empty", &self.is_empty()).finish()
+    }
+}
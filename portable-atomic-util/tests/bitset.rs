@@ -0,0 +1,101 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+#[cfg(not(portable_atomic_no_min_const_generics))]
+use portable_atomic_util::bitset::AtomicBitSet;
+use portable_atomic_util::bitset::AtomicBitSlice;
+
+const BITS: usize = std::mem::size_of::<usize>() * 8;
+
+#[test]
+fn set_clear_toggle() {
+    let words = [AtomicUsize::new(0), AtomicUsize::new(0)];
+    let bits = AtomicBitSlice::new(&words);
+    assert_eq!(bits.len(), BITS * 2);
+    assert!(!bits.is_empty());
+    for &i in &[0, 1, BITS - 1, BITS, BITS * 2 - 1] {
+        assert!(!bits.test(i, Ordering::Relaxed));
+        assert!(!bits.set(i, Ordering::Relaxed));
+        assert!(bits.test(i, Ordering::Relaxed));
+        assert!(bits.set(i, Ordering::Relaxed));
+        assert!(bits.clear(i, Ordering::Relaxed));
+        assert!(!bits.clear(i, Ordering::Relaxed));
+        assert!(!bits.toggle(i, Ordering::Relaxed));
+        assert!(bits.toggle(i, Ordering::Relaxed));
+        assert!(!bits.test(i, Ordering::Relaxed));
+    }
+    bits.set(BITS + 3, Ordering::Relaxed);
+    assert_eq!(words[1].load(Ordering::Relaxed), 1 << 3);
+    assert_eq!(words[0].load(Ordering::Relaxed), 0);
+    assert!(AtomicBitSlice::new(&[]).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "index out of bounds")]
+fn out_of_bounds() {
+    let words = [AtomicUsize::new(0)];
+    AtomicBitSlice::new(&words).set(BITS, Ordering::Relaxed);
+}
+
+#[test]
+fn find_first_zero_and_set() {
+    let words = [AtomicUsize::new(0b1011), AtomicUsize::new(!0), AtomicUsize::new(!1)];
+    let bits = AtomicBitSlice::new(&words);
+    assert_eq!(bits.count_ones(Ordering::Relaxed), 3 + BITS + (BITS - 1));
+    assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(2));
+    for i in 4..BITS {
+        assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(i));
+    }
+    assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(BITS * 2));
+    assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), None);
+    assert_eq!(bits.count_ones(Ordering::Relaxed), bits.len());
+    bits.clear(BITS + 5, Ordering::Relaxed);
+    assert_eq!(bits.find_first_zero_and_set(Ordering::AcqRel), Some(BITS + 5));
+}
+
+#[test]
+fn find_first_zero_and_set_concurrent() {
+    const THREADS: usize = 4;
+    let words: &'static [AtomicUsize] =
+        Box::leak((0..4).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>().into_boxed_slice());
+    let bits = AtomicBitSlice::new(words);
+    let taken = Arc::new(Mutex::new(BTreeSet::new()));
+    let threads: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let taken = taken.clone();
+            thread::spawn(move || {
+                let bits = AtomicBitSlice::new(words);
+                while let Some(i) = bits.find_first_zero_and_set(Ordering::AcqRel) {
+                    assert!(taken.lock().unwrap().insert(i));
+                }
+            })
+        })
+        .collect();
+    for t in threads {
+        t.join().unwrap();
+    }
+    assert_eq!(taken.lock().unwrap().len(), bits.len());
+    assert_eq!(bits.count_ones(Ordering::Relaxed), bits.len());
+}
+
+#[cfg(not(portable_atomic_no_min_const_generics))]
+#[test]
+fn fixed_size() {
+    static SET: AtomicBitSet<2> = AtomicBitSet::new();
+    assert_eq!(SET.len(), BITS * 2);
+    assert_eq!(SET.find_first_zero_and_set(Ordering::AcqRel), Some(0));
+    assert!(SET.test(0, Ordering::Relaxed));
+    assert!(SET.clear(0, Ordering::Relaxed));
+    let set = AtomicBitSet::<1>::default();
+    set.set(1, Ordering::Relaxed);
+    let [word] = set.into_words();
+    assert_eq!(word.into_inner(), 0b10);
+}
@@ -0,0 +1,183 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(feature = "std", feature = "alloc"))]
+#![allow(clippy::undocumented_unsafe_blocks)]
+
+mod helper;
+
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+use portable_atomic_util::epoch::{self, Collector};
+
+#[cfg(feature = "std")]
+use self::helper::stress_test_config;
+use self::helper::DropCounter;
+
+fn flush_until(handle: &epoch::LocalHandle, f: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if f() {
+            return;
+        }
+        handle.pin().flush();
+    }
+    panic!("deferred functions were not called");
+}
+
+#[test]
+fn pin() {
+    let collector = Collector::new();
+    let handle = collector.register();
+    assert!(!handle.is_pinned());
+    {
+        let _g1 = handle.pin();
+        assert!(handle.is_pinned());
+        {
+            let _g2 = handle.pin();
+            assert!(handle.is_pinned());
+        }
+        assert!(handle.is_pinned());
+    }
+    assert!(!handle.is_pinned());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn pin_default_collector() {
+    assert!(!epoch::is_pinned());
+    let guard = epoch::pin();
+    assert!(epoch::is_pinned());
+    drop(guard);
+    assert!(!epoch::is_pinned());
+}
+
+#[test]
+fn defer_destroy() {
+    let collector = Collector::new();
+    let handle = collector.register();
+    let dropped = Arc::new(AtomicUsize::new(0));
+    {
+        let guard = handle.pin();
+        for _ in 0..10 {
+            let p = Box::into_raw(Box::new(DropCounter(dropped.clone())));
+            unsafe { guard.defer_destroy(p) }
+        }
+        let counter = dropped.clone();
+        guard.defer(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        guard.flush();
+        // Destroying is deferred at least until this thread unpins.
+        assert_eq!(dropped.load(Ordering::Relaxed), 0);
+    }
+    flush_until(&handle, || dropped.load(Ordering::Relaxed) == 11);
+}
+
+#[test]
+fn pinned_thread_blocks_destruction() {
+    let collector = Collector::new();
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let (pinned_tx, pinned_rx) = mpsc::channel();
+    let (unpin_tx, unpin_rx) = mpsc::channel::<()>();
+    let t = {
+        let collector = collector.clone();
+        thread::spawn(move || {
+            let handle = collector.register();
+            let _guard = handle.pin();
+            pinned_tx.send(()).unwrap();
+            unpin_rx.recv().unwrap();
+        })
+    };
+    pinned_rx.recv().unwrap();
+
+    let handle = collector.register();
+    {
+        let guard = handle.pin();
+        let p = Box::into_raw(Box::new(DropCounter(dropped.clone())));
+        unsafe { guard.defer_destroy(p) }
+    }
+    for _ in 0..100 {
+        handle.pin().flush();
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), 0);
+
+    unpin_tx.send(()).unwrap();
+    t.join().unwrap();
+    flush_until(&handle, || dropped.load(Ordering::Relaxed) == 1);
+}
+
+#[test]
+fn collector_drop() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    {
+        let collector = Collector::new();
+        let handle = collector.register();
+        let guard = handle.pin();
+        for _ in 0..100 {
+            let p = Box::into_raw(Box::new(DropCounter(dropped.clone())));
+            unsafe { guard.defer_destroy(p) }
+        }
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), 100);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn stress() {
+    use portable_atomic::AtomicPtr;
+
+    const VALID: usize = 0x5a5a_5a5a;
+    struct Node {
+        value: usize,
+        _counter: DropCounter,
+    }
+    impl Drop for Node {
+        fn drop(&mut self) {
+            assert_eq!(self.value, VALID);
+            self.value = 0;
+        }
+    }
+
+    let (iterations, threads) = stress_test_config();
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let new_node =
+        || Box::into_raw(Box::new(Node { value: VALID, _counter: DropCounter(dropped.clone()) }));
+    let shared = Arc::new(AtomicPtr::new(new_node()));
+    let handles: Vec<_> = (0..threads)
+        .map(|i| {
+            let shared = shared.clone();
+            let nodes: Vec<_> = (0..iterations).map(|_| new_node() as usize).collect();
+            thread::spawn(move || {
+                for node in nodes {
+                    let guard = epoch::pin();
+                    let p = shared.load(Ordering::Acquire);
+                    if i % 2 == 0 {
+                        thread::yield_now();
+                    }
+                    // p may have been unlinked by another thread, but not destroyed yet.
+                    assert_eq!(unsafe { (*p).value }, VALID);
+                    let old = shared.swap(node as *mut Node, Ordering::AcqRel);
+                    unsafe { guard.defer_destroy(old) }
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    // All but the last node are deferred.
+    let total = iterations * threads;
+    for _ in 0..1000 {
+        if dropped.load(Ordering::Relaxed) == total {
+            break;
+        }
+        epoch::pin().flush();
+    }
+    // Deferred functions buffered by exited threads are flushed to the
+    // collector, so all of them can be destroyed.
+    assert_eq!(dropped.load(Ordering::Relaxed), total);
+    unsafe { drop(Box::from_raw(shared.load(Ordering::Relaxed))) }
+}
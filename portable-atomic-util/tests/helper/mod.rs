@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Helpers shared by the integration tests. Each test crate uses only some of them.
+#![allow(dead_code)]
+
+use std::sync::Arc;
+
+use portable_atomic::{AtomicUsize, Ordering};
+
+// Same as stress_test_config in portable-atomic's src/tests/helper.rs, without random thread count.
+pub(crate) fn stress_test_config() -> (usize, usize) {
+    let iterations = if cfg!(miri) {
+        50
+    } else if cfg!(debug_assertions) {
+        5_000
+    } else {
+        25_000
+    };
+    let threads = if cfg!(debug_assertions) { 2 } else { 8 };
+    (iterations, threads)
+}
+
+// Increments the counter when dropped.
+pub(crate) struct DropCounter(pub(crate) Arc<AtomicUsize>);
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
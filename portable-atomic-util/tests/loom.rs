@@ -0,0 +1,139 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Tests for the queues with `--cfg portable_atomic_loom`.
+//
+// Run with:
+//   RUSTFLAGS="--cfg portable_atomic_loom" cargo test --release --test loom
+
+#![cfg(portable_atomic_loom)]
+
+use loom::{cell::UnsafeCell, sync::Arc, thread};
+use portable_atomic_util::{mpmc, spsc};
+
+// The queues only carry indexes into this, so that loom checks that reading the
+// data after popping an index does not race with writing it before pushing the
+// index. That is, popping an element must synchronize with pushing it.
+struct Data([UnsafeCell<usize>; 2]);
+
+impl Data {
+    fn new() -> Self {
+        Self([UnsafeCell::new(0), UnsafeCell::new(0)])
+    }
+    fn write(&self, i: usize) {
+        // SAFETY: loom checks that this does not race with other accesses.
+        self.0[i].with_mut(|p| unsafe { *p = i + 1 });
+    }
+    fn check(&self, i: usize) {
+        // SAFETY: loom checks that this does not race with other accesses.
+        assert_eq!(self.0[i].with(|p| unsafe { *p }), i + 1);
+    }
+}
+
+fn spsc<const N: usize>() {
+    loom::model(|| {
+        // The producer and the consumer must outlive the threads.
+        let queue: &'static mut spsc::Queue<usize, N> = Box::leak(Box::new(spsc::Queue::new()));
+        let (mut producer, mut consumer) = queue.split();
+        let data = Arc::new(Data::new());
+        let t = {
+            let data = data.clone();
+            thread::spawn(move || {
+                for i in 0..2 {
+                    data.write(i);
+                    while producer.enqueue(i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+        for i in 0..2 {
+            loop {
+                if let Some(v) = consumer.dequeue() {
+                    assert_eq!(v, i);
+                    data.check(v);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+        t.join().unwrap();
+    });
+}
+
+#[test]
+fn spsc_two_slots() {
+    spsc::<3>();
+}
+
+// The producer has to wait for the consumer to free the only slot.
+#[test]
+fn spsc_one_slot() {
+    spsc::<2>();
+}
+
+#[test]
+fn mpmc() {
+    loom::model(|| {
+        let queue = Arc::new(mpmc::Queue::<usize, 2>::new());
+        let data = Arc::new(Data::new());
+        let t = {
+            let queue = queue.clone();
+            let data = data.clone();
+            thread::spawn(move || {
+                data.write(0);
+                queue.push(0).unwrap();
+            })
+        };
+        data.write(1);
+        queue.push(1).unwrap();
+        let mut popped = [false; 2];
+        for _ in 0..2 {
+            loop {
+                if let Some(v) = queue.pop() {
+                    assert!(!popped[v]);
+                    popped[v] = true;
+                    data.check(v);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+        t.join().unwrap();
+        assert_eq!(queue.pop(), None);
+    });
+}
+
+// A push that finds the queue full must not overwrite the element, and a
+// consumer on another thread must get all elements in order.
+#[test]
+fn mpmc_full() {
+    loom::model(|| {
+        let queue = Arc::new(mpmc::Queue::<usize, 1>::new());
+        let data = Arc::new(Data::new());
+        let t = {
+            let queue = queue.clone();
+            let data = data.clone();
+            thread::spawn(move || {
+                for i in 0..2 {
+                    data.write(i);
+                    let mut v = i;
+                    while let Err(e) = queue.push(v) {
+                        v = e;
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+        for i in 0..2 {
+            loop {
+                if let Some(v) = queue.pop() {
+                    assert_eq!(v, i);
+                    data.check(v);
+                    break;
+                }
+                thread::yield_now();
+            }
+        }
+        t.join().unwrap();
+    });
+}
@@ -0,0 +1,174 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use std::{
+    panic,
+    sync::{Arc, Barrier},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+use portable_atomic_util::once_cell::{race, Lazy, OnceCell};
+
+const THREADS: usize = 8;
+
+#[test]
+fn once_cell() {
+    let mut cell = OnceCell::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Err(1)), Err(1));
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| 1), &1);
+    assert_eq!(cell.get_or_init(|| 2), &1);
+    assert_eq!(cell.set(3), Err(3));
+    *cell.get_mut().unwrap() = 4;
+    assert_eq!(format!("{:?}", cell), "OnceCell(4)");
+    assert_eq!(cell.clone(), OnceCell::from(4));
+    assert_eq!(cell.take(), Some(4));
+    assert_eq!(format!("{:?}", cell), "OnceCell(<uninit>)");
+    assert_eq!(cell.set(5), Ok(()));
+    assert_eq!(cell.into_inner(), Some(5));
+}
+
+#[test]
+fn once_cell_panic() {
+    let cell = OnceCell::new();
+    let res = panic::catch_unwind(|| {
+        cell.get_or_init(|| panic!("init"));
+    });
+    assert!(res.is_err());
+    // The cell remains uninitialized and can be initialized again.
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| 1), &1);
+}
+
+#[test]
+fn once_cell_threads() {
+    static CELL: OnceCell<usize> = OnceCell::new();
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let threads: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                *CELL.get_or_init(|| {
+                    CALLS.fetch_add(1, Ordering::Relaxed);
+                    thread::yield_now();
+                    i
+                })
+            })
+        })
+        .collect();
+    let values: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    // The initialization function is called only once, and all threads see its value.
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    assert!(values.iter().all(|&v| v == values[0]));
+    assert_eq!(CELL.get(), Some(&values[0]));
+}
+
+#[test]
+fn lazy() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static LAZY: Lazy<Vec<i32>> = Lazy::new(|| {
+        CALLS.fetch_add(1, Ordering::Relaxed);
+        vec![1, 2, 3]
+    });
+    assert_eq!(Lazy::get(&LAZY), None);
+    let threads: Vec<_> = (0..THREADS).map(|_| thread::spawn(|| LAZY.len())).collect();
+    for t in threads {
+        assert_eq!(t.join().unwrap(), 3);
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    assert_eq!(Lazy::get(&LAZY), Some(&vec![1, 2, 3]));
+
+    let mut lazy = Lazy::new(|| 1);
+    *lazy += 1;
+    assert_eq!(*lazy, 2);
+    assert_eq!(lazy.into_value().ok(), Some(2));
+    assert!(Lazy::<i32, _>::new(|| 1).into_value().is_err());
+    assert_eq!(*Lazy::<i32>::default(), 0);
+}
+
+#[test]
+fn lazy_panic() {
+    let lazy = Lazy::new(|| -> i32 { panic!("init") });
+    assert!(panic::catch_unwind(panic::AssertUnwindSafe(|| *lazy)).is_err());
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| *lazy));
+    let msg = res.unwrap_err();
+    assert_eq!(*msg.downcast_ref::<&str>().unwrap(), "Lazy instance has previously been poisoned");
+}
+
+#[test]
+fn race_once_ref() {
+    static A: i32 = 1;
+    static B: i32 = 2;
+    let cell = race::OnceRef::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    assert_eq!(cell.get_or_init(|| &A), &1);
+    assert_eq!(cell.set(&B), Err(()));
+    assert_eq!(cell.get_or_init(|| &B), &1);
+    assert_eq!(format!("{:?}", cell), "OnceRef(Some(1))");
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn race_once_box() {
+    let cell = race::OnceBox::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    assert_eq!(cell.get_or_init(|| Box::new(1)), &1);
+    assert_eq!(cell.set(Box::new(2)), Err(Box::new(2)));
+    assert_eq!(format!("{:?}", cell), "OnceBox(Some(1))");
+    assert_eq!(cell.into_inner(), Some(Box::new(1)));
+    assert_eq!(race::OnceBox::<i32>::new().into_inner(), None);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn race_once_box_threads() {
+    // Each value is dropped exactly once, whether it won the race or not.
+    struct DropCounter(usize);
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let cell = Arc::new(race::OnceBox::new());
+    let barrier = Arc::new(Barrier::new(THREADS));
+    let threads: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let cell = cell.clone();
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                cell.get_or_init(|| {
+                    CALLS.fetch_add(1, Ordering::Relaxed);
+                    Box::new(DropCounter(i))
+                })
+                .0
+            })
+        })
+        .collect();
+    let values: Vec<_> = threads.into_iter().map(|t| t.join().unwrap()).collect();
+    assert!(values.iter().all(|&v| v == values[0]));
+    let calls = CALLS.load(Ordering::Relaxed);
+    assert_eq!(DROPS.load(Ordering::Relaxed), calls - 1);
+    drop(cell);
+    assert_eq!(DROPS.load(Ordering::Relaxed), calls);
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+#[test]
+fn race_lazy() {
+    static LAZY: race::Lazy<String> = race::Lazy::new(|| "lazy".to_string());
+    assert_eq!(race::Lazy::get(&LAZY), None);
+    let threads: Vec<_> = (0..THREADS).map(|_| thread::spawn(|| LAZY.len())).collect();
+    for t in threads {
+        assert_eq!(t.join().unwrap(), 4);
+    }
+    assert_eq!(race::Lazy::get(&LAZY).map(String::as_str), Some("lazy"));
+}
@@ -0,0 +1,233 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+mod helper;
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+use portable_atomic_util::{mpmc, spsc};
+
+use self::helper::{stress_test_config, DropCounter};
+
+#[test]
+fn spsc() {
+    let mut q: spsc::Queue<i32, 4> = spsc::Queue::new();
+    assert_eq!(q.capacity(), 3);
+    assert_eq!(spsc::Queue::<i32, 4>::CAPACITY, 3);
+    assert!(q.is_empty());
+    let (mut p, mut c) = q.split();
+    assert_eq!(c.peek(), None);
+    assert_eq!(c.dequeue(), None);
+    // Go around the buffer several times.
+    for i in 0..10 {
+        assert_eq!(p.enqueue(i), Ok(()));
+        assert_eq!(p.enqueue(i + 1), Ok(()));
+        assert_eq!(p.enqueue(i + 2), Ok(()));
+        assert!(p.is_full());
+        assert_eq!(p.enqueue(i + 3), Err(i + 3));
+        assert_eq!(c.len(), 3);
+        assert_eq!(c.peek(), Some(&i));
+        assert_eq!(c.dequeue(), Some(i));
+        assert_eq!(c.dequeue(), Some(i + 1));
+        assert_eq!(p.len(), 1);
+        assert_eq!(c.dequeue(), Some(i + 2));
+        assert!(c.is_empty());
+        assert_eq!(c.dequeue(), None);
+    }
+    assert_eq!(format!("{:?}", q), "Queue { len: 0, capacity: 3 }");
+
+    let mut q: spsc::Queue<i32, 0> = spsc::Queue::new();
+    let (mut p, mut c) = q.split();
+    assert!(p.is_full());
+    assert_eq!(p.enqueue(1), Err(1));
+    assert_eq!(c.dequeue(), None);
+    let mut q: spsc::Queue<i32, 1> = spsc::Queue::new();
+    let (mut p, mut c) = q.split();
+    assert!(p.is_full());
+    assert_eq!(p.enqueue(1), Err(1));
+    assert_eq!(c.dequeue(), None);
+}
+
+#[test]
+fn spsc_static() {
+    static Q: spsc::Queue<usize, 8> = spsc::Queue::new();
+    // SAFETY: this is the only producer and consumer of Q.
+    let (mut p, mut c) = unsafe { Q.split_unchecked() };
+    let (iterations, _) = stress_test_config();
+    let producer = thread::spawn(move || {
+        for i in 0..iterations {
+            let mut v = i;
+            while let Err(e) = p.enqueue(v) {
+                v = e;
+                thread::yield_now();
+            }
+        }
+    });
+    for i in 0..iterations {
+        loop {
+            if let Some(v) = c.dequeue() {
+                assert_eq!(v, i);
+                break;
+            }
+            thread::yield_now();
+        }
+    }
+    producer.join().unwrap();
+    assert!(Q.is_empty());
+}
+
+#[test]
+fn spsc_drop() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    {
+        let mut q: spsc::Queue<DropCounter, 5> = spsc::Queue::new();
+        let (mut p, mut c) = q.split();
+        for _ in 0..3 {
+            assert!(p.enqueue(DropCounter(dropped.clone())).is_ok());
+        }
+        drop(c.dequeue());
+        assert!(p.enqueue(DropCounter(dropped.clone())).is_ok());
+        assert!(p.enqueue(DropCounter(dropped.clone())).is_ok());
+        assert_eq!(dropped.load(Ordering::Relaxed), 1);
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), 5);
+}
+
+#[test]
+fn mpmc() {
+    // Use a capacity that is not a power of two to test the lap handling.
+    let q: mpmc::Queue<i32, 3> = mpmc::Queue::new();
+    assert_eq!(q.capacity(), 3);
+    assert_eq!(mpmc::Queue::<i32, 3>::CAPACITY, 3);
+    assert!(q.is_empty());
+    assert_eq!(q.pop(), None);
+    for i in 0..10 {
+        assert_eq!(q.push(i), Ok(()));
+        assert_eq!(q.push(i + 1), Ok(()));
+        assert_eq!(q.len(), 2);
+        assert_eq!(q.push(i + 2), Ok(()));
+        assert!(q.is_full());
+        assert_eq!(q.len(), 3);
+        assert_eq!(q.push(i + 3), Err(i + 3));
+        assert_eq!(q.pop(), Some(i));
+        assert_eq!(q.pop(), Some(i + 1));
+        assert_eq!(q.pop(), Some(i + 2));
+        assert!(q.is_empty());
+        assert_eq!(q.len(), 0);
+        assert_eq!(q.pop(), None);
+        // Shift the start position for the next round.
+        assert_eq!(q.push(i), Ok(()));
+        assert_eq!(q.pop(), Some(i));
+    }
+    assert_eq!(format!("{:?}", q), "Queue { len: 0, capacity: 3 }");
+
+    let q: mpmc::Queue<i32, 0> = mpmc::Queue::new();
+    assert!(q.is_full());
+    assert_eq!(q.push(1), Err(1));
+    assert_eq!(q.pop(), None);
+    let q: mpmc::Queue<i32, 1> = mpmc::Queue::new();
+    assert_eq!(q.push(1), Ok(()));
+    assert_eq!(q.push(2), Err(2));
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn mpmc_drop() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    {
+        let q: mpmc::Queue<DropCounter, 5> = mpmc::Queue::new();
+        for _ in 0..5 {
+            assert!(q.push(DropCounter(dropped.clone())).is_ok());
+        }
+        assert!(q.push(DropCounter(dropped.clone())).is_err());
+        drop(q.pop());
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), 6);
+}
+
+#[test]
+fn mpmc_stress() {
+    let (iterations, threads) = stress_test_config();
+    let q = Arc::new(mpmc::Queue::<usize, 7>::new());
+    let sum = Arc::new(AtomicUsize::new(0));
+    let count = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(threads * 2));
+    let mut handles = vec![];
+    for _ in 0..threads {
+        let (q1, barrier1) = (q.clone(), barrier.clone());
+        handles.push(thread::spawn(move || {
+            let (q, barrier) = (q1, barrier1);
+            barrier.wait();
+            for i in 0..iterations {
+                let mut v = i;
+                while let Err(e) = q.push(v) {
+                    v = e;
+                    thread::yield_now();
+                }
+            }
+        }));
+        let (q, sum, count, barrier) = (q.clone(), sum.clone(), count.clone(), barrier.clone());
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            for _ in 0..iterations {
+                loop {
+                    if let Some(v) = q.pop() {
+                        sum.fetch_add(v, Ordering::Relaxed);
+                        count.fetch_add(1, Ordering::Relaxed);
+                        break;
+                    }
+                    thread::yield_now();
+                }
+            }
+        }));
+    }
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert!(q.is_empty());
+    assert_eq!(count.load(Ordering::Relaxed), iterations * threads);
+    assert_eq!(sum.load(Ordering::Relaxed), iterations * (iterations - 1) / 2 * threads);
+}
+
+// Checks that each element is popped exactly once and that elements pushed by
+// the same producer are popped in order.
+#[test]
+fn mpmc_order() {
+    let (iterations, _) = stress_test_config();
+    let q = Arc::new(mpmc::Queue::<(usize, usize), 4>::new());
+    let producers = 2;
+    let handles: Vec<_> = (0..producers)
+        .map(|id| {
+            let q = q.clone();
+            thread::spawn(move || {
+                for i in 0..iterations {
+                    let mut v = (id, i);
+                    while let Err(e) = q.push(v) {
+                        v = e;
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    let mut next = vec![0; producers];
+    let mut received = 0;
+    while received < iterations * producers {
+        match q.pop() {
+            Some((id, i)) => {
+                assert_eq!(next[id], i);
+                next[id] += 1;
+                received += 1;
+            }
+            None => thread::yield_now(),
+        }
+    }
+    for h in handles {
+        h.join().unwrap();
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+mod helper;
+
+use std::{
+    panic,
+    sync::{Arc, Barrier},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+use portable_atomic_util::spin::{Mutex, Once, RwLock, TicketMutex};
+
+use self::helper::stress_test_config;
+
+macro_rules! test_mutex {
+    ($mutex_type:ident, $mod_name:ident) => {
+        mod $mod_name {
+            use super::*;
+
+            #[test]
+            fn lock() {
+                let mut m = $mutex_type::new(1);
+                assert!(!m.is_locked());
+                {
+                    let mut g = m.lock();
+                    assert!(m.is_locked());
+                    assert!(m.try_lock().is_none());
+                    *g += 1;
+                    assert_eq!(format!("{:?}", g), "2");
+                    assert_eq!(format!("{}", g), "2");
+                    assert_eq!(
+                        format!("{:?}", m),
+                        concat!(stringify!($mutex_type), " { data: <locked> }")
+                    );
+                }
+                assert!(!m.is_locked());
+                assert_eq!(format!("{:?}", m), concat!(stringify!($mutex_type), " { data: 2 }"));
+                *m.try_lock().unwrap() += 1;
+                *m.get_mut() += 1;
+                assert_eq!(m.into_inner(), 4);
+                assert_eq!($mutex_type::<i32>::default().into_inner(), 0);
+                assert_eq!($mutex_type::from(5).into_inner(), 5);
+                let m: &$mutex_type<[i32]> = &$mutex_type::new([1, 2]);
+                assert_eq!(m.lock().len(), 2);
+            }
+
+            #[test]
+            fn panic_unlocks() {
+                let m = $mutex_type::new(0);
+                let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+                    let _g = m.lock();
+                    panic!("lock");
+                }));
+                assert!(res.is_err());
+                // Not poisoned.
+                assert!(!m.is_locked());
+                *m.lock() += 1;
+                assert_eq!(*m.lock(), 1);
+            }
+
+            #[test]
+            fn stress() {
+                let (iterations, threads) = stress_test_config();
+                let m = Arc::new($mutex_type::new((0_usize, 0_usize)));
+                let handles: Vec<_> = (0..threads)
+                    .map(|_| {
+                        let m = m.clone();
+                        thread::spawn(move || {
+                            for _ in 0..iterations {
+                                let mut g = m.lock();
+                                // Non-atomic read-modify-write of two fields: this
+                                // fails if two threads hold the lock at the same time.
+                                let (a, b) = *g;
+                                assert_eq!(a, b);
+                                *g = (a + 1, b + 1);
+                                drop(g);
+                                // Spin locks hand over badly when there are more
+                                // threads than cores (e.g., on CI), so give other
+                                // threads a chance to run.
+                                thread::yield_now();
+                                if let Some(mut g) = m.try_lock() {
+                                    let (a, b) = *g;
+                                    assert_eq!(a, b);
+                                    *g = (a + 1, b + 1);
+                                }
+                            }
+                        })
+                    })
+                    .collect();
+                for h in handles {
+                    h.join().unwrap();
+                }
+                let (a, b) = *m.lock();
+                assert_eq!(a, b);
+                assert!(a >= iterations * threads);
+            }
+        }
+    };
+}
+test_mutex!(Mutex, mutex);
+test_mutex!(TicketMutex, ticket_mutex);
+
+#[test]
+fn rw_lock() {
+    let mut lock = RwLock::new(1);
+    {
+        let r1 = lock.read();
+        let r2 = lock.try_read().unwrap();
+        assert_eq!(lock.reader_count(), 2);
+        assert!(lock.try_write().is_none());
+        assert!(!lock.is_locked_exclusive());
+        assert_eq!(*r1 + *r2, 2);
+        assert_eq!(format!("{:?} {}", r1, r2), "1 1");
+        assert_eq!(format!("{:?}", lock), "RwLock { data: 1 }");
+    }
+    {
+        let mut w = lock.write();
+        assert!(lock.is_locked_exclusive());
+        assert!(lock.try_read().is_none());
+        assert!(lock.try_write().is_none());
+        assert_eq!(format!("{:?}", lock), "RwLock { data: <locked> }");
+        *w += 1;
+        assert_eq!(format!("{:?} {}", w, w), "2 2");
+    }
+    assert_eq!(lock.reader_count(), 0);
+    *lock.try_write().unwrap() += 1;
+    *lock.get_mut() += 1;
+    assert_eq!(lock.into_inner(), 4);
+    assert_eq!(RwLock::<i32>::default().into_inner(), 0);
+
+    let lock = RwLock::new(0);
+    let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        let _w = lock.write();
+        panic!("write");
+    }));
+    assert!(res.is_err());
+    assert_eq!(*lock.read(), 0);
+}
+
+#[test]
+fn rw_lock_stress() {
+    let (iterations, threads) = stress_test_config();
+    let lock = Arc::new(RwLock::new((0_usize, 0_usize)));
+    let handles: Vec<_> = (0..threads)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..iterations {
+                    if i % 2 == 0 {
+                        let mut w = lock.write();
+                        let (a, b) = *w;
+                        assert_eq!(a, b);
+                        *w = (a + 1, b + 1);
+                    } else {
+                        let r = lock.read();
+                        assert_eq!(r.0, r.1);
+                    }
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    let (a, b) = *lock.read();
+    assert_eq!(a, b);
+    assert_eq!(a, iterations * (0..threads).filter(|i| i % 2 == 0).count());
+}
+
+#[test]
+fn once() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static ONCE: Once = Once::new();
+    assert!(!ONCE.is_completed());
+    assert_eq!(format!("{:?}", ONCE), "Once { completed: false }");
+    let barrier = Arc::new(Barrier::new(8));
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                barrier.wait();
+                ONCE.call_once(|| {
+                    CALLS.fetch_add(1, Ordering::Relaxed);
+                });
+                assert!(ONCE.is_completed());
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+    assert!(ONCE.is_completed());
+
+    // Not poisoned.
+    let once = Once::new();
+    assert!(panic::catch_unwind(|| once.call_once(|| panic!("once"))).is_err());
+    assert!(!once.is_completed());
+    once.call_once(|| {});
+    assert!(once.is_completed());
+}
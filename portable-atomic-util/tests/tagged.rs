@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+#![cfg(any(feature = "std", feature = "alloc"))]
+
+mod helper;
+
+use std::{
+    sync::{Arc, Barrier},
+    thread,
+};
+
+use portable_atomic::{AtomicUsize, Ordering};
+use portable_atomic_util::tagged::{Queue, Stack};
+
+use self::helper::{stress_test_config, DropCounter};
+
+#[test]
+fn is_lock_free() {
+    assert_eq!(Stack::<u8>::is_lock_free(), Queue::<u8>::is_lock_free());
+}
+
+#[test]
+fn stack() {
+    static STACK: Stack<i32> = Stack::new();
+    assert!(STACK.is_empty());
+    assert_eq!(STACK.pop(), None);
+    for i in 0..10 {
+        STACK.push(i);
+    }
+    assert!(!STACK.is_empty());
+    for i in (0..10).rev() {
+        assert_eq!(STACK.pop(), Some(i));
+    }
+    assert_eq!(STACK.pop(), None);
+    // Reuse nodes in the free list.
+    STACK.push(1);
+    assert_eq!(STACK.pop(), Some(1));
+    assert_eq!(format!("{:?}", STACK), "Stack { is_empty: true }");
+}
+
+#[test]
+fn queue() {
+    let queue = Queue::new();
+    assert!(queue.is_empty());
+    assert_eq!(queue.pop(), None);
+    for i in 0..10 {
+        queue.push(i);
+    }
+    assert!(!queue.is_empty());
+    for i in 0..10 {
+        assert_eq!(queue.pop(), Some(i));
+    }
+    assert_eq!(queue.pop(), None);
+    queue.push(1);
+    assert_eq!(queue.pop(), Some(1));
+    assert_eq!(format!("{:?}", queue), "Queue { is_empty: true }");
+}
+
+#[test]
+fn drop_values() {
+    let dropped = Arc::new(AtomicUsize::new(0));
+    {
+        let stack = Stack::new();
+        let queue = Queue::new();
+        for _ in 0..10 {
+            stack.push(DropCounter(dropped.clone()));
+            queue.push(DropCounter(dropped.clone()));
+        }
+        drop(stack.pop());
+        drop(queue.pop());
+        assert_eq!(dropped.load(Ordering::Relaxed), 2);
+    }
+    assert_eq!(dropped.load(Ordering::Relaxed), 20);
+}
+
+#[test]
+fn stack_stress() {
+    let (iterations, threads) = stress_test_config();
+    let stack = Arc::new(Stack::new());
+    let sum = Arc::new(AtomicUsize::new(0));
+    let barrier = Arc::new(Barrier::new(threads));
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let (stack, sum, barrier) = (stack.clone(), sum.clone(), barrier.clone());
+            thread::spawn(move || {
+                barrier.wait();
+                for i in 0..iterations {
+                    stack.push(i);
+                    // Pop an element pushed by any thread.
+                    let v = loop {
+                        if let Some(v) = stack.pop() {
+                            break v;
+                        }
+                    };
+                    sum.fetch_add(v, Ordering::Relaxed);
+                    if i % 64 == 0 {
+                        thread::yield_now();
+                    }
+                }
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert!(stack.is_empty());
+    assert_eq!(sum.load(Ordering::Relaxed), iterations * (iterations - 1) / 2 * threads);
+}
+
+// Checks that each element is popped exactly once and that elements pushed by
+// the same producer are popped in order.
+#[test]
+fn queue_stress() {
+    let (iterations, threads) = stress_test_config();
+    let queue = Arc::new(Queue::new());
+    let barrier = Arc::new(Barrier::new(threads * 2));
+    let mut handles = vec![];
+    for id in 0..threads {
+        let (queue1, barrier1) = (queue.clone(), barrier.clone());
+        handles.push(thread::spawn(move || {
+            barrier1.wait();
+            for i in 0..iterations {
+                queue1.push((id, i));
+                if i % 64 == 0 {
+                    thread::yield_now();
+                }
+            }
+            vec![]
+        }));
+        let (queue, barrier) = (queue.clone(), barrier.clone());
+        handles.push(thread::spawn(move || {
+            barrier.wait();
+            let mut received = vec![];
+            while received.len() < iterations {
+                match queue.pop() {
+                    Some(v) => received.push(v),
+                    None => thread::yield_now(),
+                }
+            }
+            received
+        }));
+    }
+    let mut count = vec![0; threads];
+    for h in handles {
+        let mut next = vec![0; threads];
+        for (id, i) in h.join().unwrap() {
+            // Each consumer receives elements of the same producer in order.
+            assert!(next[id] <= i);
+            next[id] = i + 1;
+            count[id] += 1;
+        }
+    }
+    assert!(queue.is_empty());
+    assert_eq!(count, vec![iterations; threads]);
+}
@@ -0,0 +1,412 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Atomic enums (and other small `Copy` types), implemented on top of the atomic integer types.
+
+use core::{fmt, marker::PhantomData};
+
+use crate::Ordering;
+
+/// A type that can be stored in an [`AtomicEnum`] by converting it to and
+/// from a primitive integer.
+///
+/// This is usually implemented for field-less enums with a primitive
+/// representation (`#[repr(u8)]`, `#[repr(u16)]`, or `#[repr(u32)]`).
+/// If the `derive` feature is enabled, this can be derived for such enums with
+/// `#[derive(AtomicRepr)]`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::{AtomicEnum, AtomicRepr, Ordering};
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// #[repr(u8)]
+/// enum State {
+///     Idle,
+///     Running,
+///     Done,
+/// }
+///
+/// impl AtomicRepr for State {
+///     type Repr = u8;
+///     fn into_repr(self) -> u8 {
+///         self as u8
+///     }
+///     fn from_repr(repr: u8) -> Self {
+///         match repr {
+///             0 => State::Idle,
+///             1 => State::Running,
+///             2 => State::Done,
+///             _ => unreachable!(),
+///         }
+///     }
+/// }
+///
+/// let state = AtomicEnum::new(State::Idle);
+/// assert_eq!(
+///     state.compare_exchange(State::Idle, State::Running, Ordering::AcqRel, Ordering::Acquire),
+///     Ok(State::Idle),
+/// );
+/// assert_eq!(state.load(Ordering::Acquire), State::Running);
+/// ```
+pub trait AtomicRepr: Copy {
+    /// The primitive integer type that represents this type.
+    type Repr: AtomicReprInt;
+
+    /// Converts the value to its integer representation.
+    ///
+    /// Two values must be converted to the same integer if and only if they are
+    /// equal, because [`AtomicEnum::compare_exchange`] compares the integer representations.
+    fn into_repr(self) -> Self::Repr;
+
+    /// Converts the integer representation back to the value.
+    ///
+    /// [`AtomicEnum`] only calls this with integers previously returned by
+    /// [`into_repr`](Self::into_repr), so this may panic on other integers.
+    fn from_repr(repr: Self::Repr) -> Self;
+}
+
+/// A primitive integer type that can be used as [`AtomicRepr::Repr`].
+///
+/// This is implemented for `u8`, `u16`, and `u32` on targets where the
+/// corresponding atomic integer type is available, and cannot be implemented
+/// outside of this crate.
+pub trait AtomicReprInt: private::Sealed {}
+
+mod private {
+    use crate::Ordering;
+
+    // Operations of the atomic integer type that corresponds to the primitive integer type.
+    pub trait Sealed: Copy {
+        type Atomic;
+        fn new(v: Self) -> Self::Atomic;
+        fn is_lock_free() -> bool;
+        fn is_always_lock_free() -> bool;
+        fn into_inner(a: Self::Atomic) -> Self;
+        fn load(a: &Self::Atomic, order: Ordering) -> Self;
+        fn store(a: &Self::Atomic, v: Self, order: Ordering);
+        cfg_has_atomic_cas! {
+        fn swap(a: &Self::Atomic, v: Self, order: Ordering) -> Self;
+        fn compare_exchange(
+            a: &Self::Atomic,
+            current: Self,
+            new: Self,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<Self, Self>;
+        fn compare_exchange_weak(
+            a: &Self::Atomic,
+            current: Self,
+            new: Self,
+            success: Ordering,
+            failure: Ordering,
+        ) -> Result<Self, Self>;
+        } // cfg_has_atomic_cas!
+    }
+}
+
+macro_rules! atomic_repr_int {
+    ($int_type:ident, $atomic_type:ident) => {
+        impl AtomicReprInt for $int_type {}
+        impl private::Sealed for $int_type {
+            type Atomic = crate::$atomic_type;
+            #[inline]
+            fn new(v: Self) -> Self::Atomic {
+                crate::$atomic_type::new(v)
+            }
+            #[inline]
+            fn is_lock_free() -> bool {
+                crate::$atomic_type::is_lock_free()
+            }
+            #[inline]
+            fn is_always_lock_free() -> bool {
+                crate::$atomic_type::is_always_lock_free()
+            }
+            #[inline]
+            fn into_inner(a: Self::Atomic) -> Self {
+                a.into_inner()
+            }
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            fn load(a: &Self::Atomic, order: Ordering) -> Self {
+                a.load(order)
+            }
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            fn store(a: &Self::Atomic, v: Self, order: Ordering) {
+                a.store(v, order);
+            }
+            cfg_has_atomic_cas! {
+            #[inline]
+            #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+            fn swap(a: &Self::Atomic, v: Self, order: Ordering) -> Self {
+                a.swap(v, order)
+            }
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            fn compare_exchange(
+                a: &Self::Atomic,
+                current: Self,
+                new: Self,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self, Self> {
+                a.compare_exchange(current, new, success, failure)
+            }
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            fn compare_exchange_weak(
+                a: &Self::Atomic,
+                current: Self,
+                new: Self,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self, Self> {
+                a.compare_exchange_weak(current, new, success, failure)
+            }
+            } // cfg_has_atomic_cas!
+        }
+    };
+}
+
+cfg_has_atomic_8! {
+    atomic_repr_int!(u8, AtomicU8);
+}
+cfg_has_atomic_16! {
+    atomic_repr_int!(u16, AtomicU16);
+}
+cfg_has_atomic_32! {
+    atomic_repr_int!(u32, AtomicU32);
+}
+
+/// A value of type `E` which can be safely shared between threads.
+///
+/// This type has the same in-memory representation as the atomic integer type
+/// that corresponds to [`E::Repr`](AtomicRepr::Repr) (e.g., [`AtomicU8`](crate::AtomicU8)
+/// for `u8`), and stores values converted by [`AtomicRepr::into_repr`].
+///
+/// See [`AtomicRepr`] for an example.
+#[repr(transparent)]
+pub struct AtomicEnum<E: AtomicRepr> {
+    inner: <E::Repr as private::Sealed>::Atomic,
+    _marker: PhantomData<E>,
+}
+
+impl<E: AtomicRepr + Default> Default for AtomicEnum<E> {
+    /// Creates an `AtomicEnum` initialized to `E::default()`.
+    #[inline]
+    fn default() -> Self {
+        Self::new(E::default())
+    }
+}
+
+impl<E: AtomicRepr> From<E> for AtomicEnum<E> {
+    #[inline]
+    fn from(v: E) -> Self {
+        Self::new(v)
+    }
+}
+
+impl<E: AtomicRepr + fmt::Debug> fmt::Debug for AtomicEnum<E> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<E: AtomicRepr> AtomicEnum<E> {
+    /// Creates a new `AtomicEnum`.
+    ///
+    /// Unlike the `new` function of the atomic integer types, this is not a
+    /// `const fn`, because it calls [`AtomicRepr::into_repr`].
+    #[inline]
+    #[must_use]
+    pub fn new(v: E) -> Self {
+        Self { inner: <E::Repr as private::Sealed>::new(v.into_repr()), _marker: PhantomData }
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        <E::Repr as private::Sealed>::is_lock_free()
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// **Note:** If the atomic operation relies on dynamic CPU feature detection,
+    /// this type may be lock-free even if the function returns false.
+    #[inline]
+    #[must_use]
+    pub fn is_always_lock_free() -> bool {
+        <E::Repr as private::Sealed>::is_always_lock_free()
+    }
+
+    /// Consumes the atomic and returns the contained value.
+    #[inline]
+    pub fn into_inner(self) -> E {
+        E::from_repr(<E::Repr as private::Sealed>::into_inner(self.inner))
+    }
+
+    /// Loads a value from the atomic.
+    ///
+    /// `load` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn load(&self, order: Ordering) -> E {
+        E::from_repr(<E::Repr as private::Sealed>::load(&self.inner, order))
+    }
+
+    /// Stores a value into the atomic.
+    ///
+    /// `store` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn store(&self, val: E, order: Ordering) {
+        <E::Repr as private::Sealed>::store(&self.inner, val.into_repr(), order);
+    }
+
+    cfg_has_atomic_cas! {
+    /// Stores a value into the atomic, returning the previous value.
+    ///
+    /// `swap` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn swap(&self, val: E, order: Ordering) -> E {
+        E::from_repr(<E::Repr as private::Sealed>::swap(&self.inner, val.into_repr(), order))
+    }
+
+    /// Stores a value into the atomic if the current value is the same as
+    /// the `current` value.
+    ///
+    /// The values are compared by their integer representations.
+    ///
+    /// The return value is a result indicating whether the new value was written and
+    /// containing the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange(
+        &self,
+        current: E,
+        new: E,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<E, E> {
+        match <E::Repr as private::Sealed>::compare_exchange(
+            &self.inner,
+            current.into_repr(),
+            new.into_repr(),
+            success,
+            failure,
+        ) {
+            Ok(v) => Ok(E::from_repr(v)),
+            Err(v) => Err(E::from_repr(v)),
+        }
+    }
+
+    /// Stores a value into the atomic if the current value is the same as
+    /// the `current` value.
+    ///
+    /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+    /// allowed to spuriously fail even when the comparison succeeds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange_weak(
+        &self,
+        current: E,
+        new: E,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<E, E> {
+        match <E::Repr as private::Sealed>::compare_exchange_weak(
+            &self.inner,
+            current.into_repr(),
+            new.into_repr(),
+            success,
+            failure,
+        ) {
+            Ok(v) => Ok(E::from_repr(v)),
+            Err(v) => Err(E::from_repr(v)),
+        }
+    }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+    /// returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// See also the `fetch_update` method of the underlying atomic integer type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<E, E>
+    where
+        F: FnMut(E) -> Option<E>,
+    {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+    } // cfg_has_atomic_cas!
+}
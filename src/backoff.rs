@@ -0,0 +1,153 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::fmt;
+#[cfg(feature = "std")]
+use std::error;
+
+// Adapted from https://github.com/crossbeam-rs/crossbeam/blob/crossbeam-utils-0.8.7/crossbeam-utils/src/backoff.rs.
+// Adjusted to reduce spinning.
+/// Performs exponential backoff in spin loops.
+///
+/// Each call to [`snooze`](Self::snooze) spins for twice as long as the previous
+/// call, up to a limit. After the limit is reached, `snooze` yields the current
+/// thread to the OS scheduler if the `std` feature is enabled, and otherwise
+/// keeps spinning for the maximum duration.
+///
+/// This is used by the `fetch_update_with` methods of the atomic types between
+/// failed compare-and-swap attempts, and by the fallback implementation while
+/// waiting for a lock.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::{AtomicBool, Backoff, Ordering};
+///
+/// fn wait_until_ready(ready: &AtomicBool) {
+///     let mut backoff = Backoff::new();
+///     while !ready.load(Ordering::Acquire) {
+///         backoff.snooze();
+///     }
+/// }
+/// # wait_until_ready(&AtomicBool::new(true));
+/// ```
+pub struct Backoff {
+    step: u32,
+}
+
+// https://github.com/oneapi-src/oneTBB/blob/v2021.5.0/include/oneapi/tbb/detail/_utils.h#L46-L48
+const SPIN_LIMIT: u32 = 4;
+
+impl Backoff {
+    /// Creates a new `Backoff`.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { step: 0 }
+    }
+
+    /// Resets the `Backoff` to its initial state.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.step = 0;
+    }
+
+    /// Backs off in a spin loop.
+    #[inline]
+    pub fn snooze(&mut self) {
+        if self.step <= SPIN_LIMIT {
+            for _ in 0..1 << self.step {
+                crate::hint::spin_loop();
+            }
+            self.step += 1;
+        } else {
+            #[cfg(not(feature = "std"))]
+            for _ in 0..1 << self.step {
+                crate::hint::spin_loop();
+            }
+
+            #[cfg(all(feature = "std", not(portable_atomic_loom)))]
+            std::thread::yield_now();
+            #[cfg(all(feature = "std", portable_atomic_loom))]
+            loom::thread::yield_now();
+        }
+    }
+}
+
+impl Default for Backoff {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Backoff {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Backoff").field("step", &self.step).finish()
+    }
+}
+
+/// The error type returned by `fetch_update_with`.
+///
+/// This contains the value loaded last, and whether the operation failed because the
+/// function returned `None` or because the maximum number of retries was exceeded.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct FetchUpdateError<T> {
+    value: T,
+    exhausted: bool,
+}
+
+impl<T> FetchUpdateError<T> {
+    #[allow(dead_code)] // unused if CAS is not available
+    #[inline]
+    pub(crate) fn aborted(value: T) -> Self {
+        Self { value, exhausted: false }
+    }
+
+    #[allow(dead_code)] // unused if CAS is not available
+    #[inline]
+    pub(crate) fn exhausted(value: T) -> Self {
+        Self { value, exhausted: true }
+    }
+
+    /// Returns `true` if the operation failed because the maximum number of
+    /// retries was exceeded, or `false` if it failed because the function
+    /// returned `None`.
+    #[inline]
+    #[must_use]
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    /// Returns the value loaded last.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for FetchUpdateError<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FetchUpdateError")
+            .field("value", &self.value)
+            .field("exhausted", &self.exhausted)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for FetchUpdateError<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exhausted {
+            f.write_str("exceeded the maximum number of retries")
+        } else {
+            f.write_str("update function returned None")
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+impl<T: fmt::Debug> error::Error for FetchUpdateError<T> {}
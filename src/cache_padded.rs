@@ -1,9 +1,49 @@
 // SPDX-License-Identifier: Apache-2.0 OR MIT
 
-use core::ops;
+use core::{fmt, ops};
 
 // Adapted from https://github.com/crossbeam-rs/crossbeam/blob/9384f1eb2b356364e201ad38545e03c837d55f3a/crossbeam-utils/src/cache_padded.rs.
 /// Pads and aligns a value to the length of a cache line.
+///
+/// In concurrent programming, sometimes it is desirable to make sure commonly accessed pieces of
+/// data are not placed into the same cache line. Updating an atomic value invalidates the whole
+/// cache line it belongs to, which makes the next access to the same cache line slower for other
+/// CPU cores. Use `CachePadded` to ensure updating one piece of data doesn't invalidate other
+/// cached data.
+///
+/// # Size and alignment
+///
+/// Cache lines are assumed to be N bytes long, depending on the architecture:
+///
+/// - On x86_64, aarch64, arm64ec, and powerpc64, N = 128.
+/// - On arm, mips, mips32r6, mips64, mips64r6, sparc, and hexagon, N = 32.
+/// - On m68k, N = 16.
+/// - On s390x, N = 256.
+/// - On all others, N = 64.
+///
+/// Note that N is just a reasonable guess and is not guaranteed to match the actual cache line
+/// length of the machine the program is running on.
+///
+/// The size of `CachePadded<T>` is the smallest multiple of N bytes large enough to accommodate
+/// a value of type `T`.
+///
+/// The alignment of `CachePadded<T>` is the maximum of N bytes and the alignment of `T`.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::{AtomicUsize, CachePadded};
+///
+/// // Put the head and the tail of a queue into different cache lines.
+/// struct Queue {
+///     head: CachePadded<AtomicUsize>,
+///     tail: CachePadded<AtomicUsize>,
+/// }
+///
+/// let q = Queue { head: CachePadded::new(AtomicUsize::new(0)), tail: CachePadded::default() };
+/// assert!(core::mem::align_of_val(&q.head) >= 16);
+/// assert_eq!(q.tail.into_inner().into_inner(), 0);
+/// ```
 // Starting from Intel's Sandy Bridge, spatial prefetcher is now pulling pairs of 64-byte cache
 // lines at a time, so we have to align to 128 bytes rather than 64.
 //
@@ -89,15 +129,42 @@ use core::ops;
     )),
     repr(align(64))
 )]
-pub(crate) struct CachePadded<T> {
+#[derive(Clone, Copy, Default, Hash, PartialEq, Eq)]
+pub struct CachePadded<T> {
     value: T,
 }
 
 impl<T> CachePadded<T> {
+    /// Pads and aligns a value to the length of a cache line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::CachePadded;
+    ///
+    /// let padded_value = CachePadded::new(1);
+    /// ```
     #[inline]
-    pub(crate) const fn new(value: T) -> Self {
+    #[must_use]
+    pub const fn new(value: T) -> Self {
         Self { value }
     }
+
+    /// Returns the inner value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::CachePadded;
+    ///
+    /// let padded_value = CachePadded::new(7);
+    /// let value = padded_value.into_inner();
+    /// assert_eq!(value, 7);
+    /// ```
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.value
+    }
 }
 
 impl<T> ops::Deref for CachePadded<T> {
@@ -109,39 +176,30 @@ impl<T> ops::Deref for CachePadded<T> {
     }
 }
 
-// Adapted from https://github.com/crossbeam-rs/crossbeam/blob/crossbeam-utils-0.8.7/crossbeam-utils/src/backoff.rs.
-// Adjusted to reduce spinning.
-/// Performs exponential backoff in spin loops.
-pub(crate) struct Backoff {
-    step: u32,
+impl<T> ops::DerefMut for CachePadded<T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
 }
 
-// https://github.com/oneapi-src/oneTBB/blob/v2021.5.0/include/oneapi/tbb/detail/_utils.h#L46-L48
-const SPIN_LIMIT: u32 = 4;
-
-impl Backoff {
-    #[inline]
-    pub(crate) const fn new() -> Self {
-        Self { step: 0 }
+impl<T: fmt::Debug> fmt::Debug for CachePadded<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CachePadded").field("value", &self.value).finish()
     }
+}
 
+impl<T> From<T> for CachePadded<T> {
     #[inline]
-    pub(crate) fn snooze(&mut self) {
-        if self.step <= SPIN_LIMIT {
-            for _ in 0..1 << self.step {
-                #[allow(deprecated)]
-                core::sync::atomic::spin_loop_hint();
-            }
-            self.step += 1;
-        } else {
-            #[cfg(not(feature = "std"))]
-            for _ in 0..1 << self.step {
-                #[allow(deprecated)]
-                core::sync::atomic::spin_loop_hint();
-            }
+    fn from(t: T) -> Self {
+        Self::new(t)
+    }
+}
 
-            #[cfg(feature = "std")]
-            std::thread::yield_now();
-        }
+impl<T: fmt::Display> fmt::Display for CachePadded<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.value, f)
     }
 }
@@ -128,7 +128,7 @@ mod atomic_32_macros {
         all(
             feature = "fallback",
             any(
-                not(portable_atomic_no_atomic_cas),
+                all(not(portable_atomic_no_atomic_cas), not(feature = "require-address-free")),
                 portable_atomic_unsafe_assume_single_core,
                 feature = "critical-section",
                 target_arch = "avr",
@@ -137,6 +137,13 @@ mod atomic_32_macros {
         ),
         not(portable_atomic_no_atomic_64),
         not(any(target_pointer_width = "16", target_pointer_width = "32")),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
     ))
 )]
 #[cfg_attr(
@@ -145,7 +152,7 @@ mod atomic_32_macros {
         all(
             feature = "fallback",
             any(
-                target_has_atomic = "ptr",
+                all(target_has_atomic = "ptr", not(feature = "require-address-free")),
                 portable_atomic_unsafe_assume_single_core,
                 feature = "critical-section",
                 target_arch = "avr",
@@ -154,6 +161,13 @@ mod atomic_32_macros {
         ),
         target_has_atomic = "64",
         not(any(target_pointer_width = "16", target_pointer_width = "32")),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
         all(
             target_arch = "riscv32",
             not(any(miri, portable_atomic_sanitize_thread)),
@@ -190,7 +204,7 @@ mod atomic_64_macros {
         all(
             feature = "fallback",
             any(
-                not(portable_atomic_no_atomic_cas),
+                all(not(portable_atomic_no_atomic_cas), not(feature = "require-address-free")),
                 portable_atomic_unsafe_assume_single_core,
                 feature = "critical-section",
                 target_arch = "avr",
@@ -199,6 +213,13 @@ mod atomic_64_macros {
         ),
         not(portable_atomic_no_atomic_64),
         not(any(target_pointer_width = "16", target_pointer_width = "32")),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
     )))
 )]
 #[cfg_attr(
@@ -207,7 +228,7 @@ mod atomic_64_macros {
         all(
             feature = "fallback",
             any(
-                target_has_atomic = "ptr",
+                all(target_has_atomic = "ptr", not(feature = "require-address-free")),
                 portable_atomic_unsafe_assume_single_core,
                 feature = "critical-section",
                 target_arch = "avr",
@@ -216,6 +237,13 @@ mod atomic_64_macros {
         ),
         target_has_atomic = "64",
         not(any(target_pointer_width = "16", target_pointer_width = "32")),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
         all(
             target_arch = "riscv32",
             not(any(miri, portable_atomic_sanitize_thread)),
@@ -248,7 +276,7 @@ mod atomic_64_macros {
 }
 
 #[cfg_attr(
-    not(feature = "fallback"),
+    any(not(feature = "fallback"), feature = "require-address-free"),
     cfg(any(
         all(
             target_arch = "aarch64",
@@ -319,11 +347,33 @@ mod atomic_64_macros {
                 ),
             ),
         ),
+        all(
+            target_arch = "loongarch64",
+            not(portable_atomic_no_asm),
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(
+                target_feature = "scq",
+                portable_atomic_target_feature = "scq",
+                all(feature = "fallback", not(portable_atomic_no_outline_atomics)),
+            ),
+        ),
         all(target_arch = "s390x", not(portable_atomic_no_asm)),
+        all(
+            feature = "fallback",
+            any(
+                portable_atomic_unsafe_assume_single_core,
+                target_arch = "avr",
+                target_arch = "msp430",
+            ),
+        ),
     ))
 )]
 #[cfg_attr(
-    all(feature = "fallback", portable_atomic_no_cfg_target_has_atomic),
+    all(
+        feature = "fallback",
+        not(feature = "require-address-free"),
+        portable_atomic_no_cfg_target_has_atomic,
+    ),
     cfg(any(
         not(portable_atomic_no_atomic_cas),
         portable_atomic_unsafe_assume_single_core,
@@ -333,7 +383,11 @@ mod atomic_64_macros {
     ))
 )]
 #[cfg_attr(
-    all(feature = "fallback", not(portable_atomic_no_cfg_target_has_atomic)),
+    all(
+        feature = "fallback",
+        not(feature = "require-address-free"),
+        not(portable_atomic_no_cfg_target_has_atomic),
+    ),
     cfg(any(
         target_has_atomic = "ptr",
         portable_atomic_unsafe_assume_single_core,
@@ -356,7 +410,7 @@ mod atomic_128_macros {
     }
 }
 #[cfg_attr(
-    not(feature = "fallback"),
+    any(not(feature = "fallback"), feature = "require-address-free"),
     cfg(not(any(
         all(
             target_arch = "aarch64",
@@ -427,11 +481,33 @@ mod atomic_128_macros {
                 ),
             ),
         ),
+        all(
+            target_arch = "loongarch64",
+            not(portable_atomic_no_asm),
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(
+                target_feature = "scq",
+                portable_atomic_target_feature = "scq",
+                all(feature = "fallback", not(portable_atomic_no_outline_atomics)),
+            ),
+        ),
         all(target_arch = "s390x", not(portable_atomic_no_asm)),
+        all(
+            feature = "fallback",
+            any(
+                portable_atomic_unsafe_assume_single_core,
+                target_arch = "avr",
+                target_arch = "msp430",
+            ),
+        ),
     )))
 )]
 #[cfg_attr(
-    all(feature = "fallback", portable_atomic_no_cfg_target_has_atomic),
+    all(
+        feature = "fallback",
+        not(feature = "require-address-free"),
+        portable_atomic_no_cfg_target_has_atomic,
+    ),
     cfg(not(any(
         not(portable_atomic_no_atomic_cas),
         portable_atomic_unsafe_assume_single_core,
@@ -441,7 +517,11 @@ mod atomic_128_macros {
     )))
 )]
 #[cfg_attr(
-    all(feature = "fallback", not(portable_atomic_no_cfg_target_has_atomic)),
+    all(
+        feature = "fallback",
+        not(feature = "require-address-free"),
+        not(portable_atomic_no_cfg_target_has_atomic),
+    ),
     cfg(not(any(
         target_has_atomic = "ptr",
         portable_atomic_unsafe_assume_single_core,
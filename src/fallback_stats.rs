@@ -0,0 +1,165 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Contention statistics of the locks used by the fallback implementation.
+//!
+//! This module is only available when the `fallback-stats` feature is enabled.
+//!
+//! Atomic types whose `is_lock_free` returns `false` may be implemented using
+//! global seqlocks. Each atomic value is assigned to one of a fixed number of
+//! lock slots based on its address, and atomic values that share a slot
+//! contend with each other. This module provides the following counters for
+//! each slot:
+//!
+//! - [`optimistic_read_failures`](LockStats::optimistic_read_failures):
+//!   the number of loads that could not be performed optimistically because
+//!   a writer held the lock or modified the value concurrently. Such loads
+//!   acquire the lock instead.
+//! - [`write_lock_acquisitions`](LockStats::write_lock_acquisitions):
+//!   the number of times the lock was acquired, by operations that modify the
+//!   value and by loads whose optimistic read failed.
+//! - [`spin_iterations`](LockStats::spin_iterations): the number of times
+//!   [`Backoff::snooze`](crate::Backoff::snooze) was called while waiting for
+//!   the lock.
+//!
+//! Counters wrap around on overflow. If the fallback implementation is not
+//! used on the current target (or the atomic types that use it are
+//! implemented by disabling interrupts instead), there are no lock slots.
+//!
+//! # Examples
+//!
+//! ```
+//! use portable_atomic::fallback_stats;
+//!
+//! fallback_stats::reset();
+//! // ... run a workload ...
+//! let total = fallback_stats::total();
+//! println!(
+//!     "{} lock acquisitions, {} spin iterations",
+//!     total.write_lock_acquisitions(),
+//!     total.spin_iterations(),
+//! );
+//! for (slot, stats) in fallback_stats::locks().enumerate() {
+//!     if stats.spin_iterations() != 0 {
+//!         println!("slot {}: {:?}", slot, stats);
+//!     }
+//! }
+//! ```
+
+use core::fmt;
+
+use crate::imp;
+
+/// A snapshot of the counters of a lock slot, or the sum of the counters of
+/// all lock slots.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct LockStats {
+    optimistic_read_failures: usize,
+    write_lock_acquisitions: usize,
+    spin_iterations: usize,
+}
+
+impl LockStats {
+    #[allow(dead_code)] // unused if the fallback implementation is not used
+    #[inline]
+    pub(crate) fn new(
+        optimistic_read_failures: usize,
+        write_lock_acquisitions: usize,
+        spin_iterations: usize,
+    ) -> Self {
+        Self { optimistic_read_failures, write_lock_acquisitions, spin_iterations }
+    }
+
+    /// Returns the number of loads whose optimistic read failed.
+    #[inline]
+    #[must_use]
+    pub fn optimistic_read_failures(&self) -> usize {
+        self.optimistic_read_failures
+    }
+
+    /// Returns the number of times the lock was acquired.
+    #[inline]
+    #[must_use]
+    pub fn write_lock_acquisitions(&self) -> usize {
+        self.write_lock_acquisitions
+    }
+
+    /// Returns the number of spin iterations while waiting for the lock.
+    #[inline]
+    #[must_use]
+    pub fn spin_iterations(&self) -> usize {
+        self.spin_iterations
+    }
+}
+
+impl fmt::Debug for LockStats {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LockStats")
+            .field("optimistic_read_failures", &self.optimistic_read_failures)
+            .field("write_lock_acquisitions", &self.write_lock_acquisitions)
+            .field("spin_iterations", &self.spin_iterations)
+            .finish()
+    }
+}
+
+/// An iterator over the counters of each lock slot.
+///
+/// This is created by [`locks`].
+#[derive(Clone)]
+pub struct Locks {
+    index: usize,
+}
+
+impl Iterator for Locks {
+    type Item = LockStats;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let stats = imp::fallback_lock_stats(self.index)?;
+        self.index += 1;
+        Some(stats)
+    }
+}
+
+impl fmt::Debug for Locks {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Locks").field("index", &self.index).finish()
+    }
+}
+
+/// Returns an iterator over the counters of each lock slot.
+///
+/// Counters are read one by one while other threads may update them, so the
+/// result is not an atomic snapshot.
+#[inline]
+#[must_use]
+pub fn locks() -> Locks {
+    Locks { index: 0 }
+}
+
+/// Returns the sum of the counters of all lock slots.
+#[inline]
+#[must_use]
+pub fn total() -> LockStats {
+    locks().fold(LockStats::default(), |sum, stats| LockStats {
+        optimistic_read_failures: sum
+            .optimistic_read_failures
+            .wrapping_add(stats.optimistic_read_failures),
+        write_lock_acquisitions: sum
+            .write_lock_acquisitions
+            .wrapping_add(stats.write_lock_acquisitions),
+        spin_iterations: sum.spin_iterations.wrapping_add(stats.spin_iterations),
+    })
+}
+
+/// Resets the counters of all lock slots to `0`.
+///
+/// Updates that happen concurrently with this call may or may not be
+/// reset.
+#[inline]
+pub fn reset() {
+    imp::fallback_reset_lock_stats();
+}
@@ -0,0 +1,390 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+128-bit atomic implementation on LoongArch64 using ll.d/ld.d/sc.q.
+
+sc.q (added in LoongArch v1.1, available on LA664 and later) stores a 128-bit value
+if the LL bit set by the preceding ll.d is still set. Combined with ll.d and ld.d,
+it provides a 128-bit LL/SC sequence. There is no 128-bit single-copy atomic load
+instruction, so loads are also implemented by this sequence (with writing back the
+loaded value).
+
+If sc.q is not available at compile-time, we use run-time detection (through CPUCFG)
+and use fallback implementation if it is not available.
+
+Note: On Miri and ThreadSanitizer which do not support inline assembly, we don't use
+this module and use fallback implementation instead.
+
+Refs:
+- LoongArch Reference Manual Volume 1: Basic Architecture
+  https://loongson.github.io/LoongArch-Documentation/LoongArch-Vol1-EN.html
+- atomic-maybe-uninit https://github.com/taiki-e/atomic-maybe-uninit
+*/
+
+include!("macros.rs");
+
+#[cfg(not(any(target_feature = "scq", portable_atomic_target_feature = "scq")))]
+#[path = "../fallback/outline_atomics.rs"]
+mod fallback;
+
+#[cfg(not(portable_atomic_no_outline_atomics))]
+#[cfg(any(test, not(any(target_feature = "scq", portable_atomic_target_feature = "scq"))))]
+#[path = "../detect/loongarch.rs"]
+mod detect;
+
+use core::{arch::asm, sync::atomic::Ordering};
+
+use crate::utils::{Pair, U128};
+
+// Asserts that the function is called in the correct context.
+macro_rules! debug_assert_scq {
+    () => {
+        #[cfg(not(any(target_feature = "scq", portable_atomic_target_feature = "scq")))]
+        {
+            debug_assert!(detect::detect().has_scq());
+        }
+    };
+}
+
+// LLVM supports sc.q mnemonic since LLVM 20, so we use .word directive.
+//
+// sc.q rd, rk, rj stores rd (lower 64 bits) and rk (upper 64 bits) to the
+// 16-byte aligned memory location at rj, and writes 1 to rd on success,
+// 0 on failure.
+//
+// This uses fixed registers: rd = $t0 ($r12), rk = $t1 ($r13), rj = $a0 ($r4).
+macro_rules! sc_q {
+    () => {
+        // sc.q $t0, $t1, $a0
+        ".word 0x3857348C"
+    };
+}
+
+// All operations in this module are SeqCst.
+// dbar 0 is a full barrier and we use it before and after the LL/SC loop. The barrier between
+// ll.d and ld.d is needed to prevent ld.d from being reordered before ll.d.
+
+#[inline]
+unsafe fn atomic_load_scq(src: *mut u128) -> u128 {
+    debug_assert!(src as usize % 16 == 0);
+    debug_assert_scq!();
+
+    // SAFETY: the caller must guarantee that `src` is valid for both writes and
+    // reads, 16-byte aligned, that there are no concurrent non-atomic operations,
+    // and that the CPU supports sc.q.
+    unsafe {
+        let (out_lo, out_hi);
+        asm!(
+            "dbar 0",
+            "2:",
+                "ll.d {out_lo}, $a0, 0",
+                "dbar 0",
+                "ld.d {out_hi}, $a0, 8",
+                "move $t0, {out_lo}",
+                "move $t1, {out_hi}",
+                sc_q!(),
+                "beqz $t0, 2b",
+            "dbar 0",
+            out_lo = out(reg) out_lo,
+            out_hi = out(reg) out_hi,
+            in("$a0") src,
+            out("$t0") _,
+            out("$t1") _,
+            options(nostack, preserves_flags),
+        );
+        U128 { pair: Pair { lo: out_lo, hi: out_hi } }.whole
+    }
+}
+
+#[inline]
+unsafe fn atomic_compare_exchange_scq(dst: *mut u128, old: u128, new: u128) -> (u128, bool) {
+    debug_assert!(dst as usize % 16 == 0);
+    debug_assert_scq!();
+
+    // SAFETY: the caller must guarantee that `dst` is valid for both writes and
+    // reads, 16-byte aligned, that there are no concurrent non-atomic operations,
+    // and that the CPU supports sc.q.
+    let prev = unsafe {
+        let old = U128 { whole: old };
+        let new = U128 { whole: new };
+        let (prev_lo, prev_hi);
+        asm!(
+            "dbar 0",
+            "2:",
+                "ll.d {prev_lo}, $a0, 0",
+                "dbar 0",
+                "ld.d {prev_hi}, $a0, 8",
+                "bne {prev_lo}, {old_lo}, 3f",
+                "bne {prev_hi}, {old_hi}, 3f",
+                "move $t0, {new_lo}",
+                sc_q!(),
+                "beqz $t0, 2b",
+            "3:",
+            "dbar 0",
+            prev_lo = out(reg) prev_lo,
+            prev_hi = out(reg) prev_hi,
+            old_lo = in(reg) old.pair.lo,
+            old_hi = in(reg) old.pair.hi,
+            new_lo = in(reg) new.pair.lo,
+            in("$a0") dst,
+            out("$t0") _,
+            in("$t1") new.pair.hi,
+            options(nostack, preserves_flags),
+        );
+        U128 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    };
+    (prev, prev == old)
+}
+
+// 128-bit atomic load by two 64-bit atomic loads.
+#[inline]
+unsafe fn byte_wise_atomic_load(src: *const u128) -> u128 {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        let (out_lo, out_hi);
+        asm!(
+            "ld.d {out_lo}, {src}, 0",
+            "ld.d {out_hi}, {src}, 8",
+            src = in(reg) src,
+            out_lo = out(reg) out_lo,
+            out_hi = out(reg) out_hi,
+            options(pure, nostack, preserves_flags, readonly),
+        );
+        U128 { pair: Pair { lo: out_lo, hi: out_hi } }.whole
+    }
+}
+
+#[inline(always)]
+unsafe fn atomic_update_scq<F>(dst: *mut u128, mut f: F) -> u128
+where
+    F: FnMut(u128) -> u128,
+{
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        // This is not single-copy atomic reads, but this is ok because subsequent
+        // CAS will check for consistency.
+        //
+        // Note that the C++20 memory model does not allow mixed-sized atomic access,
+        // so we must use inline assembly to implement this.
+        // (i.e., byte-wise atomic based on the standard library's atomic types
+        // cannot be used here).
+        let mut prev = byte_wise_atomic_load(dst);
+        loop {
+            let next = f(prev);
+            match atomic_compare_exchange_scq(dst, prev, next) {
+                (_, true) => return prev,
+                (v, false) => prev = v,
+            }
+        }
+    }
+}
+
+macro_rules! atomic_with_ifunc {
+    (
+        unsafe fn $name:ident($($arg_pat:ident: $arg_ty:ty),*) $(-> $ret_ty:ty)? {
+            $($scq_fn_body:tt)*
+        }
+        fallback = $seqcst_fallback_fn:ident
+    ) => {
+        #[inline]
+        unsafe fn $name($($arg_pat: $arg_ty,)* _: Ordering) $(-> $ret_ty)? {
+            unsafe fn scq_fn($($arg_pat: $arg_ty),*) $(-> $ret_ty)? {
+                $($scq_fn_body)*
+            }
+            // If sc.q is available at compile-time, we can always use scq_fn.
+            #[cfg(any(target_feature = "scq", portable_atomic_target_feature = "scq"))]
+            // SAFETY: the caller must uphold the safety contract.
+            // cfg guarantees that sc.q is available at compile-time.
+            unsafe {
+                scq_fn($($arg_pat),*)
+            }
+            // Otherwise, we need to do run-time detection and can use scq_fn only if sc.q is available.
+            #[cfg(not(any(target_feature = "scq", portable_atomic_target_feature = "scq")))]
+            // SAFETY: the caller must uphold the safety contract.
+            // we only calls scq_fn if sc.q is available.
+            unsafe {
+                ifunc!(unsafe fn($($arg_pat: $arg_ty),*) $(-> $ret_ty)? {
+                    if detect::detect().has_scq() {
+                        scq_fn
+                    } else {
+                        // Use SeqCst because sc.q-based implementation is always SeqCst.
+                        fallback::$seqcst_fallback_fn
+                    }
+                })
+            }
+        }
+    };
+}
+
+atomic_with_ifunc! {
+    unsafe fn atomic_load(src: *mut u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_load_scq(src) }
+    }
+    fallback = atomic_load_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_store(dst: *mut u128, val: u128) {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            atomic_update_scq(dst, |_| val);
+        }
+    }
+    fallback = atomic_store_seqcst
+}
+#[inline]
+unsafe fn atomic_compare_exchange(
+    dst: *mut u128,
+    old: u128,
+    new: u128,
+    success: Ordering,
+    _failure: Ordering,
+) -> Result<u128, u128> {
+    atomic_with_ifunc! {
+        unsafe fn cmpxchg(dst: *mut u128, old: u128, new: u128) -> (u128, bool) {
+            // SAFETY: the caller must uphold the safety contract.
+            unsafe { atomic_compare_exchange_scq(dst, old, new) }
+        }
+        fallback = atomic_compare_exchange_seqcst
+    }
+    // SAFETY: the caller must guarantee that `dst` is valid for both writes and
+    // reads, 16-byte aligned, and that there are no different kinds of concurrent accesses.
+    let (prev, ok) = unsafe { cmpxchg(dst, old, new, success) };
+    if ok {
+        Ok(prev)
+    } else {
+        Err(prev)
+    }
+}
+
+// atomic_compare_exchange_scq retries on spurious failure of sc.q, so it is always strong.
+use atomic_compare_exchange as atomic_compare_exchange_weak;
+
+atomic_with_ifunc! {
+    unsafe fn atomic_swap(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |_| val) }
+    }
+    fallback = atomic_swap_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_add(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| x.wrapping_add(val)) }
+    }
+    fallback = atomic_add_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_sub(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| x.wrapping_sub(val)) }
+    }
+    fallback = atomic_sub_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_and(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| x & val) }
+    }
+    fallback = atomic_and_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_nand(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| !(x & val)) }
+    }
+    fallback = atomic_nand_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_or(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| x | val) }
+    }
+    fallback = atomic_or_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_xor(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| x ^ val) }
+    }
+    fallback = atomic_xor_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_max(dst: *mut u128, val: u128) -> u128 {
+        #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            atomic_update_scq(dst, |x| core::cmp::max(x as i128, val as i128) as u128)
+        }
+    }
+    fallback = atomic_max_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_umax(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| core::cmp::max(x, val)) }
+    }
+    fallback = atomic_umax_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_min(dst: *mut u128, val: u128) -> u128 {
+        #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            atomic_update_scq(dst, |x| core::cmp::min(x as i128, val as i128) as u128)
+        }
+    }
+    fallback = atomic_min_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_umin(dst: *mut u128, val: u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| core::cmp::min(x, val)) }
+    }
+    fallback = atomic_umin_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_not(dst: *mut u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, |x| !x) }
+    }
+    fallback = atomic_not_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_neg(dst: *mut u128) -> u128 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_scq(dst, u128::wrapping_neg) }
+    }
+    fallback = atomic_neg_seqcst
+}
+
+#[inline]
+fn is_lock_free() -> bool {
+    #[cfg(any(target_feature = "scq", portable_atomic_target_feature = "scq"))]
+    {
+        // sc.q is available at compile-time.
+        true
+    }
+    #[cfg(not(any(target_feature = "scq", portable_atomic_target_feature = "scq")))]
+    {
+        detect::detect().has_scq()
+    }
+}
+const IS_ALWAYS_LOCK_FREE: bool =
+    cfg!(any(target_feature = "scq", portable_atomic_target_feature = "scq"));
+
+atomic128!(AtomicI128, i128, atomic_max, atomic_min);
+atomic128!(AtomicU128, u128, atomic_umax, atomic_umin);
+
+#[allow(clippy::undocumented_unsafe_blocks, clippy::wildcard_imports)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_atomic_int!(i128);
+    test_atomic_int!(u128);
+
+    // load/store/swap implementation is not affected by signedness, so it is
+    // enough to test only unsigned types.
+    stress_test!(u128);
+}
@@ -18,6 +18,19 @@ See README.md for details.
 )]
 pub(super) mod aarch64;
 
+// loongarch64
+#[cfg(all(
+    target_arch = "loongarch64",
+    not(portable_atomic_no_asm),
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(
+        target_feature = "scq",
+        portable_atomic_target_feature = "scq",
+        all(feature = "fallback", not(portable_atomic_no_outline_atomics)),
+    ),
+))]
+pub(super) mod loongarch64;
+
 // powerpc64
 #[cfg(all(
     target_arch = "powerpc64",
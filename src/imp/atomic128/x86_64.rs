@@ -20,12 +20,20 @@ include!("macros.rs");
 
 #[cfg(not(any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b")))]
 #[path = "../fallback/outline_atomics.rs"]
+// Load/store of fallback are unused if VMOVDQA is selected at compile-time.
+#[cfg_attr(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    allow(dead_code)
+)]
 mod fallback;
 
 #[cfg(not(portable_atomic_no_outline_atomics))]
 #[cfg(not(target_env = "sgx"))]
 #[cfg_attr(
-    not(target_feature = "sse"),
+    any(
+        not(target_feature = "sse"),
+        all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    ),
     cfg(not(any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b")))
 )]
 #[path = "../detect/x86_64.rs"]
@@ -49,25 +57,34 @@ macro_rules! debug_assert_cmpxchg16b {
         }
     };
 }
-#[cfg(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")))]
-#[cfg(target_feature = "sse")]
+#[cfg(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")), target_feature = "sse"),
+))]
 macro_rules! debug_assert_vmovdqa_atomic {
-    () => {{
-        debug_assert_cmpxchg16b!();
-        debug_assert!(detect::detect().has_vmovdqa_atomic());
-    }};
+    () => {
+        #[cfg(not(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)))]
+        {
+            debug_assert_cmpxchg16b!();
+            debug_assert!(detect::detect().has_vmovdqa_atomic());
+        }
+    };
 }
 
-#[cfg(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")))]
-#[cfg(target_feature = "sse")]
+#[cfg(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")), target_feature = "sse"),
+))]
 #[cfg(target_pointer_width = "32")]
 macro_rules! ptr_modifier {
     () => {
         ":e"
     };
 }
-#[cfg(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")))]
-#[cfg(target_feature = "sse")]
+#[cfg(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")), target_feature = "sse"),
+))]
 #[cfg(target_pointer_width = "64")]
 macro_rules! ptr_modifier {
     () => {
@@ -146,8 +163,16 @@ unsafe fn cmpxchg16b(dst: *mut u128, old: u128, new: u128) -> (u128, bool) {
 // use cases such as kernels and firmware that should not use vector registers.
 // So, do not use vector registers unless SSE target feature is enabled.
 // See also https://github.com/rust-lang/rust/blob/1.80.0/src/doc/rustc/src/platform-support/x86_64-unknown-none.md.
-#[cfg(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")))]
-#[cfg(target_feature = "sse")]
+//
+// The CPU vendor cannot be known at compile-time, so VMOVDQA is selected at
+// compile-time only when the AVX target feature is enabled and the user asserts
+// that the program only runs on the above vendors' CPUs by
+// `--cfg portable_atomic_unsafe_assume_vmovdqa_atomic`. Otherwise, we use
+// run-time detection.
+#[cfg(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")), target_feature = "sse"),
+))]
 #[target_feature(enable = "avx")]
 #[inline]
 unsafe fn atomic_load_vmovdqa(src: *mut u128) -> u128 {
@@ -168,8 +193,10 @@ unsafe fn atomic_load_vmovdqa(src: *mut u128) -> u128 {
         core::mem::transmute(out)
     }
 }
-#[cfg(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")))]
-#[cfg(target_feature = "sse")]
+#[cfg(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(not(any(portable_atomic_no_outline_atomics, target_env = "sgx")), target_feature = "sse"),
+))]
 #[target_feature(enable = "avx")]
 #[inline]
 unsafe fn atomic_store_vmovdqa(dst: *mut u128, val: u128, order: Ordering) {
@@ -212,9 +239,12 @@ unsafe fn atomic_store_vmovdqa(dst: *mut u128, val: u128, order: Ordering) {
     }
 }
 
-#[cfg(not(all(
-    any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
-    any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
+#[cfg(not(any(
+    all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+    all(
+        any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
+        any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
+    ),
 )))]
 macro_rules! load_store_detect {
     (
@@ -260,8 +290,16 @@ macro_rules! load_store_detect {
 
 #[inline]
 unsafe fn atomic_load(src: *mut u128, _order: Ordering) -> u128 {
+    #[cfg(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic))]
+    // SAFETY: the caller must uphold the safety contract.
+    // cfg guarantees that VMOVDQA is atomic on the CPU that runs this code.
+    unsafe {
+        // atomic load by vmovdqa is always SeqCst.
+        atomic_load_vmovdqa(src)
+    }
     // We only use VMOVDQA when SSE is enabled. See atomic_load_vmovdqa() for more.
     // SGX doesn't support CPUID.
+    #[cfg(not(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)))]
     #[cfg(all(
         any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
         any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
@@ -272,9 +310,16 @@ unsafe fn atomic_load(src: *mut u128, _order: Ordering) -> u128 {
         // cmpxchg16b is always SeqCst.
         atomic_load_cmpxchg16b(src)
     }
-    #[cfg(not(all(
-        any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
-        any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
+    #[cfg(not(any(
+        all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+        all(
+            any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
+            any(
+                portable_atomic_no_outline_atomics,
+                target_env = "sgx",
+                not(target_feature = "sse")
+            ),
+        ),
     )))]
     // SAFETY: the caller must uphold the safety contract.
     unsafe {
@@ -289,6 +334,7 @@ unsafe fn atomic_load(src: *mut u128, _order: Ordering) -> u128 {
     }
 }
 // See cmpxchg16b() for target_feature(enable).
+#[cfg(not(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)))]
 #[cfg_attr(
     not(portable_atomic_no_cmpxchg16b_target_feature),
     target_feature(enable = "cmpxchg16b")
@@ -337,8 +383,15 @@ unsafe fn atomic_load_cmpxchg16b(src: *mut u128) -> u128 {
 
 #[inline]
 unsafe fn atomic_store(dst: *mut u128, val: u128, order: Ordering) {
+    #[cfg(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic))]
+    // SAFETY: the caller must uphold the safety contract.
+    // cfg guarantees that VMOVDQA is atomic on the CPU that runs this code.
+    unsafe {
+        atomic_store_vmovdqa(dst, val, order);
+    }
     // We only use VMOVDQA when SSE is enabled. See atomic_load_vmovdqa() for more.
     // SGX doesn't support CPUID.
+    #[cfg(not(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)))]
     #[cfg(all(
         any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
         any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
@@ -350,9 +403,16 @@ unsafe fn atomic_store(dst: *mut u128, val: u128, order: Ordering) {
         let _ = order;
         atomic_store_cmpxchg16b(dst, val);
     }
-    #[cfg(not(all(
-        any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
-        any(portable_atomic_no_outline_atomics, target_env = "sgx", not(target_feature = "sse")),
+    #[cfg(not(any(
+        all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic),
+        all(
+            any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b"),
+            any(
+                portable_atomic_no_outline_atomics,
+                target_env = "sgx",
+                not(target_feature = "sse")
+            ),
+        ),
     )))]
     // SAFETY: the caller must uphold the safety contract.
     unsafe {
@@ -391,6 +451,7 @@ unsafe fn atomic_store(dst: *mut u128, val: u128, order: Ordering) {
     }
 }
 // See cmpxchg16b() for target_feature(enable).
+#[cfg(not(all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)))]
 #[cfg_attr(
     not(portable_atomic_no_cmpxchg16b_target_feature),
     target_feature(enable = "cmpxchg16b")
@@ -0,0 +1,457 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+64-bit atomic implementation using ldrexd/strexd on Armv7-A/R (and AArch32 Armv8-A/R).
+
+This is used only on targets where core does not provide 64-bit atomics
+(e.g., custom bare-metal targets with max-atomic-width 32). Built-in Armv7+
+A/R-profile targets provide 64-bit atomics in core, so we use them.
+
+M-profile doesn't have ldrexd/strexd, so we don't use this module there.
+
+Note: On Miri and ThreadSanitizer which do not support inline assembly, we don't use
+this module and use fallback implementation instead.
+
+Refs:
+- Arm Architecture Reference Manual ARMv7-A and ARMv7-R edition
+  A3.5.3 Atomicity in the ARM architecture
+  A3.4 Synchronization and semaphores
+- LDREXD: https://developer.arm.com/documentation/ddi0406/cb/Application-Level-Architecture/Instruction-Details/Alphabetical-list-of-instructions/LDREXD
+- STREXD: https://developer.arm.com/documentation/ddi0406/cb/Application-Level-Architecture/Instruction-Details/Alphabetical-list-of-instructions/STREXD
+- atomic-maybe-uninit https://github.com/taiki-e/atomic-maybe-uninit
+
+Note that in ARM state, the first register of LDREXD/STREXD must be an even-numbered
+register and the second register must be the next consecutive register. So we use
+r0/r1 pair and r2/r3 pair for them. (Thumb state doesn't have this restriction,
+but using the same registers in both states is fine.)
+*/
+
+include!("macros.rs");
+
+#[cfg(not(portable_atomic_no_asm))]
+use core::arch::asm;
+use core::sync::atomic::Ordering;
+
+use crate::utils::{Pair, U64};
+
+#[cfg(target_endian = "little")]
+macro_rules! select_le_or_be {
+    ($le:expr, $be:expr) => {
+        $le
+    };
+}
+#[cfg(target_endian = "big")]
+macro_rules! select_le_or_be {
+    ($le:expr, $be:expr) => {
+        $be
+    };
+}
+
+// Fences for RMW and CAS.
+// Release and stronger orderings need a fence before the operation, and
+// Acquire and stronger orderings need a fence after the operation.
+// https://www.cl.cam.ac.uk/~pes20/cpp/cpp0xmappings.html
+macro_rules! atomic_rmw {
+    ($op:ident, $order:ident) => {
+        match $order {
+            Ordering::Relaxed => $op!("", ""),
+            Ordering::Acquire => $op!("dmb ish", ""),
+            Ordering::Release => $op!("", "dmb ish"),
+            Ordering::AcqRel | Ordering::SeqCst => $op!("dmb ish", "dmb ish"),
+            _ => unreachable!(),
+        }
+    };
+}
+
+// -----------------------------------------------------------------------------
+// load/store
+
+#[inline]
+unsafe fn atomic_load(src: *mut u64, order: Ordering) -> u64 {
+    debug_assert!(src as usize % 8 == 0);
+
+    // SAFETY: the caller must uphold the safety contract.
+    //
+    // LDREXD (by itself) is single-copy atomic on Armv7, so we don't need the
+    // corresponding STREXD here. (This also means that this is usable for
+    // read-only memory.)
+    // Refs: A3.5.3 of the Arm Architecture Reference Manual ARMv7-A and ARMv7-R edition
+    unsafe {
+        let (prev_lo, prev_hi);
+        macro_rules! atomic_load {
+            ($acquire:tt) => {
+                asm!(
+                    "ldrexd r0, r1, [{src}]",
+                    "clrex",
+                    $acquire,
+                    src = in(reg) src,
+                    out("r0") prev_lo,
+                    out("r1") prev_hi,
+                    options(nostack, preserves_flags),
+                )
+            };
+        }
+        match order {
+            Ordering::Relaxed => atomic_load!(""),
+            Ordering::Acquire | Ordering::SeqCst => atomic_load!("dmb ish"),
+            _ => unreachable!(),
+        }
+        U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    }
+}
+
+#[inline]
+unsafe fn atomic_store(dst: *mut u64, val: u64, order: Ordering) {
+    debug_assert!(dst as usize % 8 == 0);
+
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        let val = U64 { whole: val };
+        macro_rules! atomic_store {
+            ($acquire:tt, $release:tt) => {
+                asm!(
+                    $release,
+                    "2:",
+                        // LDREXD is needed to set the exclusive monitor for STREXD.
+                        "ldrexd r0, r1, [{dst}]",
+                        "strexd {r}, r2, r3, [{dst}]",
+                        // 0 if the store was successful, 1 if no store was performed
+                        "cmp {r}, #0",
+                        "bne 2b",
+                    $acquire,
+                    dst = in(reg) dst,
+                    r = out(reg) _,
+                    out("r0") _,
+                    out("r1") _,
+                    in("r2") val.pair.lo,
+                    in("r3") val.pair.hi,
+                    // Do not use `preserves_flags` because CMP modifies the condition flags.
+                    options(nostack),
+                )
+            };
+        }
+        match order {
+            Ordering::Relaxed => atomic_store!("", ""),
+            Ordering::Release => atomic_store!("", "dmb ish"),
+            Ordering::SeqCst => atomic_store!("dmb ish", "dmb ish"),
+            _ => unreachable!(),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// compare_exchange
+
+#[inline]
+unsafe fn atomic_compare_exchange(
+    dst: *mut u64,
+    old: u64,
+    new: u64,
+    success: Ordering,
+    failure: Ordering,
+) -> Result<u64, u64> {
+    debug_assert!(dst as usize % 8 == 0);
+    let order = crate::utils::upgrade_success_ordering(success, failure);
+
+    // SAFETY: the caller must uphold the safety contract.
+    let prev = unsafe {
+        let old = U64 { whole: old };
+        let new = U64 { whole: new };
+        let (prev_lo, prev_hi);
+        macro_rules! cmpxchg {
+            ($acquire:tt, $release:tt) => {
+                asm!(
+                    $release,
+                    "2:",
+                        "ldrexd r0, r1, [{dst}]",
+                        "cmp r0, {old_lo}",
+                        "bne 3f",
+                        "cmp r1, {old_hi}",
+                        "bne 3f",
+                        "strexd {r}, r2, r3, [{dst}]",
+                        // 0 if the store was successful, 1 if no store was performed
+                        "cmp {r}, #0",
+                        "bne 2b",
+                        "b 4f",
+                    "3:",
+                        "clrex",
+                    "4:",
+                    $acquire,
+                    dst = in(reg) dst,
+                    old_lo = in(reg) old.pair.lo,
+                    old_hi = in(reg) old.pair.hi,
+                    r = out(reg) _,
+                    out("r0") prev_lo,
+                    out("r1") prev_hi,
+                    in("r2") new.pair.lo,
+                    in("r3") new.pair.hi,
+                    // Do not use `preserves_flags` because CMP modifies the condition flags.
+                    options(nostack),
+                )
+            };
+        }
+        atomic_rmw!(cmpxchg, order);
+        U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    };
+    if prev == old {
+        Ok(prev)
+    } else {
+        Err(prev)
+    }
+}
+
+// TODO: Armv7 has weak CAS (STREXD can fail spuriously), but we currently
+// always use strong CAS.
+use self::atomic_compare_exchange as atomic_compare_exchange_weak;
+
+// -----------------------------------------------------------------------------
+// RMW
+
+#[inline]
+unsafe fn atomic_swap(dst: *mut u64, val: u64, order: Ordering) -> u64 {
+    debug_assert!(dst as usize % 8 == 0);
+
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        let val = U64 { whole: val };
+        let (prev_lo, prev_hi);
+        macro_rules! swap {
+            ($acquire:tt, $release:tt) => {
+                asm!(
+                    $release,
+                    "2:",
+                        "ldrexd r0, r1, [{dst}]",
+                        "strexd {r}, r2, r3, [{dst}]",
+                        // 0 if the store was successful, 1 if no store was performed
+                        "cmp {r}, #0",
+                        "bne 2b",
+                    $acquire,
+                    dst = in(reg) dst,
+                    r = out(reg) _,
+                    out("r0") prev_lo,
+                    out("r1") prev_hi,
+                    in("r2") val.pair.lo,
+                    in("r3") val.pair.hi,
+                    // Do not use `preserves_flags` because CMP modifies the condition flags.
+                    options(nostack),
+                )
+            };
+        }
+        atomic_rmw!(swap, order);
+        U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    }
+}
+
+/// Atomic RMW by LL/SC loop (3 arguments)
+/// `unsafe fn(dst: *mut u64, val: u64, order: Ordering) -> u64;`
+///
+/// `$op` can use the following registers:
+/// - val_lo/val_hi pair: val argument (read-only for `$op`)
+/// - r0/r1 pair: previous value loaded by ll (read-only for `$op`)
+/// - r2/r3 pair: new value that will be stored by sc
+macro_rules! atomic_rmw_ll_sc_3 {
+    ($name:ident, $($op:tt)*) => {
+        #[inline]
+        unsafe fn $name(dst: *mut u64, val: u64, order: Ordering) -> u64 {
+            debug_assert!(dst as usize % 8 == 0);
+            // SAFETY: the caller must uphold the safety contract.
+            unsafe {
+                let val = U64 { whole: val };
+                let (prev_lo, prev_hi);
+                macro_rules! op {
+                    ($acquire:tt, $release:tt) => {
+                        asm!(
+                            $release,
+                            "2:",
+                                "ldrexd r0, r1, [{dst}]",
+                                $($op)*
+                                "strexd {r}, r2, r3, [{dst}]",
+                                // 0 if the store was successful, 1 if no store was performed
+                                "cmp {r}, #0",
+                                "bne 2b",
+                            $acquire,
+                            dst = in(reg) dst,
+                            val_lo = in(reg) val.pair.lo,
+                            val_hi = in(reg) val.pair.hi,
+                            r = out(reg) _,
+                            out("r0") prev_lo,
+                            out("r1") prev_hi,
+                            out("r2") _,
+                            out("r3") _,
+                            // Do not use `preserves_flags` because CMP modifies the condition flags.
+                            options(nostack),
+                        )
+                    };
+                }
+                atomic_rmw!(op, order);
+                U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+            }
+        }
+    };
+}
+/// Atomic RMW by LL/SC loop (2 arguments)
+/// `unsafe fn(dst: *mut u64, order: Ordering) -> u64;`
+///
+/// `$op` can use the following registers:
+/// - r0/r1 pair: previous value loaded by ll (read-only for `$op`)
+/// - r2/r3 pair: new value that will be stored by sc
+macro_rules! atomic_rmw_ll_sc_2 {
+    ($name:ident, $($op:tt)*) => {
+        #[inline]
+        unsafe fn $name(dst: *mut u64, order: Ordering) -> u64 {
+            debug_assert!(dst as usize % 8 == 0);
+            // SAFETY: the caller must uphold the safety contract.
+            unsafe {
+                let (prev_lo, prev_hi);
+                macro_rules! op {
+                    ($acquire:tt, $release:tt) => {
+                        asm!(
+                            $release,
+                            "2:",
+                                "ldrexd r0, r1, [{dst}]",
+                                $($op)*
+                                "strexd {r}, r2, r3, [{dst}]",
+                                // 0 if the store was successful, 1 if no store was performed
+                                "cmp {r}, #0",
+                                "bne 2b",
+                            $acquire,
+                            dst = in(reg) dst,
+                            r = out(reg) _,
+                            out("r0") prev_lo,
+                            out("r1") prev_hi,
+                            out("r2") _,
+                            out("r3") _,
+                            // Do not use `preserves_flags` because CMP modifies the condition flags.
+                            options(nostack),
+                        )
+                    };
+                }
+                atomic_rmw!(op, order);
+                U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+            }
+        }
+    };
+}
+
+atomic_rmw_ll_sc_3! {
+    atomic_add,
+    select_le_or_be!("adds r2, r0, {val_lo}", "adds r3, r1, {val_hi}"),
+    select_le_or_be!("adc r3, r1, {val_hi}", "adc r2, r0, {val_lo}"),
+}
+atomic_rmw_ll_sc_3! {
+    atomic_sub,
+    select_le_or_be!("subs r2, r0, {val_lo}", "subs r3, r1, {val_hi}"),
+    select_le_or_be!("sbc r3, r1, {val_hi}", "sbc r2, r0, {val_lo}"),
+}
+atomic_rmw_ll_sc_3! {
+    atomic_and,
+    "and r2, r0, {val_lo}",
+    "and r3, r1, {val_hi}",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_nand,
+    "and r2, r0, {val_lo}",
+    "and r3, r1, {val_hi}",
+    "mvn r2, r2",
+    "mvn r3, r3",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_or,
+    "orr r2, r0, {val_lo}",
+    "orr r3, r1, {val_hi}",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_xor,
+    "eor r2, r0, {val_lo}",
+    "eor r3, r1, {val_hi}",
+}
+
+// Use branches instead of conditional execution, because the latter
+// requires IT blocks in Thumb state.
+// MOV (without S suffix) doesn't modify the condition flags.
+atomic_rmw_ll_sc_3! {
+    atomic_max,
+    select_le_or_be!("cmp {val_lo}, r0", "cmp {val_hi}, r1"),
+    select_le_or_be!("sbcs r2, {val_hi}, r1", "sbcs r2, {val_lo}, r0"),
+    "mov r2, r0",
+    "mov r3, r1",
+    "blt 3f", // keep prev if val < prev
+    "mov r2, {val_lo}",
+    "mov r3, {val_hi}",
+    "3:",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_umax,
+    select_le_or_be!("cmp {val_lo}, r0", "cmp {val_hi}, r1"),
+    select_le_or_be!("sbcs r2, {val_hi}, r1", "sbcs r2, {val_lo}, r0"),
+    "mov r2, r0",
+    "mov r3, r1",
+    "blo 3f", // keep prev if val < prev
+    "mov r2, {val_lo}",
+    "mov r3, {val_hi}",
+    "3:",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_min,
+    select_le_or_be!("cmp {val_lo}, r0", "cmp {val_hi}, r1"),
+    select_le_or_be!("sbcs r2, {val_hi}, r1", "sbcs r2, {val_lo}, r0"),
+    "mov r2, r0",
+    "mov r3, r1",
+    "bge 3f", // keep prev if val >= prev
+    "mov r2, {val_lo}",
+    "mov r3, {val_hi}",
+    "3:",
+}
+atomic_rmw_ll_sc_3! {
+    atomic_umin,
+    select_le_or_be!("cmp {val_lo}, r0", "cmp {val_hi}, r1"),
+    select_le_or_be!("sbcs r2, {val_hi}, r1", "sbcs r2, {val_lo}, r0"),
+    "mov r2, r0",
+    "mov r3, r1",
+    "bhs 3f", // keep prev if val >= prev
+    "mov r2, {val_lo}",
+    "mov r3, {val_hi}",
+    "3:",
+}
+
+atomic_rmw_ll_sc_2! {
+    atomic_not,
+    "mvn r2, r0",
+    "mvn r3, r1",
+}
+// RSC is not available in Thumb state, so use !x + 1 instead of 0 - x.
+atomic_rmw_ll_sc_2! {
+    atomic_neg,
+    "mvn r2, r0",
+    "mvn r3, r1",
+    select_le_or_be!("adds r2, r2, #1", "adds r3, r3, #1"),
+    select_le_or_be!("adc r3, r3, #0", "adc r2, r2, #0"),
+}
+
+#[inline]
+const fn is_lock_free() -> bool {
+    IS_ALWAYS_LOCK_FREE
+}
+const IS_ALWAYS_LOCK_FREE: bool = true;
+
+atomic64!(AtomicI64, i64, atomic_max, atomic_min);
+atomic64!(AtomicU64, u64, atomic_umax, atomic_umin);
+
+#[allow(
+    clippy::alloc_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::std_instead_of_core,
+    clippy::undocumented_unsafe_blocks,
+    clippy::wildcard_imports
+)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_atomic_int!(i64);
+    test_atomic_int!(u64);
+
+    // load/store/swap implementation is not affected by signedness, so it is
+    // enough to test only unsigned types.
+    stress_test!(u64);
+}
@@ -0,0 +1,277 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+64-bit atomic implementation on MIPS32r6 using llwp/scwp (paired LL/SC).
+
+LLWP/SCWP are optional in MIPS32r6 (their presence is indicated by Config5.XNP == 0),
+and whether they are implemented is not exposed to user space (Config5 is not
+readable from user space and Linux does not report it in hwcap), so run-time
+detection is not possible. This module is only used when the user asserts that
+the CPU implements them by `--cfg portable_atomic_unsafe_assume_llwp_scwp`.
+Otherwise, the fallback implementation is used.
+
+LoongArch32 and pre-r6 MIPS32 have no paired LL/SC instructions, so 64-bit
+atomics on them always use the fallback implementation.
+
+Note: On Miri and ThreadSanitizer which do not support inline assembly, we don't use
+this module and use fallback implementation instead.
+
+Refs:
+- MIPS Architecture For Programmers Volume II-A: The MIPS32 Instruction Set Manual, Revision 6.06
+  https://s3-eu-west-1.amazonaws.com/downloads-mips/documents/MD00086-2B-MIPS32BIS-AFP-6.06.pdf
+- atomic-maybe-uninit https://github.com/taiki-e/atomic-maybe-uninit
+*/
+
+include!("macros.rs");
+
+use core::{arch::asm, sync::atomic::Ordering};
+
+use crate::utils::{Pair, U64};
+
+// LLVM doesn't support llwp/scwp mnemonics as of LLVM 19, so we use .word directive.
+//
+// llwp rt, rd, 0(base) loads the lower 32 bits of the doubleword at base to rt and
+// the upper 32 bits to rd (i.e., the halves of the value, regardless of endianness).
+// scwp rt, rd, 0(base) stores them in the same way and writes 1 to rt on success,
+// 0 on failure.
+//
+// Both instructions use fixed registers here:
+// - base: $4
+// - llwp: rt = $8, rd = $9
+// - scwp: rt = $10, rd = $11
+macro_rules! llwp {
+    () => {
+        // llwp $8, $9, 0($4)
+        ".word 0x7C884876"
+    };
+}
+macro_rules! scwp {
+    () => {
+        // scwp $10, $11, 0($4)
+        ".word 0x7C8A5866"
+    };
+}
+
+// Fences for RMW and CAS.
+// Release and stronger orderings need a fence before the operation, and
+// Acquire and stronger orderings need a fence after the operation.
+// This is the same as what LLVM generates for 32-bit atomics on MIPS.
+macro_rules! atomic_rmw {
+    ($op:ident, $order:ident) => {
+        match $order {
+            Ordering::Relaxed => $op!("", ""),
+            Ordering::Acquire => $op!("sync", ""),
+            Ordering::Release => $op!("", "sync"),
+            Ordering::AcqRel | Ordering::SeqCst => $op!("sync", "sync"),
+            _ => unreachable!(),
+        }
+    };
+}
+
+// -----------------------------------------------------------------------------
+// load/store
+
+#[inline]
+unsafe fn atomic_load(src: *mut u64, order: Ordering) -> u64 {
+    debug_assert!(src as usize % 8 == 0);
+
+    // SAFETY: the caller must uphold the safety contract.
+    //
+    // llwp (by itself) is single-copy atomic, so we don't need the
+    // corresponding scwp here.
+    unsafe {
+        let (out_lo, out_hi);
+        macro_rules! atomic_load {
+            ($acquire:tt) => {
+                asm!(
+                    llwp!(),
+                    $acquire,
+                    in("$4") src,
+                    out("$8") out_lo,
+                    out("$9") out_hi,
+                    options(nostack, preserves_flags),
+                )
+            };
+        }
+        match order {
+            Ordering::Relaxed => atomic_load!(""),
+            Ordering::Acquire | Ordering::SeqCst => atomic_load!("sync"),
+            _ => unreachable!(),
+        }
+        U64 { pair: Pair { lo: out_lo, hi: out_hi } }.whole
+    }
+}
+
+#[inline]
+unsafe fn atomic_store(dst: *mut u64, val: u64, order: Ordering) {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        atomic_update(dst, order, |_| val);
+    }
+}
+
+// -----------------------------------------------------------------------------
+// compare_exchange
+
+#[inline]
+unsafe fn llwp_scwp_cmpxchg(dst: *mut u64, old: u64, new: u64, order: Ordering) -> (u64, bool) {
+    debug_assert!(dst as usize % 8 == 0);
+
+    // SAFETY: the caller must uphold the safety contract.
+    let prev = unsafe {
+        let old = U64 { whole: old };
+        let new = U64 { whole: new };
+        let (prev_lo, prev_hi);
+        macro_rules! cmpxchg {
+            ($acquire:tt, $release:tt) => {
+                asm!(
+                    $release,
+                    "2:",
+                        llwp!(),
+                        "xor {tmp_lo}, $8, {old_lo}",
+                        "xor {tmp_hi}, $9, {old_hi}",
+                        "or {tmp_lo}, {tmp_lo}, {tmp_hi}",
+                        // Use compact branches to avoid delay slots.
+                        // Note that the instruction after a compact branch (forbidden slot)
+                        // must not be a control transfer instruction.
+                        "bnezc {tmp_lo}, 3f",
+                        "move $10, {new_lo}",
+                        "move $11, {new_hi}",
+                        scwp!(),
+                        "beqzc $10, 2b",
+                    "3:",
+                    $acquire,
+                    old_lo = in(reg) old.pair.lo,
+                    old_hi = in(reg) old.pair.hi,
+                    new_lo = in(reg) new.pair.lo,
+                    new_hi = in(reg) new.pair.hi,
+                    tmp_lo = out(reg) _,
+                    tmp_hi = out(reg) _,
+                    in("$4") dst,
+                    out("$8") prev_lo,
+                    out("$9") prev_hi,
+                    out("$10") _,
+                    out("$11") _,
+                    options(nostack, preserves_flags),
+                )
+            };
+        }
+        atomic_rmw!(cmpxchg, order);
+        U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    };
+    (prev, prev == old)
+}
+
+#[inline]
+unsafe fn atomic_compare_exchange(
+    dst: *mut u64,
+    old: u64,
+    new: u64,
+    success: Ordering,
+    failure: Ordering,
+) -> Result<u64, u64> {
+    let order = crate::utils::upgrade_success_ordering(success, failure);
+    // SAFETY: the caller must uphold the safety contract.
+    let (prev, ok) = unsafe { llwp_scwp_cmpxchg(dst, old, new, order) };
+    if ok {
+        Ok(prev)
+    } else {
+        Err(prev)
+    }
+}
+
+// llwp_scwp_cmpxchg retries on spurious failure of scwp, so it is always strong.
+use self::atomic_compare_exchange as atomic_compare_exchange_weak;
+
+// -----------------------------------------------------------------------------
+// RMW
+
+#[inline(always)]
+unsafe fn atomic_update<F>(dst: *mut u64, order: Ordering, mut f: F) -> u64
+where
+    F: FnMut(u64) -> u64,
+{
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        // The operation always completes with a successful CAS, so there is
+        // no need to synchronize the first load/failed CAS.
+        let mut prev = atomic_load(dst, Ordering::Relaxed);
+        loop {
+            let next = f(prev);
+            match llwp_scwp_cmpxchg(dst, prev, next, order) {
+                (_, true) => return prev,
+                (v, false) => prev = v,
+            }
+        }
+    }
+}
+
+macro_rules! atomic_rmw_by_cas {
+    ($(#[$attr:meta])* $name:ident, |$x:ident, $val:ident| $new:expr) => {
+        #[inline]
+        $(#[$attr])*
+        unsafe fn $name(dst: *mut u64, $val: u64, order: Ordering) -> u64 {
+            // SAFETY: the caller must uphold the safety contract.
+            unsafe { atomic_update(dst, order, |$x| $new) }
+        }
+    };
+}
+
+atomic_rmw_by_cas!(atomic_swap, |_x, val| val);
+atomic_rmw_by_cas!(atomic_add, |x, val| x.wrapping_add(val));
+atomic_rmw_by_cas!(atomic_sub, |x, val| x.wrapping_sub(val));
+atomic_rmw_by_cas!(atomic_and, |x, val| x & val);
+atomic_rmw_by_cas!(atomic_nand, |x, val| !(x & val));
+atomic_rmw_by_cas!(atomic_or, |x, val| x | val);
+atomic_rmw_by_cas!(atomic_xor, |x, val| x ^ val);
+atomic_rmw_by_cas!(
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    atomic_max,
+    |x, val| core::cmp::max(x as i64, val as i64) as u64
+);
+atomic_rmw_by_cas!(atomic_umax, |x, val| core::cmp::max(x, val));
+atomic_rmw_by_cas!(
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    atomic_min,
+    |x, val| core::cmp::min(x as i64, val as i64) as u64
+);
+atomic_rmw_by_cas!(atomic_umin, |x, val| core::cmp::min(x, val));
+
+#[inline]
+unsafe fn atomic_not(dst: *mut u64, order: Ordering) -> u64 {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe { atomic_update(dst, order, |x| !x) }
+}
+#[inline]
+unsafe fn atomic_neg(dst: *mut u64, order: Ordering) -> u64 {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe { atomic_update(dst, order, u64::wrapping_neg) }
+}
+
+#[inline]
+const fn is_lock_free() -> bool {
+    IS_ALWAYS_LOCK_FREE
+}
+const IS_ALWAYS_LOCK_FREE: bool = true;
+
+atomic64!(AtomicI64, i64, atomic_max, atomic_min);
+atomic64!(AtomicU64, u64, atomic_umax, atomic_umin);
+
+#[allow(
+    clippy::alloc_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::std_instead_of_core,
+    clippy::undocumented_unsafe_blocks,
+    clippy::wildcard_imports
+)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_atomic_int!(i64);
+    test_atomic_int!(u64);
+
+    // load/store/swap implementation is not affected by signedness, so it is
+    // enough to test only unsigned types.
+    stress_test!(u64);
+}
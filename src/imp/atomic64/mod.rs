@@ -21,6 +21,31 @@ See README.md for details.
 #[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
 pub(super) mod arm_linux;
 
+// Armv7-A/R without 64-bit atomics in core
+// Miri and Sanitizer do not support inline assembly.
+#[cfg(all(
+    target_arch = "arm",
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+    any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+    not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(super) mod arm;
+
+// MIPS32r6 with llwp/scwp
+// Miri and Sanitizer do not support inline assembly.
+#[cfg(all(
+    target_arch = "mips32r6",
+    portable_atomic_unsafe_assume_llwp_scwp,
+    portable_atomic_unstable_asm_experimental_arch,
+    not(any(miri, portable_atomic_sanitize_thread)),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(super) mod mips32r6;
+
 // riscv32
 // Miri and Sanitizer do not support inline assembly.
 #[cfg(all(
@@ -39,3 +64,16 @@ pub(super) mod arm_linux;
     ),
 ))]
 pub(super) mod riscv32;
+
+// x86 without 64-bit atomics in core (e.g., i486-class)
+#[cfg(feature = "fallback")]
+// Miri and Sanitizer do not support inline assembly.
+#[cfg(all(
+    target_arch = "x86",
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+    not(portable_atomic_no_outline_atomics),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(super) mod x86;
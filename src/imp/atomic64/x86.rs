@@ -0,0 +1,642 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+64-bit atomic implementation on x86 (32-bit) using CMPXCHG8B.
+
+Rust's built-in x86 targets (i586 and later) provide 64-bit atomics in
+core::sync::atomic, so this module is only used on targets whose
+max-atomic-width is 32 (e.g., custom i386/i486-class targets). CMPXCHG8B
+is not available on such CPUs (it was introduced in Pentium), so we use
+run-time detection and use fallback implementation if it is not available.
+
+Note: On Miri and ThreadSanitizer which do not support inline assembly, we don't use
+this module and use fallback implementation instead.
+
+Refs:
+- x86 and amd64 instruction reference https://www.felixcloutier.com/x86
+- Intel 64 and IA-32 Architectures Software Developer's Manual, Volume 3A,
+  Section 9.1.1 "Guaranteed Atomic Operations"
+  https://www.intel.com/content/www/us/en/developer/articles/technical/intel-sdm.html
+- atomic-maybe-uninit https://github.com/taiki-e/atomic-maybe-uninit
+*/
+
+include!("macros.rs");
+
+#[path = "../fallback/outline_atomics.rs"]
+mod fallback;
+
+#[path = "../detect/x86.rs"]
+mod detect;
+
+#[cfg(not(portable_atomic_no_asm))]
+use core::arch::asm;
+use core::sync::atomic::Ordering;
+
+use crate::utils::{Pair, U64};
+
+// Asserts that the function is called in the correct context.
+macro_rules! debug_assert_cmpxchg8b {
+    () => {
+        debug_assert!(detect::detect().has_cmpxchg8b());
+    };
+}
+
+#[inline]
+unsafe fn cmpxchg8b(dst: *mut u64, old: u64, new: u64) -> (u64, bool) {
+    debug_assert!(dst as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+
+    // SAFETY: the caller must guarantee that `dst` is valid for both writes and
+    // reads, 8-byte aligned, that there are no concurrent non-atomic operations,
+    // and that the CPU supports CMPXCHG8B.
+    //
+    // If the value at `dst` (destination operand) and edx:eax are equal, the
+    // 64-bit value in ecx:ebx is stored in the `dst`, otherwise the value at
+    // `dst` is loaded to edx:eax.
+    //
+    // The ZF flag is set if the value at `dst` and edx:eax are equal,
+    // otherwise it is cleared. Other flags are unaffected.
+    //
+    // Refs: https://www.felixcloutier.com/x86/cmpxchg8b:cmpxchg16b
+    unsafe {
+        // cmpxchg8b is always SeqCst.
+        let r: u8;
+        let old = U64 { whole: old };
+        let new = U64 { whole: new };
+        let (prev_lo, prev_hi);
+        asm!(
+            "lock cmpxchg8b qword ptr [{dst}]",
+            "sete cl",
+            dst = in(reg) dst,
+            in("ebx") new.pair.lo,
+            in("ecx") new.pair.hi,
+            inout("eax") old.pair.lo => prev_lo,
+            inout("edx") old.pair.hi => prev_hi,
+            lateout("cl") r,
+            // Do not use `preserves_flags` because CMPXCHG8B modifies the ZF flag.
+            options(nostack),
+        );
+        crate::utils::assert_unchecked(r == 0 || r == 1); // needed to remove extra test
+        (U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole, r != 0)
+    }
+}
+
+// 64-bit load/store by SSE2 or x87 FPU.
+//
+// The Pentium processor (and newer processors since) guarantees that reading
+// or writing a quadword aligned on a 64-bit boundary is atomic. CMPXCHG8B is
+// also introduced in Pentium, so we only use these when CMPXCHG8B is available.
+//
+// Loads and stores are not reordered with other loads and stores of the
+// same kind on x86, so plain load/store has acquire/release semantics.
+// SeqCst store needs an additional full fence.
+//
+// This is the same as what LLVM generates for 64-bit atomic load/store on i586/i686.
+#[cfg(target_feature = "sse2")]
+#[inline]
+unsafe fn atomic_load_sse2(src: *mut u64) -> u64 {
+    debug_assert!(src as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+
+    // SAFETY: the caller must uphold the safety contract.
+    //
+    // atomic load by movq is always SeqCst.
+    unsafe {
+        let out: u64;
+        asm!(
+            "movq {out}, qword ptr [{src}]",
+            src = in(reg) src,
+            out = out(xmm_reg) out,
+            options(nostack, preserves_flags),
+        );
+        out
+    }
+}
+#[cfg(target_feature = "sse2")]
+#[inline]
+unsafe fn atomic_store_sse2(dst: *mut u64, val: u64, order: Ordering) {
+    debug_assert!(dst as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        match order {
+            // Relaxed and Release stores are equivalent.
+            Ordering::Relaxed | Ordering::Release => {
+                asm!(
+                    "movq qword ptr [{dst}], {val}",
+                    dst = in(reg) dst,
+                    val = in(xmm_reg) val,
+                    options(nostack, preserves_flags),
+                );
+            }
+            Ordering::SeqCst => {
+                let p = core::cell::UnsafeCell::new(core::mem::MaybeUninit::<u32>::uninit());
+                asm!(
+                    "movq qword ptr [{dst}], {val}",
+                    // Equivalent to mfence. See atomic_store_vmovdqa() in atomic128/x86_64.rs for details.
+                    "xchg dword ptr [{p}], {tmp}",
+                    dst = in(reg) dst,
+                    val = in(xmm_reg) val,
+                    p = inout(reg) p.get() => _,
+                    tmp = lateout(reg) _,
+                    options(nostack, preserves_flags),
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+// x87 registers can only be used as clobbers, and when all of them are clobbered,
+// the x87 register stack is guaranteed to be empty on entry to the asm block and
+// must also be empty on exit.
+#[cfg(not(target_feature = "sse2"))]
+#[inline]
+unsafe fn atomic_load_x87(src: *mut u64) -> u64 {
+    debug_assert!(src as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+    debug_assert!(detect::detect().has_x87());
+
+    // SAFETY: the caller must uphold the safety contract.
+    //
+    // FILD converts a signed 64-bit integer to 80-bit extended precision
+    // floating-point (with 64-bit significand) and FISTP converts it back, so
+    // the round trip is always exact regardless of the rounding and precision
+    // control (the precision control doesn't affect FILD and FISTP).
+    //
+    // atomic load by fild is always SeqCst.
+    unsafe {
+        let mut out = core::mem::MaybeUninit::<u64>::uninit();
+        asm!(
+            "fild qword ptr [{src}]",
+            "fistp qword ptr [{out}]",
+            src = in(reg) src,
+            out = in(reg) out.as_mut_ptr(),
+            out("st(0)") _,
+            out("st(1)") _,
+            out("st(2)") _,
+            out("st(3)") _,
+            out("st(4)") _,
+            out("st(5)") _,
+            out("st(6)") _,
+            out("st(7)") _,
+            // Do not use `preserves_flags` because FILD and FISTP modify the x87 FPU status word.
+            options(nostack),
+        );
+        out.assume_init()
+    }
+}
+#[cfg(not(target_feature = "sse2"))]
+#[inline]
+unsafe fn atomic_store_x87(dst: *mut u64, val: u64, order: Ordering) {
+    debug_assert!(dst as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+    debug_assert!(detect::detect().has_x87());
+
+    // SAFETY: the caller must uphold the safety contract.
+    //
+    // See atomic_load_x87 for details.
+    unsafe {
+        match order {
+            // Relaxed and Release stores are equivalent.
+            Ordering::Relaxed | Ordering::Release => {
+                asm!(
+                    "fild qword ptr [{val}]",
+                    "fistp qword ptr [{dst}]",
+                    dst = in(reg) dst,
+                    val = in(reg) core::ptr::addr_of!(val),
+                    out("st(0)") _,
+                    out("st(1)") _,
+                    out("st(2)") _,
+                    out("st(3)") _,
+                    out("st(4)") _,
+                    out("st(5)") _,
+                    out("st(6)") _,
+                    out("st(7)") _,
+                    // Do not use `preserves_flags` because FILD and FISTP modify the x87 FPU status word.
+                    options(nostack),
+                );
+            }
+            Ordering::SeqCst => {
+                let p = core::cell::UnsafeCell::new(core::mem::MaybeUninit::<u32>::uninit());
+                asm!(
+                    "fild qword ptr [{val}]",
+                    "fistp qword ptr [{dst}]",
+                    // Equivalent to mfence (which requires SSE2).
+                    // See atomic_store_vmovdqa() in atomic128/x86_64.rs for details.
+                    "xchg dword ptr [{p}], {tmp}",
+                    dst = in(reg) dst,
+                    val = in(reg) core::ptr::addr_of!(val),
+                    p = inout(reg) p.get() => _,
+                    tmp = lateout(reg) _,
+                    out("st(0)") _,
+                    out("st(1)") _,
+                    out("st(2)") _,
+                    out("st(3)") _,
+                    out("st(4)") _,
+                    out("st(5)") _,
+                    out("st(6)") _,
+                    out("st(7)") _,
+                    // Do not use `preserves_flags` because FILD and FISTP modify the x87 FPU status word.
+                    options(nostack),
+                );
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[inline]
+unsafe fn atomic_load(src: *mut u64, _order: Ordering) -> u64 {
+    // SAFETY: the caller must uphold the safety contract.
+    // we only calls atomic_load_{sse2,x87,cmpxchg8b} if cmpxchg8b is available.
+    unsafe {
+        ifunc!(unsafe fn(src: *mut u64) -> u64 {
+            let cpuid = detect::detect();
+            // Check CMPXCHG8B first to prevent mixing atomic and non-atomic access.
+            if cpuid.has_cmpxchg8b() {
+                #[cfg(target_feature = "sse2")]
+                {
+                    atomic_load_sse2
+                }
+                #[cfg(not(target_feature = "sse2"))]
+                {
+                    if cpuid.has_x87() {
+                        atomic_load_x87
+                    } else {
+                        atomic_load_cmpxchg8b
+                    }
+                }
+            } else {
+                // Use SeqCst because cmpxchg8b and atomic load by movq/fild is always SeqCst.
+                fallback::atomic_load_seqcst
+            }
+        })
+    }
+}
+#[cfg_attr(target_feature = "sse2", allow(dead_code))]
+#[inline]
+unsafe fn atomic_load_cmpxchg8b(src: *mut u64) -> u64 {
+    debug_assert!(src as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+
+    // SAFETY: the caller must guarantee that `src` is valid for both writes and
+    // reads, 8-byte aligned, and that there are no concurrent non-atomic operations.
+    // The caller must also guarantee that the CPU supports CMPXCHG8B.
+    //
+    // See cmpxchg8b function for more.
+    //
+    // We could use CAS loop by atomic_compare_exchange here, but using an inline assembly allows
+    // omitting the storing of condition flags.
+    unsafe {
+        // cmpxchg8b is always SeqCst.
+        let (out_lo, out_hi);
+        asm!(
+            "lock cmpxchg8b qword ptr [{src}]",
+            src = in(reg) src,
+            // set old/new args of cmpxchg8b to 0
+            in("ebx") 0_u32,
+            in("ecx") 0_u32,
+            inout("eax") 0_u32 => out_lo,
+            inout("edx") 0_u32 => out_hi,
+            // Do not use `preserves_flags` because CMPXCHG8B modifies the ZF flag.
+            options(nostack),
+        );
+        U64 { pair: Pair { lo: out_lo, hi: out_hi } }.whole
+    }
+}
+
+#[inline]
+unsafe fn atomic_store(dst: *mut u64, val: u64, order: Ordering) {
+    // SAFETY: the caller must uphold the safety contract.
+    // we only calls atomic_store_{sse2,x87,cmpxchg8b} if cmpxchg8b is available.
+    unsafe {
+        #[cfg(target_feature = "sse2")]
+        fn_alias! {
+            unsafe fn(dst: *mut u64, val: u64);
+            // atomic store by movq has at least release semantics.
+            atomic_store_fast_non_seqcst = atomic_store_sse2(Ordering::Release);
+            atomic_store_fast_seqcst = atomic_store_sse2(Ordering::SeqCst);
+        }
+        #[cfg(not(target_feature = "sse2"))]
+        fn_alias! {
+            unsafe fn(dst: *mut u64, val: u64);
+            // atomic store by fistp has at least release semantics.
+            atomic_store_fast_non_seqcst = atomic_store_x87(Ordering::Release);
+            atomic_store_fast_seqcst = atomic_store_x87(Ordering::SeqCst);
+        }
+        macro_rules! select {
+            ($fast:ident, $fallback:ident) => {
+                ifunc!(unsafe fn(dst: *mut u64, val: u64) {
+                    let cpuid = detect::detect();
+                    // Check CMPXCHG8B first to prevent mixing atomic and non-atomic access.
+                    if cpuid.has_cmpxchg8b() {
+                        #[cfg(target_feature = "sse2")]
+                        {
+                            $fast
+                        }
+                        #[cfg(not(target_feature = "sse2"))]
+                        {
+                            if cpuid.has_x87() {
+                                $fast
+                            } else {
+                                atomic_store_cmpxchg8b
+                            }
+                        }
+                    } else {
+                        fallback::$fallback
+                    }
+                })
+            };
+        }
+        match order {
+            // Relaxed and Release stores are equivalent in all implementations
+            // that may be called here (movq, fistp, cmpxchg8b, and fallback).
+            Ordering::Relaxed | Ordering::Release => {
+                select!(atomic_store_fast_non_seqcst, atomic_store_non_seqcst);
+            }
+            Ordering::SeqCst => {
+                select!(atomic_store_fast_seqcst, atomic_store_seqcst);
+            }
+            _ => unreachable!(),
+        }
+    }
+}
+#[cfg_attr(target_feature = "sse2", allow(dead_code))]
+#[inline]
+unsafe fn atomic_store_cmpxchg8b(dst: *mut u64, val: u64) {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        // cmpxchg8b is always SeqCst.
+        atomic_swap_cmpxchg8b(dst, val);
+    }
+}
+
+#[inline]
+unsafe fn atomic_compare_exchange(
+    dst: *mut u64,
+    old: u64,
+    new: u64,
+    _success: Ordering,
+    _failure: Ordering,
+) -> Result<u64, u64> {
+    // SAFETY: the caller must guarantee that `dst` is valid for both writes and
+    // reads, 8-byte aligned, and that there are no different kinds of concurrent accesses.
+    // we only calls cmpxchg8b if cmpxchg8b is available.
+    let (prev, ok) = unsafe {
+        ifunc!(unsafe fn(dst: *mut u64, old: u64, new: u64) -> (u64, bool) {
+            if detect::detect().has_cmpxchg8b() {
+                cmpxchg8b
+            } else {
+                // Use SeqCst because cmpxchg8b is always SeqCst.
+                fallback::atomic_compare_exchange_seqcst
+            }
+        })
+    };
+    if ok {
+        Ok(prev)
+    } else {
+        Err(prev)
+    }
+}
+
+// cmpxchg8b is always strong.
+use atomic_compare_exchange as atomic_compare_exchange_weak;
+
+#[inline]
+unsafe fn atomic_swap_cmpxchg8b(dst: *mut u64, val: u64) -> u64 {
+    debug_assert!(dst as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+
+    // SAFETY: the caller must guarantee that `dst` is valid for both writes and
+    // reads, 8-byte aligned, and that there are no concurrent non-atomic operations.
+    // The caller must also guarantee that the CPU supports CMPXCHG8B.
+    //
+    // See cmpxchg8b function for more.
+    //
+    // We could use CAS loop by atomic_compare_exchange here, but using an inline assembly allows
+    // omitting the storing/comparing of condition flags.
+    unsafe {
+        // cmpxchg8b is always SeqCst.
+        let val = U64 { whole: val };
+        let (mut prev_lo, mut prev_hi);
+        asm!(
+            // This is not single-copy atomic reads, but this is ok because subsequent
+            // CAS will check for consistency.
+            //
+            // This is based on the code generated for the first load in DW RMWs by LLVM.
+            //
+            // Note that the C++20 memory model does not allow mixed-sized atomic access,
+            // so we must use inline assembly to implement this.
+            // (i.e., byte-wise atomic based on the standard library's atomic types
+            // cannot be used here).
+            "mov eax, dword ptr [{dst}]",
+            "mov edx, dword ptr [{dst} + 4]",
+            "2:",
+                "lock cmpxchg8b qword ptr [{dst}]",
+                "jne 2b",
+            dst = in(reg) dst,
+            in("ebx") val.pair.lo,
+            in("ecx") val.pair.hi,
+            out("eax") prev_lo,
+            out("edx") prev_hi,
+            // Do not use `preserves_flags` because CMPXCHG8B modifies the ZF flag.
+            options(nostack),
+        );
+        U64 { pair: Pair { lo: prev_lo, hi: prev_hi } }.whole
+    }
+}
+
+// 64-bit atomic load by two 32-bit atomic loads.
+#[inline]
+unsafe fn byte_wise_atomic_load(src: *const u64) -> u64 {
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        let (out_lo, out_hi);
+        asm!(
+            "mov {out_lo}, dword ptr [{src}]",
+            "mov {out_hi}, dword ptr [{src} + 4]",
+            src = in(reg) src,
+            out_lo = out(reg) out_lo,
+            out_hi = out(reg) out_hi,
+            options(pure, nostack, preserves_flags, readonly),
+        );
+        U64 { pair: Pair { lo: out_lo, hi: out_hi } }.whole
+    }
+}
+
+#[inline(always)]
+unsafe fn atomic_update_cmpxchg8b<F>(dst: *mut u64, mut f: F) -> u64
+where
+    F: FnMut(u64) -> u64,
+{
+    debug_assert!(dst as usize % 8 == 0);
+    debug_assert_cmpxchg8b!();
+    // SAFETY: the caller must uphold the safety contract.
+    unsafe {
+        // This is not single-copy atomic reads, but this is ok because subsequent
+        // CAS will check for consistency.
+        //
+        // See atomic_swap_cmpxchg8b for more.
+        let mut prev = byte_wise_atomic_load(dst);
+        loop {
+            let next = f(prev);
+            match cmpxchg8b(dst, prev, next) {
+                (_, true) => return prev,
+                (v, false) => prev = v,
+            }
+        }
+    }
+}
+
+macro_rules! atomic_with_ifunc {
+    (
+        unsafe fn $name:ident($($arg:tt)*) $(-> $ret_ty:ty)? { $($cmpxchg8b_fn_body:tt)* }
+        fallback = $seqcst_fallback_fn:ident
+    ) => {
+        #[inline]
+        unsafe fn $name($($arg)*, _: Ordering) $(-> $ret_ty)? {
+            unsafe fn cmpxchg8b_fn($($arg)*) $(-> $ret_ty)? {
+                $($cmpxchg8b_fn_body)*
+            }
+            // SAFETY: the caller must uphold the safety contract.
+            // we only calls cmpxchg8b_fn if cmpxchg8b is available.
+            unsafe {
+                ifunc!(unsafe fn($($arg)*) $(-> $ret_ty)? {
+                    if detect::detect().has_cmpxchg8b() {
+                        cmpxchg8b_fn
+                    } else {
+                        // Use SeqCst because cmpxchg8b is always SeqCst.
+                        fallback::$seqcst_fallback_fn
+                    }
+                })
+            }
+        }
+    };
+}
+
+atomic_with_ifunc! {
+    unsafe fn atomic_swap(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_swap_cmpxchg8b(dst, val) }
+    }
+    fallback = atomic_swap_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_add(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| x.wrapping_add(val)) }
+    }
+    fallback = atomic_add_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_sub(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| x.wrapping_sub(val)) }
+    }
+    fallback = atomic_sub_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_and(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| x & val) }
+    }
+    fallback = atomic_and_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_nand(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| !(x & val)) }
+    }
+    fallback = atomic_nand_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_or(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| x | val) }
+    }
+    fallback = atomic_or_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_xor(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| x ^ val) }
+    }
+    fallback = atomic_xor_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_max(dst: *mut u64, val: u64) -> u64 {
+        #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            atomic_update_cmpxchg8b(dst, |x| core::cmp::max(x as i64, val as i64) as u64)
+        }
+    }
+    fallback = atomic_max_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_umax(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| core::cmp::max(x, val)) }
+    }
+    fallback = atomic_umax_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_min(dst: *mut u64, val: u64) -> u64 {
+        #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe {
+            atomic_update_cmpxchg8b(dst, |x| core::cmp::min(x as i64, val as i64) as u64)
+        }
+    }
+    fallback = atomic_min_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_umin(dst: *mut u64, val: u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| core::cmp::min(x, val)) }
+    }
+    fallback = atomic_umin_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_not(dst: *mut u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, |x| !x) }
+    }
+    fallback = atomic_not_seqcst
+}
+atomic_with_ifunc! {
+    unsafe fn atomic_neg(dst: *mut u64) -> u64 {
+        // SAFETY: the caller must uphold the safety contract.
+        unsafe { atomic_update_cmpxchg8b(dst, u64::wrapping_neg) }
+    }
+    fallback = atomic_neg_seqcst
+}
+
+#[inline]
+fn is_lock_free() -> bool {
+    detect::detect().has_cmpxchg8b()
+}
+const IS_ALWAYS_LOCK_FREE: bool = false;
+
+atomic64!(AtomicI64, i64, atomic_max, atomic_min);
+atomic64!(AtomicU64, u64, atomic_umax, atomic_umin);
+
+#[allow(
+    clippy::alloc_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::std_instead_of_core,
+    clippy::undocumented_unsafe_blocks,
+    clippy::wildcard_imports
+)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_atomic_int!(i64);
+    test_atomic_int!(u64);
+
+    // load/store/swap implementation is not affected by signedness, so it is
+    // enough to test only unsigned types.
+    stress_test!(u64);
+}
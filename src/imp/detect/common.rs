@@ -100,6 +100,12 @@ flags! {
     HAS_LSE128(4, has_lse128, "lse128", any(target_feature = "lse128", portable_atomic_target_feature = "lse128")),
 }
 
+#[cfg(target_arch = "loongarch64")]
+flags! {
+    // sc.q
+    HAS_SCQ(1, has_scq, "scq", any(target_feature = "scq", portable_atomic_target_feature = "scq")),
+}
+
 #[cfg(target_arch = "powerpc64")]
 flags! {
     // lqarx and stqcx.
@@ -112,13 +118,21 @@ flags! {
     HAS_ZACAS(1, has_zacas, "zacas", any(target_feature = "experimental-zacas", portable_atomic_target_feature = "experimental-zacas")),
 }
 
+#[cfg(target_arch = "x86")]
+flags! {
+    // cmpxchg8b
+    HAS_CMPXCHG8B(1, has_cmpxchg8b, "cmpxchg8b", any(/* always false */)),
+    // x87 FPU (fild/fistp)
+    HAS_X87(2, has_x87, "x87", any(/* always false */)),
+}
+
 #[cfg(target_arch = "x86_64")]
 flags! {
     // cmpxchg16b
     HAS_CMPXCHG16B(1, has_cmpxchg16b, "cmpxchg16b", any(target_feature = "cmpxchg16b", portable_atomic_target_feature = "cmpxchg16b")),
     // atomic vmovdqa
     #[cfg(target_feature = "sse")]
-    HAS_VMOVDQA_ATOMIC(2, has_vmovdqa_atomic, "vmovdqa-atomic", any(/* always false */)),
+    HAS_VMOVDQA_ATOMIC(2, has_vmovdqa_atomic, "vmovdqa-atomic", all(target_feature = "avx", portable_atomic_unsafe_assume_vmovdqa_atomic)),
 }
 
 // core::ffi::c_* (except c_void) requires Rust 1.64, libc 1.0 plans to require Rust 1.63
@@ -449,6 +463,16 @@ mod tests_common {
             }
         }
     }
+    #[cfg(target_arch = "loongarch64")]
+    #[test]
+    #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
+    fn test_detect() {
+        if detect().has_scq() {
+            assert!(detect().test(CpuInfo::HAS_SCQ));
+        } else {
+            assert!(!detect().test(CpuInfo::HAS_SCQ));
+        }
+    }
     #[cfg(target_arch = "powerpc64")]
     #[test]
     #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
@@ -476,6 +500,21 @@ mod tests_common {
             assert!(!detect().test(CpuInfo::HAS_ZACAS));
         }
     }
+    #[cfg(target_arch = "x86")]
+    #[test]
+    #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
+    fn test_detect() {
+        if detect().has_cmpxchg8b() {
+            assert!(detect().test(CpuInfo::HAS_CMPXCHG8B));
+        } else {
+            assert!(!detect().test(CpuInfo::HAS_CMPXCHG8B));
+        }
+        if detect().has_x87() {
+            assert!(detect().test(CpuInfo::HAS_X87));
+        } else {
+            assert!(!detect().test(CpuInfo::HAS_X87));
+        }
+    }
     #[cfg(target_arch = "x86_64")]
     #[test]
     #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
@@ -0,0 +1,68 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Run-time CPU feature detection on LoongArch64 by using CPUCFG.
+
+Unlike other architectures, LoongArch's CPUCFG instruction is available to
+user space on all CPUs, so this works on all operating systems.
+
+Refs:
+- LoongArch Reference Manual Volume 1: Basic Architecture, Section 2.2.10.5 "CPUCFG"
+  https://loongson.github.io/LoongArch-Documentation/LoongArch-Vol1-EN.html
+*/
+
+#![cfg_attr(portable_atomic_sanitize_thread, allow(dead_code))]
+
+// Miri doesn't support inline assembly.
+#[cfg(miri)]
+compile_error!("internal error: this module is not supported on this environment");
+
+include!("common.rs");
+
+use core::arch::asm;
+
+// CPUCFG word 2, bit 30: SC.Q is implemented (added in LoongArch v1.1).
+const CPUCFG2_SCQ: u64 = 1 << 30;
+
+#[inline]
+fn cpucfg(word: u64) -> u64 {
+    let out;
+    // SAFETY: CPUCFG is available on all LoongArch CPUs, and reading an
+    // unimplemented configuration word returns 0.
+    unsafe {
+        asm!(
+            "cpucfg {out}, {word}",
+            word = in(reg) word,
+            out = lateout(reg) out,
+            options(pure, nomem, nostack, preserves_flags),
+        );
+    }
+    out
+}
+
+#[cold]
+fn _detect(info: &mut CpuInfo) {
+    if cpucfg(2) & CPUCFG2_SCQ != 0 {
+        info.set(CpuInfo::HAS_SCQ);
+    }
+}
+
+#[allow(
+    clippy::alloc_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::std_instead_of_core,
+    clippy::undocumented_unsafe_blocks,
+    clippy::wildcard_imports
+)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
+    fn test_cpucfg() {
+        // CPUCFG word 0 is the processor identity, which is non-zero on all CPUs.
+        assert_ne!(cpucfg(0), 0);
+        assert_eq!(cpucfg(2) & CPUCFG2_SCQ != 0, detect().has_scq());
+    }
+}
@@ -0,0 +1,128 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Run-time CPU feature detection on x86 (32-bit) by using CPUID.
+
+Unlike x86_64, CPUID itself is not available on all x86 CPUs (i386 and
+early i486 don't support it), so we check whether CPUID is available by
+toggling the ID flag in EFLAGS first.
+
+Adapted from https://github.com/rust-lang/stdarch.
+*/
+
+#![cfg_attr(portable_atomic_sanitize_thread, allow(dead_code))]
+
+// Miri doesn't support inline assembly used in __cpuid: https://github.com/rust-lang/miri/issues/932
+#[cfg(miri)]
+compile_error!("internal error: this module is not supported on this environment");
+
+include!("common.rs");
+
+#[cfg(not(portable_atomic_no_asm))]
+use core::arch::asm;
+use core::arch::x86::CpuidResult;
+
+// Refs:
+// - https://www.felixcloutier.com/x86/cpuid
+// - https://en.wikipedia.org/wiki/CPUID#CPUID_usage_from_high-level_languages
+// - https://github.com/rust-lang/stdarch/blob/a0c30f3e3c75adcd6ee7efc94014ebcead61c507/crates/core_arch/src/x86/cpuid.rs#L115-L136
+fn has_cpuid() -> bool {
+    let result: u32;
+    // SAFETY: Reading and writing EFLAGS is safe on all x86 CPUs. The original
+    // EFLAGS are restored before the asm block ends.
+    //
+    // If the ID flag (bit 21) in EFLAGS can be toggled, the CPU supports CPUID.
+    unsafe {
+        asm!(
+            "pushfd",
+            "pop {result}",
+            "mov {tmp}, {result}",
+            "xor {tmp}, 0x200000",
+            "push {tmp}",
+            "popfd",
+            "pushfd",
+            "pop {tmp}",
+            "push {result}", // restore the original EFLAGS
+            "popfd",
+            "xor {result}, {tmp}",
+            result = out(reg) result,
+            tmp = out(reg) _,
+            // Do not use `nostack` because pushfd/popfd use the stack.
+            // Do not use `preserves_flags` because XOR modifies flags.
+        );
+    }
+    result & 0x200000 != 0
+}
+
+// Workaround for https://github.com/rust-lang/rust/issues/101346
+// It is not clear if our use cases are affected, but we implement this just in case.
+//
+// Refs:
+// - https://www.felixcloutier.com/x86/cpuid
+// - https://en.wikipedia.org/wiki/CPUID
+// - https://github.com/rust-lang/stdarch/blob/a0c30f3e3c75adcd6ee7efc94014ebcead61c507/crates/core_arch/src/x86/cpuid.rs
+#[inline]
+unsafe fn __cpuid(leaf: u32) -> CpuidResult {
+    let eax;
+    let ebx;
+    let ecx;
+    let edx;
+    // SAFETY: the caller must guarantee that the CPU supports CPUID.
+    unsafe {
+        asm!(
+            "cpuid",
+            inout("eax") leaf => eax,
+            out("ebx") ebx,
+            inout("ecx") 0 => ecx,
+            out("edx") edx,
+            options(nostack, preserves_flags),
+        );
+    }
+    CpuidResult { eax, ebx, ecx, edx }
+}
+
+#[cold]
+fn _detect(info: &mut CpuInfo) {
+    if !has_cpuid() {
+        return;
+    }
+    // SAFETY: we have checked that the CPU supports CPUID.
+    let CpuidResult { eax: max_leaf, .. } = unsafe { __cpuid(0) };
+    if max_leaf < 1 {
+        return;
+    }
+    // SAFETY: we have checked that the CPU supports CPUID and leaf 1.
+    let CpuidResult { edx: proc_info_edx, .. } = unsafe { __cpuid(1) };
+
+    // https://github.com/rust-lang/stdarch/blob/a0c30f3e3c75adcd6ee7efc94014ebcead61c507/crates/std_detect/src/detect/os/x86.rs#L94-L100
+    if test(proc_info_edx, 8) {
+        info.set(CpuInfo::HAS_CMPXCHG8B);
+    }
+    if test(proc_info_edx, 0) {
+        info.set(CpuInfo::HAS_X87);
+    }
+}
+
+#[allow(
+    clippy::alloc_instead_of_core,
+    clippy::std_instead_of_alloc,
+    clippy::std_instead_of_core,
+    clippy::undocumented_unsafe_blocks,
+    clippy::wildcard_imports
+)]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(portable_atomic_test_outline_atomics_detect_false, ignore)]
+    fn test_cpuid() {
+        assert!(has_cpuid());
+        // All x86 CPUs that Rust's built-in targets support (i586+) have CMPXCHG8B and x87 FPU.
+        assert!(detect().has_cmpxchg8b());
+        assert!(detect().has_x87());
+        let CpuidResult { edx: proc_info_edx, .. } = unsafe { __cpuid(1) };
+        assert_eq!(test(proc_info_edx, 25), std::is_x86_feature_detected!("sse"));
+        assert_eq!(test(proc_info_edx, 26), std::is_x86_feature_detected!("sse2"));
+    }
+}
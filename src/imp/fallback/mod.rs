@@ -81,13 +81,28 @@ type and the value type must be the same.
             any(target_os = "linux", target_os = "android"),
             not(portable_atomic_no_outline_atomics),
         ),
+        all(
+            target_arch = "x86",
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            not(portable_atomic_no_outline_atomics),
+        ),
+        all(
+            target_arch = "loongarch64",
+            not(portable_atomic_no_asm),
+            not(portable_atomic_no_outline_atomics),
+            not(any(miri, portable_atomic_sanitize_thread)),
+        ),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
     ),
     allow(dead_code)
 )]
 
-#[macro_use]
-pub(crate) mod utils;
-
 // Use "wide" sequence lock if the pointer width <= 32 for preventing its counter against wrap
 // around.
 //
@@ -106,10 +121,13 @@ cfg_no_fast_atomic_64! {
     mod seq_lock;
 }
 
+#[cfg(feature = "fallback-stats")]
+use core::sync::atomic::AtomicUsize;
 use core::{cell::UnsafeCell, mem, sync::atomic::Ordering};
 
 use seq_lock::{SeqLock, SeqLockWriteGuard};
-use utils::CachePadded;
+
+use crate::cache_padded::CachePadded;
 
 // Some 64-bit architectures have ABI with 32-bit pointer width (e.g., x86_64 X32 ABI,
 // AArch64 ILP32 ABI, mips64 N32 ABI). On those targets, AtomicU64 is fast,
@@ -117,27 +135,87 @@ use utils::CachePadded;
 use seq_lock::{AtomicChunk, Chunk};
 
 // Adapted from https://github.com/crossbeam-rs/crossbeam/blob/crossbeam-utils-0.8.7/crossbeam-utils/src/atomic/atomic_cell.rs#L969-L1016.
+// The number of locks is a prime number because we want to make sure `addr % LEN` gets
+// dispersed across all locks.
+//
+// crossbeam-utils 0.8.7 uses 97 here but does not use CachePadded,
+// so the actual concurrency level will be smaller.
+const LEN: usize = 67;
+const L: CachePadded<SeqLock> = CachePadded::new(SeqLock::new());
+static LOCKS: [CachePadded<SeqLock>; LEN] = [
+    L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L,
+    L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L,
+    L, L, L,
+];
+
 #[inline]
 #[must_use]
 fn lock(addr: usize) -> &'static SeqLock {
-    // The number of locks is a prime number because we want to make sure `addr % LEN` gets
-    // dispersed across all locks.
-    //
-    // crossbeam-utils 0.8.7 uses 97 here but does not use CachePadded,
-    // so the actual concurrency level will be smaller.
-    const LEN: usize = 67;
-    const L: CachePadded<SeqLock> = CachePadded::new(SeqLock::new());
-    static LOCKS: [CachePadded<SeqLock>; LEN] = [
-        L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L,
-        L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L, L,
-        L, L, L, L, L, L, L,
-    ];
-
     // If the modulus is a constant number, the compiler will use crazy math to transform this into
     // a sequence of cheap arithmetic operations rather than using the slow modulo instruction.
     &LOCKS[addr % LEN]
 }
 
+// Counters of a lock, used when the `fallback-stats` feature is enabled.
+// See src/fallback_stats.rs for the public API.
+#[cfg(feature = "fallback-stats")]
+struct Counters {
+    optimistic_read_failures: AtomicUsize,
+    write_lock_acquisitions: AtomicUsize,
+    spin_iterations: AtomicUsize,
+}
+
+#[cfg(feature = "fallback-stats")]
+impl Counters {
+    #[inline]
+    const fn new() -> Self {
+        Self {
+            optimistic_read_failures: AtomicUsize::new(0),
+            write_lock_acquisitions: AtomicUsize::new(0),
+            spin_iterations: AtomicUsize::new(0),
+        }
+    }
+
+    #[inline]
+    fn optimistic_read_failed(&self) {
+        self.optimistic_read_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn write_locked(&self, spins: usize) {
+        self.write_lock_acquisitions.fetch_add(1, Ordering::Relaxed);
+        if spins != 0 {
+            self.spin_iterations.fetch_add(spins, Ordering::Relaxed);
+        }
+    }
+
+    fn load(&self) -> crate::fallback_stats::LockStats {
+        crate::fallback_stats::LockStats::new(
+            self.optimistic_read_failures.load(Ordering::Relaxed),
+            self.write_lock_acquisitions.load(Ordering::Relaxed),
+            self.spin_iterations.load(Ordering::Relaxed),
+        )
+    }
+
+    fn reset(&self) {
+        self.optimistic_read_failures.store(0, Ordering::Relaxed);
+        self.write_lock_acquisitions.store(0, Ordering::Relaxed);
+        self.spin_iterations.store(0, Ordering::Relaxed);
+    }
+}
+
+#[cfg(feature = "fallback-stats")]
+pub(crate) fn lock_stats(index: usize) -> Option<crate::fallback_stats::LockStats> {
+    // Locks are indexed in the same way as lock() does.
+    LOCKS.get(index).map(|lock| lock.counters.load())
+}
+#[cfg(feature = "fallback-stats")]
+pub(crate) fn reset_lock_stats() {
+    for lock in &LOCKS {
+        lock.counters.reset();
+    }
+}
+
 macro_rules! atomic {
     ($atomic_type:ident, $int_type:ident, $align:literal) => {
         #[repr(C, align($align))]
@@ -441,4 +519,22 @@ mod tests {
         stress_test!(u64);
     }
     stress_test!(u128);
+
+    #[cfg(feature = "fallback-stats")]
+    #[test]
+    fn stats() {
+        let a = AtomicU128::new(0);
+        let index = a.as_ptr() as usize % LEN;
+        let before = lock_stats(index).unwrap();
+        a.store(1, Ordering::Relaxed);
+        a.fetch_add(1, Ordering::Relaxed);
+        assert_eq!(a.load(Ordering::Relaxed), 2);
+        let after = lock_stats(index).unwrap();
+        // Other tests running concurrently may use the same lock.
+        assert!(
+            after.write_lock_acquisitions().wrapping_sub(before.write_lock_acquisitions()) >= 2
+        );
+        assert!(lock_stats(LEN - 1).is_some());
+        assert!(lock_stats(LEN).is_none());
+    }
 }
@@ -12,18 +12,33 @@ This module provides helpers to implement them.
 
 use core::sync::atomic::Ordering;
 
-#[cfg(any(target_arch = "x86_64", target_arch = "powerpc64", target_arch = "riscv64"))]
+#[cfg(any(
+    target_arch = "loongarch64",
+    target_arch = "powerpc64",
+    target_arch = "riscv64",
+    target_arch = "x86_64",
+))]
 pub(crate) type Udw = u128;
-#[cfg(any(target_arch = "x86_64", target_arch = "powerpc64", target_arch = "riscv64"))]
+#[cfg(any(
+    target_arch = "loongarch64",
+    target_arch = "powerpc64",
+    target_arch = "riscv64",
+    target_arch = "x86_64",
+))]
 pub(crate) type AtomicUdw = super::super::super::fallback::AtomicU128;
-#[cfg(any(target_arch = "x86_64", target_arch = "powerpc64", target_arch = "riscv64"))]
+#[cfg(any(
+    target_arch = "loongarch64",
+    target_arch = "powerpc64",
+    target_arch = "riscv64",
+    target_arch = "x86_64",
+))]
 pub(crate) type AtomicIdw = super::super::super::fallback::AtomicI128;
 
-#[cfg(any(target_arch = "arm", target_arch = "riscv32"))]
+#[cfg(any(target_arch = "arm", target_arch = "riscv32", target_arch = "x86"))]
 pub(crate) type Udw = u64;
-#[cfg(any(target_arch = "arm", target_arch = "riscv32"))]
+#[cfg(any(target_arch = "arm", target_arch = "riscv32", target_arch = "x86"))]
 pub(crate) type AtomicUdw = super::super::super::fallback::AtomicU64;
-#[cfg(any(target_arch = "arm", target_arch = "riscv32"))]
+#[cfg(any(target_arch = "arm", target_arch = "riscv32", target_arch = "x86"))]
 pub(crate) type AtomicIdw = super::super::super::fallback::AtomicI64;
 
 // Asserts that the function is called in the correct context.
@@ -45,6 +60,14 @@ macro_rules! debug_assert_outline_atomics {
         {
             debug_assert!(!super::has_kuser_cmpxchg64());
         }
+        #[cfg(target_arch = "x86")]
+        {
+            debug_assert!(!super::detect::detect().has_cmpxchg8b());
+        }
+        #[cfg(target_arch = "loongarch64")]
+        {
+            debug_assert!(!super::detect::detect().has_scq());
+        }
     };
 }
 
@@ -61,7 +84,12 @@ fn_alias! {
     #[cold]
     pub(crate) unsafe fn(src: *mut Udw) -> Udw;
     // fallback's atomic load has at least acquire semantics.
-    #[cfg(not(any(target_arch = "arm", target_arch = "x86_64")))]
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "loongarch64",
+        target_arch = "x86",
+        target_arch = "x86_64",
+    )))]
     atomic_load_non_seqcst = atomic_load(Ordering::Acquire);
     atomic_load_seqcst = atomic_load(Ordering::SeqCst);
 }
@@ -81,7 +109,10 @@ fn_alias! {
     #[cold]
     pub(crate) unsafe fn(dst: *mut Udw, val: Udw);
     // fallback's atomic store has at least release semantics.
-    #[cfg(not(target_arch = "arm"))]
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "loongarch64",
+    )))]
     atomic_store_non_seqcst = atomic_store(Ordering::Release);
     atomic_store_seqcst = atomic_store(Ordering::SeqCst);
 }
@@ -108,7 +139,12 @@ fn_alias! {
     #[cold]
     pub(crate) unsafe fn(dst: *mut Udw, old: Udw, new: Udw) -> (Udw, bool);
     // fallback's atomic CAS has at least AcqRel semantics.
-    #[cfg(not(any(target_arch = "arm", target_arch = "x86_64")))]
+    #[cfg(not(any(
+        target_arch = "arm",
+        target_arch = "loongarch64",
+        target_arch = "x86",
+        target_arch = "x86_64",
+    )))]
     atomic_compare_exchange_non_seqcst
         = atomic_compare_exchange(Ordering::AcqRel, Ordering::Acquire);
     atomic_compare_exchange_seqcst
@@ -138,7 +174,12 @@ macro_rules! atomic_rmw_3 {
             #[cold]
             pub(crate) unsafe fn(dst: *mut Udw, val: Udw) -> Udw;
             // fallback's atomic RMW has at least AcqRel semantics.
-            #[cfg(not(any(target_arch = "arm", target_arch = "x86_64")))]
+            #[cfg(not(any(
+                target_arch = "arm",
+                target_arch = "loongarch64",
+                target_arch = "x86",
+                target_arch = "x86_64",
+            )))]
             $non_seqcst_alias = $name(Ordering::AcqRel);
             $seqcst_alias = $name(Ordering::SeqCst);
         }
@@ -162,7 +203,12 @@ macro_rules! atomic_rmw_2 {
             #[cold]
             pub(crate) unsafe fn(dst: *mut Udw) -> Udw;
             // fallback's atomic RMW has at least AcqRel semantics.
-            #[cfg(not(any(target_arch = "arm", target_arch = "x86_64")))]
+            #[cfg(not(any(
+                target_arch = "arm",
+                target_arch = "loongarch64",
+                target_arch = "x86",
+                target_arch = "x86_64",
+            )))]
             $non_seqcst_alias = $name(Ordering::AcqRel);
             $seqcst_alias = $name(Ordering::SeqCst);
         }
@@ -7,7 +7,7 @@ use core::{
     sync::atomic::{self, Ordering},
 };
 
-use super::utils::Backoff;
+use crate::Backoff;
 
 // See mod.rs for details.
 #[cfg(any(target_pointer_width = "16", target_pointer_width = "32"))]
@@ -30,12 +30,20 @@ pub(super) struct SeqLock {
     /// All bits except the least significant one hold the current stamp. When locked, the state
     /// equals 1 and doesn't contain a valid stamp.
     state: AtomicStamp,
+
+    /// Contention statistics of the lock.
+    #[cfg(feature = "fallback-stats")]
+    pub(super) counters: super::Counters,
 }
 
 impl SeqLock {
     #[inline]
     pub(super) const fn new() -> Self {
-        Self { state: AtomicStamp::new(0) }
+        Self {
+            state: AtomicStamp::new(0),
+            #[cfg(feature = "fallback-stats")]
+            counters: super::Counters::new(),
+        }
     }
 
     /// If not locked, returns the current stamp.
@@ -45,6 +53,8 @@ impl SeqLock {
     pub(super) fn optimistic_read(&self) -> Option<Stamp> {
         let state = self.state.load(Ordering::Acquire);
         if state == 1 {
+            #[cfg(feature = "fallback-stats")]
+            self.counters.optimistic_read_failed();
             None
         } else {
             Some(state)
@@ -58,17 +68,27 @@ impl SeqLock {
     #[inline]
     pub(super) fn validate_read(&self, stamp: Stamp) -> bool {
         atomic::fence(Ordering::Acquire);
-        self.state.load(Ordering::Relaxed) == stamp
+        let valid = self.state.load(Ordering::Relaxed) == stamp;
+        #[cfg(feature = "fallback-stats")]
+        if !valid {
+            self.counters.optimistic_read_failed();
+        }
+        valid
     }
 
     /// Grabs the lock for writing.
     #[inline]
     pub(super) fn write(&self) -> SeqLockWriteGuard<'_> {
         let mut backoff = Backoff::new();
+        #[cfg(feature = "fallback-stats")]
+        let mut spins = 0;
         loop {
             let previous = self.state.swap(1, Ordering::Acquire);
 
             if previous != 1 {
+                #[cfg(feature = "fallback-stats")]
+                self.counters.write_locked(spins);
+
                 atomic::fence(Ordering::Release);
 
                 return SeqLockWriteGuard { lock: self, state: previous };
@@ -76,6 +96,10 @@ impl SeqLock {
 
             while self.state.load(Ordering::Relaxed) == 1 {
                 backoff.snooze();
+                #[cfg(feature = "fallback-stats")]
+                {
+                    spins += 1;
+                }
             }
         }
     }
@@ -7,7 +7,7 @@ use core::{
     sync::atomic::{self, AtomicUsize, Ordering},
 };
 
-use super::utils::Backoff;
+use crate::Backoff;
 
 // See mod.rs for details.
 pub(super) type AtomicChunk = AtomicUsize;
@@ -26,12 +26,21 @@ pub(super) struct SeqLock {
     /// All bits except the least significant one hold the current stamp. When locked, the state_lo
     /// equals 1 and doesn't contain a valid stamp.
     state_lo: AtomicUsize,
+
+    /// Contention statistics of the lock.
+    #[cfg(feature = "fallback-stats")]
+    pub(super) counters: super::Counters,
 }
 
 impl SeqLock {
     #[inline]
     pub(super) const fn new() -> Self {
-        Self { state_hi: AtomicUsize::new(0), state_lo: AtomicUsize::new(0) }
+        Self {
+            state_hi: AtomicUsize::new(0),
+            state_lo: AtomicUsize::new(0),
+            #[cfg(feature = "fallback-stats")]
+            counters: super::Counters::new(),
+        }
     }
 
     /// If not locked, returns the current stamp.
@@ -48,6 +57,8 @@ impl SeqLock {
         let state_hi = self.state_hi.load(Ordering::Acquire);
         let state_lo = self.state_lo.load(Ordering::Acquire);
         if state_lo == 1 {
+            #[cfg(feature = "fallback-stats")]
+            self.counters.optimistic_read_failed();
             None
         } else {
             Some((state_hi, state_lo))
@@ -79,17 +90,27 @@ impl SeqLock {
         // Except for the case that both `state_hi` and `state_lo` wrapped around, the following
         // condition implies that we're noticing no modification to the data after the critical
         // section of `(stamp.0, stamp.1)`.
-        (state_hi, state_lo) == stamp
+        let valid = (state_hi, state_lo) == stamp;
+        #[cfg(feature = "fallback-stats")]
+        if !valid {
+            self.counters.optimistic_read_failed();
+        }
+        valid
     }
 
     /// Grabs the lock for writing.
     #[inline]
     pub(super) fn write(&self) -> SeqLockWriteGuard<'_> {
         let mut backoff = Backoff::new();
+        #[cfg(feature = "fallback-stats")]
+        let mut spins = 0;
         loop {
             let previous = self.state_lo.swap(1, Ordering::Acquire);
 
             if previous != 1 {
+                #[cfg(feature = "fallback-stats")]
+                self.counters.write_locked(spins);
+
                 // To synchronize with the acquire fence in `validate_read` via any modification to
                 // the data at the critical section of `(state_hi, previous)`.
                 atomic::fence(Ordering::Release);
@@ -99,6 +120,10 @@ impl SeqLock {
 
             while self.state_lo.load(Ordering::Relaxed) == 1 {
                 backoff.snooze();
+                #[cfg(feature = "fallback-stats")]
+                {
+                    spins += 1;
+                }
             }
         }
     }
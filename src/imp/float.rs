@@ -20,7 +20,9 @@ future: https://github.com/taiki-e/portable-atomic/issues/34 / https://github.co
     allow(unused_imports, unused_macros)
 )]
 
-use core::{cell::UnsafeCell, sync::atomic::Ordering};
+#[cfg(not(portable_atomic_loom))]
+use core::cell::UnsafeCell;
+use core::sync::atomic::Ordering;
 
 macro_rules! atomic_float {
     (
@@ -29,7 +31,11 @@ macro_rules! atomic_float {
     ) => {
         #[repr(C, align($align))]
         pub(crate) struct $atomic_type {
+            #[cfg(not(portable_atomic_loom))]
             v: UnsafeCell<$float_type>,
+            // loom's atomic integers do not have the same layout as the integer.
+            #[cfg(portable_atomic_loom)]
+            v: crate::$atomic_int_type,
         }
 
         // Send is implicitly implemented.
@@ -37,10 +43,16 @@ macro_rules! atomic_float {
         unsafe impl Sync for $atomic_type {}
 
         impl $atomic_type {
+            #[cfg(not(portable_atomic_loom))]
             #[inline]
             pub(crate) const fn new(v: $float_type) -> Self {
                 Self { v: UnsafeCell::new(v) }
             }
+            #[cfg(portable_atomic_loom)]
+            #[inline]
+            pub(crate) fn new(v: $float_type) -> Self {
+                Self { v: crate::$atomic_int_type::new(v.to_bits()) }
+            }
 
             #[inline]
             pub(crate) fn is_lock_free() -> bool {
@@ -71,15 +83,34 @@ macro_rules! atomic_float {
                 const_if: #[cfg(not(portable_atomic_no_const_raw_ptr_deref))];
                 #[inline(always)]
                 pub(crate) const fn as_bits(&self) -> &crate::$atomic_int_type {
+                    #[cfg(not(portable_atomic_loom))]
                     // SAFETY: $atomic_type and $atomic_int_type have the same layout,
                     // and there is no concurrent access to the value that does not go through this method.
-                    unsafe { &*(self as *const Self as *const crate::$atomic_int_type) }
+                    unsafe {
+                        &*(self as *const Self as *const crate::$atomic_int_type)
+                    }
+                    #[cfg(portable_atomic_loom)]
+                    {
+                        &self.v
+                    }
                 }
             }
 
             #[inline]
             pub(crate) const fn as_ptr(&self) -> *mut $float_type {
-                self.v.get()
+                #[cfg(not(portable_atomic_loom))]
+                {
+                    self.v.get()
+                }
+                #[cfg(portable_atomic_loom)]
+                {
+                    self.v.as_ptr() as *mut $float_type
+                }
+            }
+            #[cfg(portable_atomic_loom)]
+            #[inline]
+            pub(crate) fn into_inner(self) -> $float_type {
+                $float_type::from_bits(self.v.into_inner())
             }
         }
 
@@ -0,0 +1,554 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Atomic types for model checking with loom. This is used instead of other
+implementations when `--cfg portable_atomic_loom` is set.
+
+Each atomic type owns a loom object, which is created by `new`, so loom sees the
+thread that calls `new` as the thread that created the atomic value, like loom's
+own atomic types. Since loom objects cannot be created in const context, `new` is
+not `const fn` when this cfg is set.
+
+loom's atomic types do not have the same in-memory representation as the value
+type, so the atomic types here also keep the latest value (the latest value in
+the modification order) in UnsafeCell, which as_ptr, get_mut, etc. access. The
+value is written back after each modification. This does not cause data races
+because loom runs all threads of an execution on a single OS thread. APIs that
+require the same in-memory representation as the value type (from_ptr, from_mut,
+etc.) are not available when this cfg is set.
+
+Values written via as_ptr or get_mut are passed to the loom object by the next
+atomic operation, so loom sees the thread that performs that operation as the
+thread that wrote them.
+
+Operations that loom's atomic types do not provide (e.g., fetch_neg, bit_set)
+are emulated using CAS loops, and 128-bit atomic types are emulated using loom's
+Mutex (this ignores the ordering argument and always models it as SeqCst).
+*/
+
+#![allow(dead_code)]
+
+use core::{
+    cell::{Cell, UnsafeCell},
+    marker::PhantomData,
+    sync::atomic::Ordering,
+};
+use std::sync::PoisonError;
+
+#[cfg(feature = "float")]
+#[allow(clippy::float_arithmetic)]
+#[path = "float.rs"]
+pub(crate) mod float;
+
+#[path = "memcpy.rs"]
+pub(crate) mod memcpy;
+
+// loom does not use the lock-based fallback implementation.
+#[cfg(feature = "fallback-stats")]
+pub(crate) fn fallback_lock_stats(_index: usize) -> Option<crate::fallback_stats::LockStats> {
+    None
+}
+#[cfg(feature = "fallback-stats")]
+pub(crate) fn fallback_reset_lock_stats() {}
+
+// A loom object and the latest value of it.
+struct Object<L, T> {
+    inner: UnsafeCell<L>,
+    v: UnsafeCell<T>,
+    // The value that was last written back to `v`.
+    last: Cell<T>,
+}
+
+impl<L, T: Copy + PartialEq> Object<L, T> {
+    #[inline]
+    fn new(v: T, new: fn(T) -> L) -> Self {
+        Self { inner: UnsafeCell::new(new(v)), v: UnsafeCell::new(v), last: Cell::new(v) }
+    }
+
+    // Returns the loom object, after passing the value written via as_ptr or
+    // get_mut to it if there is such a value.
+    #[inline]
+    fn get(&self, set: fn(&mut L, T)) -> &L {
+        // SAFETY: loom runs all threads on a single OS thread.
+        let current = unsafe { *self.v.get() };
+        if current != self.last.get() {
+            self.last.set(current);
+            // SAFETY: the value was written non-atomically, so the atomic operations
+            // performed before it happen before this, and the others have not yet
+            // reached the loom object because they perform this check first.
+            unsafe { set(&mut *self.inner.get(), current) }
+        }
+        // SAFETY: the loom object is only mutably accessed above.
+        unsafe { &*self.inner.get() }
+    }
+
+    #[inline]
+    fn write_back(&self, new: T) {
+        self.last.set(new);
+        // SAFETY: loom runs all threads on a single OS thread.
+        unsafe { *self.v.get() = new }
+    }
+
+    #[inline]
+    const fn as_ptr(&self) -> *mut T {
+        self.v.get()
+    }
+
+    #[inline]
+    fn into_inner(self) -> T {
+        self.v.into_inner()
+    }
+}
+
+macro_rules! atomic_int {
+    ($atomic_type:ident, $int_type:ident, $loom_type:ident) => {
+        pub(crate) struct $atomic_type {
+            object: Object<loom::sync::atomic::$loom_type, $int_type>,
+        }
+
+        // Send is implicitly implemented.
+        // SAFETY: the value is only accessed via loom's atomic types, and loom
+        // runs all threads on a single OS thread.
+        unsafe impl Sync for $atomic_type {}
+
+        impl_default_no_fetch_ops!($atomic_type, $int_type);
+        impl_default_bit_opts!($atomic_type, $int_type);
+        impl $atomic_type {
+            #[inline]
+            pub(crate) fn new(v: $int_type) -> Self {
+                Self { object: Object::new(v, loom::sync::atomic::$loom_type::new) }
+            }
+
+            #[inline]
+            pub(crate) fn is_lock_free() -> bool {
+                Self::IS_ALWAYS_LOCK_FREE
+            }
+            pub(crate) const IS_ALWAYS_LOCK_FREE: bool = true;
+
+            #[inline]
+            fn inner(&self) -> &loom::sync::atomic::$loom_type {
+                self.object.get(|inner, v| inner.with_mut(|inner| *inner = v))
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn load(&self, order: Ordering) -> $int_type {
+                crate::utils::assert_load_ordering(order);
+                self.inner().load(order)
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn store(&self, val: $int_type, order: Ordering) {
+                crate::utils::assert_store_ordering(order);
+                self.inner().store(val, order);
+                self.object.write_back(val);
+            }
+
+            #[inline]
+            pub(crate) fn swap(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().swap(val, order);
+                self.object.write_back(val);
+                prev
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn compare_exchange(
+                &self,
+                current: $int_type,
+                new: $int_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int_type, $int_type> {
+                crate::utils::assert_compare_exchange_ordering(success, failure);
+                let res = self.inner().compare_exchange(current, new, success, failure);
+                if res.is_ok() {
+                    self.object.write_back(new);
+                }
+                res
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn compare_exchange_weak(
+                &self,
+                current: $int_type,
+                new: $int_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int_type, $int_type> {
+                crate::utils::assert_compare_exchange_ordering(success, failure);
+                let res = self.inner().compare_exchange_weak(current, new, success, failure);
+                if res.is_ok() {
+                    self.object.write_back(new);
+                }
+                res
+            }
+
+            #[inline]
+            fn fetch_update_<F>(&self, order: Ordering, mut f: F) -> $int_type
+            where
+                F: FnMut($int_type) -> $int_type,
+            {
+                let mut prev = self.load(Ordering::Relaxed);
+                loop {
+                    let next = f(prev);
+                    match self.compare_exchange_weak(prev, next, order, Ordering::Relaxed) {
+                        Ok(x) => return x,
+                        Err(next_prev) => prev = next_prev,
+                    }
+                }
+            }
+
+            #[inline]
+            pub(crate) fn fetch_add(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_add(val, order);
+                self.object.write_back(prev.wrapping_add(val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_sub(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_sub(val, order);
+                self.object.write_back(prev.wrapping_sub(val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_and(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_and(val, order);
+                self.object.write_back(prev & val);
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_nand(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_nand(val, order);
+                self.object.write_back(!(prev & val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_or(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_or(val, order);
+                self.object.write_back(prev | val);
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_xor(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_xor(val, order);
+                self.object.write_back(prev ^ val);
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_max(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_max(val, order);
+                self.object.write_back(core::cmp::max(prev, val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_min(&self, val: $int_type, order: Ordering) -> $int_type {
+                let prev = self.inner().fetch_min(val, order);
+                self.object.write_back(core::cmp::min(prev, val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn fetch_not(&self, order: Ordering) -> $int_type {
+                self.fetch_xor(!0, order)
+            }
+            #[inline]
+            pub(crate) fn not(&self, order: Ordering) {
+                self.fetch_not(order);
+            }
+
+            #[inline]
+            pub(crate) fn fetch_neg(&self, order: Ordering) -> $int_type {
+                self.fetch_update_(order, $int_type::wrapping_neg)
+            }
+            #[inline]
+            pub(crate) fn neg(&self, order: Ordering) {
+                self.fetch_neg(order);
+            }
+
+            #[inline]
+            pub(crate) const fn as_ptr(&self) -> *mut $int_type {
+                self.object.as_ptr()
+            }
+            #[inline]
+            pub(crate) fn into_inner(self) -> $int_type {
+                self.object.into_inner()
+            }
+        }
+    };
+}
+
+// loom does not have 128-bit atomic types, so emulate them using loom's Mutex.
+macro_rules! atomic_int_mutex {
+    ($atomic_type:ident, $int_type:ident) => {
+        pub(crate) struct $atomic_type {
+            object: Object<loom::sync::Mutex<$int_type>, $int_type>,
+        }
+
+        // Send is implicitly implemented.
+        // SAFETY: the value is only accessed via loom's Mutex, and loom runs all
+        // threads on a single OS thread.
+        unsafe impl Sync for $atomic_type {}
+
+        impl_default_no_fetch_ops!($atomic_type, $int_type);
+        impl_default_bit_opts!($atomic_type, $int_type);
+        impl $atomic_type {
+            #[inline]
+            pub(crate) fn new(v: $int_type) -> Self {
+                Self { object: Object::new(v, loom::sync::Mutex::new) }
+            }
+
+            #[inline]
+            pub(crate) fn is_lock_free() -> bool {
+                Self::IS_ALWAYS_LOCK_FREE
+            }
+            pub(crate) const IS_ALWAYS_LOCK_FREE: bool = false;
+
+            // Applies `f` to the value, and returns the previous value.
+            #[inline]
+            fn update<F>(&self, f: F) -> $int_type
+            where
+                F: FnOnce($int_type) -> Option<$int_type>,
+            {
+                let inner = self.object.get(|inner, v| {
+                    *inner.get_mut().unwrap_or_else(PoisonError::into_inner) = v;
+                });
+                let mut v = inner.lock().unwrap_or_else(PoisonError::into_inner);
+                let prev = *v;
+                if let Some(next) = f(prev) {
+                    *v = next;
+                    self.object.write_back(next);
+                }
+                prev
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn load(&self, order: Ordering) -> $int_type {
+                crate::utils::assert_load_ordering(order);
+                self.update(|_| None)
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn store(&self, val: $int_type, order: Ordering) {
+                crate::utils::assert_store_ordering(order);
+                self.update(|_| Some(val));
+            }
+
+            #[inline]
+            pub(crate) fn swap(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|_| Some(val))
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn compare_exchange(
+                &self,
+                current: $int_type,
+                new: $int_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int_type, $int_type> {
+                crate::utils::assert_compare_exchange_ordering(success, failure);
+                let prev = self.update(|v| if v == current { Some(new) } else { None });
+                if prev == current {
+                    Ok(prev)
+                } else {
+                    Err(prev)
+                }
+            }
+
+            #[inline]
+            #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+            pub(crate) fn compare_exchange_weak(
+                &self,
+                current: $int_type,
+                new: $int_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$int_type, $int_type> {
+                self.compare_exchange(current, new, success, failure)
+            }
+
+            #[inline]
+            pub(crate) fn fetch_add(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v.wrapping_add(val)))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_sub(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v.wrapping_sub(val)))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_and(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v & val))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_nand(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(!(v & val)))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_or(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v | val))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_xor(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v ^ val))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_max(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(core::cmp::max(v, val)))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_min(&self, val: $int_type, _order: Ordering) -> $int_type {
+                self.update(|v| Some(core::cmp::min(v, val)))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_not(&self, _order: Ordering) -> $int_type {
+                self.update(|v| Some(!v))
+            }
+            #[inline]
+            pub(crate) fn not(&self, order: Ordering) {
+                self.fetch_not(order);
+            }
+
+            #[inline]
+            pub(crate) fn fetch_neg(&self, _order: Ordering) -> $int_type {
+                self.update(|v| Some(v.wrapping_neg()))
+            }
+            #[inline]
+            pub(crate) fn neg(&self, order: Ordering) {
+                self.fetch_neg(order);
+            }
+
+            #[inline]
+            pub(crate) const fn as_ptr(&self) -> *mut $int_type {
+                self.object.as_ptr()
+            }
+            #[inline]
+            pub(crate) fn into_inner(self) -> $int_type {
+                self.object.into_inner()
+            }
+        }
+    };
+}
+
+atomic_int!(AtomicIsize, isize, AtomicIsize);
+atomic_int!(AtomicUsize, usize, AtomicUsize);
+atomic_int!(AtomicI8, i8, AtomicI8);
+atomic_int!(AtomicU8, u8, AtomicU8);
+atomic_int!(AtomicI16, i16, AtomicI16);
+atomic_int!(AtomicU16, u16, AtomicU16);
+atomic_int!(AtomicI32, i32, AtomicI32);
+atomic_int!(AtomicU32, u32, AtomicU32);
+atomic_int!(AtomicI64, i64, AtomicI64);
+atomic_int!(AtomicU64, u64, AtomicU64);
+atomic_int_mutex!(AtomicI128, i128);
+atomic_int_mutex!(AtomicU128, u128);
+
+// AtomicPtr shares the layout with AtomicUsize, because the public AtomicPtr
+// implements some operations by casting itself to AtomicUsize.
+#[repr(transparent)]
+pub(crate) struct AtomicPtr<T> {
+    inner: AtomicUsize,
+    _marker: PhantomData<*mut T>,
+}
+
+// SAFETY: the value is only accessed via loom's atomic types, and loom runs all
+// threads on a single OS thread.
+unsafe impl<T> Send for AtomicPtr<T> {}
+// SAFETY: see above.
+unsafe impl<T> Sync for AtomicPtr<T> {}
+
+impl<T> AtomicPtr<T> {
+    #[inline]
+    pub(crate) fn new(v: *mut T) -> Self {
+        Self { inner: AtomicUsize::new(v as usize), _marker: PhantomData }
+    }
+    #[inline]
+    pub(crate) fn is_lock_free() -> bool {
+        Self::IS_ALWAYS_LOCK_FREE
+    }
+    pub(crate) const IS_ALWAYS_LOCK_FREE: bool = true;
+    #[inline]
+    fn as_atomic_usize(&self) -> &AtomicUsize {
+        &self.inner
+    }
+    #[inline]
+    #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+    pub(crate) fn load(&self, order: Ordering) -> *mut T {
+        self.as_atomic_usize().load(order) as *mut T
+    }
+    #[inline]
+    #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+    pub(crate) fn store(&self, ptr: *mut T, order: Ordering) {
+        self.as_atomic_usize().store(ptr as usize, order);
+    }
+    #[inline]
+    pub(crate) fn swap(&self, ptr: *mut T, order: Ordering) -> *mut T {
+        self.as_atomic_usize().swap(ptr as usize, order) as *mut T
+    }
+    #[inline]
+    #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+    pub(crate) fn compare_exchange(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        match self.as_atomic_usize().compare_exchange(
+            current as usize,
+            new as usize,
+            success,
+            failure,
+        ) {
+            Ok(v) => Ok(v as *mut T),
+            Err(v) => Err(v as *mut T),
+        }
+    }
+    #[inline]
+    #[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+    pub(crate) fn compare_exchange_weak(
+        &self,
+        current: *mut T,
+        new: *mut T,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<*mut T, *mut T> {
+        match self.as_atomic_usize().compare_exchange_weak(
+            current as usize,
+            new as usize,
+            success,
+            failure,
+        ) {
+            Ok(v) => Ok(v as *mut T),
+            Err(v) => Err(v as *mut T),
+        }
+    }
+    #[inline]
+    pub(crate) const fn as_ptr(&self) -> *mut *mut T {
+        self.inner.as_ptr() as *mut *mut T
+    }
+    #[inline]
+    pub(crate) fn into_inner(self) -> *mut T {
+        self.inner.into_inner() as *mut T
+    }
+}
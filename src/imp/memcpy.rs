@@ -0,0 +1,210 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Per-element atomic memcpy between slices of atomic integers and plain buffers.
+
+This is the "byte-wise atomic memcpy" proposed in P1478, generalized to
+element types wider than a byte:
+https://www.open-std.org/jtc1/sc22/wg21/docs/papers/2022/p1478r7.html
+
+Like the fallback implementation does (see fallback/mod.rs), the chunked variants
+load/store the middle of the slice in chunks of usize and the remaining elements at
+both ends one by one. The chunk boundaries are determined by the address, so
+calls on the same range always access each element with the same granularity.
+
+See also atomic-memcpy crate, a generic implementation of this pattern:
+https://github.com/taiki-e/atomic-memcpy
+*/
+
+use core::{mem, ptr, sync::atomic::Ordering};
+
+use super::AtomicUsize;
+
+const CHUNK_SIZE: usize = mem::size_of::<AtomicUsize>();
+
+// Orderings of the operation on each element/chunk. Acquire/Release are
+// implemented by a single fence before/after the whole copy.
+#[inline]
+fn per_element_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::SeqCst => Ordering::SeqCst,
+        _ => Ordering::Relaxed,
+    }
+}
+
+#[inline]
+#[cfg_attr(any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri), track_caller)]
+fn assert_len(src: usize, dst: usize) {
+    assert!(
+        src == dst,
+        "source slice length ({}) does not match destination slice length ({})",
+        src,
+        dst,
+    );
+}
+
+// Returns the number of elements before the first chunk and the number of chunks.
+#[inline]
+fn split<A>(ptr: *const A, len: usize) -> (usize, usize) {
+    let size = mem::size_of::<A>();
+    // loom cannot model mixed-size atomic accesses.
+    if size >= CHUNK_SIZE || cfg!(portable_atomic_loom) {
+        return (len, 0);
+    }
+    let misalign = ptr as usize % CHUNK_SIZE;
+    let head = if misalign == 0 { 0 } else { (CHUNK_SIZE - misalign) / size };
+    if head >= len {
+        return (len, 0);
+    }
+    (head, (len - head) * size / CHUNK_SIZE)
+}
+
+#[inline]
+#[cfg_attr(any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri), track_caller)]
+pub(crate) fn load<A, T: Copy>(
+    src: &[A],
+    dst: &mut [T],
+    order: Ordering,
+    load: fn(&A, Ordering) -> T,
+) {
+    crate::utils::assert_load_ordering(order);
+    assert_len(src.len(), dst.len());
+    load_elements(src, dst, order, load);
+}
+
+#[inline]
+fn load_elements<A, T: Copy>(
+    src: &[A],
+    dst: &mut [T],
+    order: Ordering,
+    load: fn(&A, Ordering) -> T,
+) {
+    debug_assert_eq!(mem::size_of::<A>(), mem::size_of::<T>());
+    let elem_order = per_element_ordering(order);
+    for (d, s) in dst.iter_mut().zip(src) {
+        *d = load(s, elem_order);
+    }
+    if order == Ordering::Acquire {
+        crate::fence(Ordering::Acquire);
+    }
+}
+
+#[inline]
+#[cfg_attr(any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri), track_caller)]
+pub(crate) fn store<A, T: Copy>(dst: &[A], src: &[T], order: Ordering, store: fn(&A, T, Ordering)) {
+    crate::utils::assert_store_ordering(order);
+    assert_len(src.len(), dst.len());
+    store_elements(dst, src, order, store);
+}
+
+#[inline]
+fn store_elements<A, T: Copy>(dst: &[A], src: &[T], order: Ordering, store: fn(&A, T, Ordering)) {
+    debug_assert_eq!(mem::size_of::<A>(), mem::size_of::<T>());
+    if order == Ordering::Release {
+        crate::fence(Ordering::Release);
+    }
+    let elem_order = per_element_ordering(order);
+    for (d, &s) in dst.iter().zip(src) {
+        store(d, s, elem_order);
+    }
+}
+
+/// # Safety
+///
+/// `src` must not be concurrently accessed by atomic operations of other sizes,
+/// including `load_chunked`/`store_chunked` on a different range.
+#[inline]
+#[cfg_attr(any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri), track_caller)]
+pub(crate) unsafe fn load_chunked<A, T: Copy>(
+    src: &[A],
+    dst: &mut [T],
+    order: Ordering,
+    load: fn(&A, Ordering) -> T,
+) {
+    crate::utils::assert_load_ordering(order);
+    assert_len(src.len(), dst.len());
+    debug_assert_eq!(mem::size_of::<A>(), mem::size_of::<T>());
+    let (head, chunks) = split(src.as_ptr(), src.len());
+    if chunks == 0 {
+        load_elements(src, dst, order, load);
+        return;
+    }
+    let elem_order = per_element_ordering(order);
+    let mid = head + chunks * (CHUNK_SIZE / mem::size_of::<A>());
+    for (d, s) in dst[..head].iter_mut().zip(&src[..head]) {
+        *d = load(s, elem_order);
+    }
+    let src_chunks = src[head..].as_ptr() as *const AtomicUsize;
+    let dst_chunks = dst[head..].as_mut_ptr() as *mut usize;
+    for i in 0..chunks {
+        // SAFETY:
+        // - src[head..mid] is aligned to CHUNK_SIZE (checked by split) and has the same
+        //   size as [AtomicUsize; chunks].
+        // - A is an atomic integer type, so it has the same in-memory representation as
+        //   an integer, and loading it as a chunk of usize is equivalent to loading each
+        //   element atomically.
+        // - dst[head..mid] is valid for writes of [usize; chunks] (may be unaligned).
+        // - the caller must guarantee that there are no concurrent atomic accesses of
+        //   other sizes.
+        unsafe {
+            let v = (*src_chunks.add(i)).load(elem_order);
+            ptr::write_unaligned(dst_chunks.add(i), v);
+        }
+    }
+    for (d, s) in dst[mid..].iter_mut().zip(&src[mid..]) {
+        *d = load(s, elem_order);
+    }
+    if order == Ordering::Acquire {
+        crate::fence(Ordering::Acquire);
+    }
+}
+
+/// # Safety
+///
+/// `dst` must not be concurrently accessed by atomic operations of other sizes,
+/// including `load_chunked`/`store_chunked` on a different range.
+#[inline]
+#[cfg_attr(any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri), track_caller)]
+pub(crate) unsafe fn store_chunked<A, T: Copy>(
+    dst: &[A],
+    src: &[T],
+    order: Ordering,
+    store: fn(&A, T, Ordering),
+) {
+    crate::utils::assert_store_ordering(order);
+    assert_len(src.len(), dst.len());
+    debug_assert_eq!(mem::size_of::<A>(), mem::size_of::<T>());
+    let (head, chunks) = split(dst.as_ptr(), dst.len());
+    if chunks == 0 {
+        store_elements(dst, src, order, store);
+        return;
+    }
+    if order == Ordering::Release {
+        crate::fence(Ordering::Release);
+    }
+    let elem_order = per_element_ordering(order);
+    let mid = head + chunks * (CHUNK_SIZE / mem::size_of::<A>());
+    for (d, &s) in dst[..head].iter().zip(&src[..head]) {
+        store(d, s, elem_order);
+    }
+    let dst_chunks = dst[head..].as_ptr() as *const AtomicUsize;
+    let src_chunks = src[head..].as_ptr() as *const usize;
+    for i in 0..chunks {
+        // SAFETY:
+        // - dst[head..mid] is aligned to CHUNK_SIZE (checked by split) and has the same
+        //   size as [AtomicUsize; chunks].
+        // - A is an atomic integer type, so storing a chunk of usize is equivalent to
+        //   storing each element atomically.
+        // - src[head..mid] is valid for reads of [usize; chunks] (may be unaligned), and
+        //   integers are plain old data types so we can always read them as usize.
+        // - the caller must guarantee that there are no concurrent atomic accesses of
+        //   other sizes.
+        unsafe {
+            let v = ptr::read_unaligned(src_chunks.add(i));
+            (*dst_chunks.add(i)).store(v, elem_order);
+        }
+    }
+    for (d, &s) in dst[mid..].iter().zip(&src[mid..]) {
+        store(d, s, elem_order);
+    }
+}
@@ -70,13 +70,19 @@ mod riscv;
 mod x86;
 
 // 64-bit atomic implementations on 32-bit architectures
-#[cfg(any(target_arch = "arm", target_arch = "riscv32"))]
+#[cfg(any(
+    target_arch = "arm",
+    target_arch = "mips32r6",
+    target_arch = "riscv32",
+    target_arch = "x86",
+))]
 mod atomic64;
 
 // 128-bit atomic implementations on 64-bit architectures
 #[cfg(any(
     target_arch = "aarch64",
     target_arch = "arm64ec",
+    target_arch = "loongarch64",
     target_arch = "powerpc64",
     target_arch = "riscv64",
     target_arch = "s390x",
@@ -90,6 +96,7 @@ mod atomic128;
 #[cfg(feature = "fallback")]
 #[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(not(portable_atomic_no_atomic_cas)))]
 #[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(target_has_atomic = "ptr"))]
+#[cfg(any(test, not(feature = "require-address-free")))]
 #[cfg(any(
     test,
     not(any(
@@ -119,10 +126,35 @@ mod atomic128;
                 portable_atomic_target_feature = "quadword-atomics",
             ),
         ),
+        all(
+            target_arch = "loongarch64",
+            not(portable_atomic_no_asm),
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(target_feature = "scq", portable_atomic_target_feature = "scq"),
+        ),
         all(target_arch = "s390x", not(portable_atomic_no_asm)),
     ))
 ))]
-mod fallback;
+items! {
+    mod fallback;
+    #[cfg(feature = "fallback-stats")]
+    pub(crate) use self::fallback::{
+        lock_stats as fallback_lock_stats, reset_lock_stats as fallback_reset_lock_stats,
+    };
+}
+// There are no locks if the lock-based fallback implementation is not used.
+// The re-exports above take precedence over this glob import.
+#[cfg(feature = "fallback-stats")]
+#[allow(unused_imports)]
+pub(crate) use self::no_fallback::*;
+#[cfg(feature = "fallback-stats")]
+#[allow(dead_code)] // shadowed if the fallback implementation is used
+mod no_fallback {
+    pub(crate) fn fallback_lock_stats(_index: usize) -> Option<crate::fallback_stats::LockStats> {
+        None
+    }
+    pub(crate) fn fallback_reset_lock_stats() {}
+}
 
 // -----------------------------------------------------------------------------
 // Critical section based fallback implementations
@@ -161,6 +193,14 @@ mod interrupt;
 #[allow(clippy::float_arithmetic)]
 pub(crate) mod float;
 
+// -----------------------------------------------------------------------------
+// Per-element atomic memcpy implementations
+
+// scheduler.rs and trace.rs include this module themselves so that it uses
+// their atomic types.
+#[cfg(not(any(portable_atomic_scheduler_hook, portable_atomic_trace)))]
+pub(crate) mod memcpy;
+
 // -----------------------------------------------------------------------------
 
 // has CAS | (has core atomic & !(avr | msp430 | critical section)) => core atomic
@@ -255,6 +295,13 @@ items! {
         not(any(target_pointer_width = "16", target_pointer_width = "32")),
         feature = "fallback",
     ))]
+    #[cfg(not(all(
+        target_arch = "arm",
+        not(any(miri, portable_atomic_sanitize_thread)),
+        any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+        any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+        not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+    )))]
     pub(crate) use self::interrupt::{AtomicI64, AtomicU64};
     #[cfg(feature = "fallback")]
     pub(crate) use self::interrupt::{AtomicI128, AtomicU128};
@@ -262,6 +309,7 @@ items! {
 
 // no core (64-bit | 128-bit) atomic & has CAS => use lock-base fallback
 #[cfg(feature = "fallback")]
+#[cfg(not(feature = "require-address-free"))]
 #[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(not(portable_atomic_no_atomic_cas)))]
 #[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(target_has_atomic = "ptr"))]
 items! {
@@ -274,6 +322,19 @@ items! {
             not(any(target_feature = "v6", portable_atomic_target_feature = "v6")),
             not(portable_atomic_no_outline_atomics),
         ),
+        all(
+            target_arch = "arm",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+            not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+        ),
+        all(
+            target_arch = "mips32r6",
+            portable_atomic_unsafe_assume_llwp_scwp,
+            portable_atomic_unstable_asm_experimental_arch,
+            not(any(miri, portable_atomic_sanitize_thread)),
+        ),
         all(
             target_arch = "riscv32",
             not(any(miri, portable_atomic_sanitize_thread)),
@@ -289,6 +350,12 @@ items! {
                 ),
             ),
         ),
+        all(
+            target_arch = "x86",
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+            not(portable_atomic_no_outline_atomics),
+        ),
     )))]
     #[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
     #[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
@@ -363,6 +430,16 @@ items! {
                 ),
             ),
         ),
+        all(
+            target_arch = "loongarch64",
+            not(portable_atomic_no_asm),
+            not(any(miri, portable_atomic_sanitize_thread)),
+            any(
+                target_feature = "scq",
+                portable_atomic_target_feature = "scq",
+                all(feature = "fallback", not(portable_atomic_no_outline_atomics)),
+            ),
+        ),
         all(target_arch = "s390x", not(portable_atomic_no_asm)),
     )))]
     pub(crate) use self::fallback::{AtomicI128, AtomicU128};
@@ -382,6 +459,27 @@ items! {
 #[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
 #[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
 pub(crate) use self::atomic64::arm_linux::{AtomicI64, AtomicU64};
+// Armv7-A/R without 64-bit atomics in core
+#[cfg(all(
+    target_arch = "arm",
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+    any(target_feature = "v7", portable_atomic_target_feature = "v7"),
+    not(any(target_feature = "mclass", portable_atomic_target_feature = "mclass")),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(crate) use self::atomic64::arm::{AtomicI64, AtomicU64};
+// MIPS32r6 with llwp/scwp
+#[cfg(all(
+    target_arch = "mips32r6",
+    portable_atomic_unsafe_assume_llwp_scwp,
+    portable_atomic_unstable_asm_experimental_arch,
+    not(any(miri, portable_atomic_sanitize_thread)),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(crate) use self::atomic64::mips32r6::{AtomicI64, AtomicU64};
 // riscv32 & (zacas | outline-atomics)
 #[cfg(all(
     target_arch = "riscv32",
@@ -399,6 +497,17 @@ pub(crate) use self::atomic64::arm_linux::{AtomicI64, AtomicU64};
     ),
 ))]
 pub(crate) use self::atomic64::riscv32::{AtomicI64, AtomicU64};
+// x86 without 64-bit atomics in core (e.g., i486-class)
+#[cfg(feature = "fallback")]
+#[cfg(all(
+    target_arch = "x86",
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(not(portable_atomic_no_asm), portable_atomic_unstable_asm),
+    not(portable_atomic_no_outline_atomics),
+))]
+#[cfg_attr(portable_atomic_no_cfg_target_has_atomic, cfg(portable_atomic_no_atomic_64))]
+#[cfg_attr(not(portable_atomic_no_cfg_target_has_atomic), cfg(not(target_has_atomic = "64")))]
+pub(crate) use self::atomic64::x86::{AtomicI64, AtomicU64};
 
 // 128-bit atomics (platform-specific)
 // AArch64
@@ -474,6 +583,18 @@ pub(crate) use self::atomic128::riscv64::{AtomicI128, AtomicU128};
     ),
 ))]
 pub(crate) use self::atomic128::powerpc64::{AtomicI128, AtomicU128};
+// loongarch64 & (scq | outline-atomics)
+#[cfg(all(
+    target_arch = "loongarch64",
+    not(portable_atomic_no_asm),
+    not(any(miri, portable_atomic_sanitize_thread)),
+    any(
+        target_feature = "scq",
+        portable_atomic_target_feature = "scq",
+        all(feature = "fallback", not(portable_atomic_no_outline_atomics)),
+    ),
+))]
+pub(crate) use self::atomic128::loongarch64::{AtomicI128, AtomicU128};
 // s390x
 #[cfg(all(target_arch = "s390x", not(portable_atomic_no_asm)))]
 pub(crate) use self::atomic128::s390x::{AtomicI128, AtomicU128};
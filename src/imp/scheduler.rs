@@ -0,0 +1,194 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Atomic types that yield to a user-provided scheduler hook before each atomic
+operation. This is used instead of other implementations when
+`--cfg portable_atomic_scheduler_hook` is set.
+
+The atomic types here wrap the implementations that would otherwise be used
+(`mod.rs` is included as `inner`), and call the hook when dereferencing to the
+inner type. The public atomic types perform exactly one operation on the inner
+type per atomic operation, so the hook is called once per operation, and
+operations that are implemented on top of other atomic types (atomic floats,
+AtomicBool) are also covered. memcpy.rs is included here so that slice
+operations call the hook for each element or usize chunk.
+
+The hook is not called recursively: atomic operations performed by the hook
+itself (e.g., by a scheduler that uses portable-atomic) do not call the hook.
+*/
+
+use core::{
+    cell::Cell,
+    mem, ops, ptr,
+    sync::atomic::{self, Ordering},
+};
+
+#[path = "mod.rs"]
+mod inner;
+
+#[cfg(feature = "float")]
+pub(crate) use self::inner::float;
+#[cfg(target_arch = "msp430")]
+pub(crate) use self::inner::msp430;
+#[cfg(feature = "fallback-stats")]
+pub(crate) use self::inner::{fallback_lock_stats, fallback_reset_lock_stats};
+
+// Included here (instead of re-exporting inner::memcpy) so that the chunks
+// are loaded/stored by the AtomicUsize below, which calls the hook.
+#[path = "memcpy.rs"]
+pub(crate) mod memcpy;
+
+// The current hook as a pointer, or null if no hook is set. This uses
+// core::sync::atomic directly so that reading the hook does not call the hook.
+static HOOK: atomic::AtomicPtr<()> = atomic::AtomicPtr::new(ptr::null_mut());
+
+std::thread_local! {
+    static IN_HOOK: Cell<bool> = Cell::new(false);
+}
+
+fn from_raw(hook: *mut ()) -> Option<fn()> {
+    if hook.is_null() {
+        None
+    } else {
+        // SAFETY: non-null pointers stored in HOOK are always created from `fn()`
+        // in set_scheduler_hook.
+        Some(unsafe { mem::transmute::<*mut (), fn()>(hook) })
+    }
+}
+
+/// Sets the function that is called before each atomic operation, and returns
+/// the previously set function.
+///
+/// This is only available when `--cfg portable_atomic_scheduler_hook` is set.
+/// When a hook is set, every atomic operation on portable-atomic's atomic types
+/// (including loads, stores, and [`fence`](crate::fence)) calls it first, on
+/// the thread that performs the operation. This allows a randomized or
+/// deterministic scheduler (e.g., [shuttle](https://github.com/awslabs/shuttle))
+/// to switch threads at every atomic operation, so that interleavings can be
+/// explored and replayed from a seed. Pass `None` to remove the hook.
+///
+/// The hook is shared by all threads. Atomic operations performed inside the
+/// hook do not call the hook again.
+///
+/// See also [the crate-level documentation](crate#optional-cfg-scheduler-hook).
+///
+/// # Examples
+///
+/// ```ignore
+/// fn yield_to_shuttle() {
+///     shuttle::thread::yield_now();
+/// }
+///
+/// portable_atomic::set_scheduler_hook(Some(yield_to_shuttle));
+/// shuttle::check_random(|| { /* ... */ }, 1000);
+/// portable_atomic::set_scheduler_hook(None);
+/// ```
+#[inline]
+pub fn set_scheduler_hook(hook: Option<fn()>) -> Option<fn()> {
+    let hook = match hook {
+        Some(hook) => hook as *mut (),
+        None => ptr::null_mut(),
+    };
+    from_raw(HOOK.swap(hook, Ordering::AcqRel))
+}
+
+#[inline]
+fn call_hook() {
+    struct Reset<'a>(&'a Cell<bool>);
+    impl Drop for Reset<'_> {
+        fn drop(&mut self) {
+            self.0.set(false);
+        }
+    }
+
+    let hook = match from_raw(HOOK.load(Ordering::Acquire)) {
+        Some(hook) => hook,
+        None => return,
+    };
+    // try_with fails only while thread-local variables are being destroyed.
+    let _ = IN_HOOK.try_with(|in_hook| {
+        if !in_hook.replace(true) {
+            let _reset = Reset(in_hook);
+            hook();
+        }
+    });
+}
+
+/// An atomic fence.
+///
+/// This calls the scheduler hook before performing the fence.
+///
+/// # Panics
+///
+/// Panics if `order` is [`Relaxed`](Ordering::Relaxed).
+#[inline]
+#[cfg_attr(all(debug_assertions, not(portable_atomic_no_track_caller)), track_caller)]
+pub fn fence(order: Ordering) {
+    call_hook();
+    atomic::fence(order);
+}
+
+macro_rules! atomic {
+    ($([$($generics:tt)*])? $atomic_type:ident $(<$t:ident>)?, $value_type:ty) => {
+        #[repr(transparent)]
+        pub(crate) struct $atomic_type $(<$($generics)*>)? {
+            inner: inner::$atomic_type $(<$t>)?,
+        }
+
+        impl $(<$($generics)*>)? $atomic_type $(<$t>)? {
+            #[inline]
+            pub(crate) const fn new(v: $value_type) -> Self {
+                Self { inner: inner::$atomic_type::new(v) }
+            }
+
+            #[inline]
+            pub(crate) fn is_lock_free() -> bool {
+                <inner::$atomic_type $(<$t>)?>::is_lock_free()
+            }
+            pub(crate) const IS_ALWAYS_LOCK_FREE: bool =
+                <inner::$atomic_type $(<$t>)?>::IS_ALWAYS_LOCK_FREE;
+
+            // This is not an atomic operation, and needs to be const fn.
+            #[inline]
+            pub(crate) const fn as_ptr(&self) -> *mut $value_type {
+                self.inner.as_ptr()
+            }
+        }
+
+        // All atomic operations go through this.
+        impl $(<$($generics)*>)? ops::Deref for $atomic_type $(<$t>)? {
+            type Target = inner::$atomic_type $(<$t>)?;
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                call_hook();
+                &self.inner
+            }
+        }
+    };
+}
+
+crate::cfg_has_atomic_ptr! {
+    atomic!([T] AtomicPtr<T>, *mut T);
+    atomic!(AtomicIsize, isize);
+    atomic!(AtomicUsize, usize);
+}
+cfg_has_atomic_8! {
+    atomic!(AtomicI8, i8);
+    atomic!(AtomicU8, u8);
+}
+cfg_has_atomic_16! {
+    atomic!(AtomicI16, i16);
+    atomic!(AtomicU16, u16);
+}
+cfg_has_atomic_32! {
+    atomic!(AtomicI32, i32);
+    atomic!(AtomicU32, u32);
+}
+cfg_has_atomic_64! {
+    atomic!(AtomicI64, i64);
+    atomic!(AtomicU64, u64);
+}
+cfg_has_atomic_128! {
+    atomic!(AtomicI128, i128);
+    atomic!(AtomicU128, u128);
+}
@@ -0,0 +1,268 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+/*
+Atomic types that record each atomic operation in a per-thread buffer. This is
+used instead of other implementations when `--cfg portable_atomic_trace` is set.
+
+The atomic types here wrap the implementations that would otherwise be used
+(`mod.rs` is included as `inner`), so every backend is covered. Operations on
+AtomicBool, AtomicPtr's bit and pointer arithmetic operations, and atomic floats
+are implemented on top of the atomic integer types, so they are recorded as
+operations on them. memcpy.rs is included here so that slice operations are
+recorded as operations on each element or usize chunk.
+
+Operations that do not return the previous value (e.g., add, bit_set) are
+performed using the corresponding fetch_* operation so that the previous value
+can be recorded. Operations that are only available without CAS (e.g., swap on
+targets with only AMOs) are not recorded and are forwarded to the inner type.
+
+See src/trace.rs for the buffer.
+*/
+
+use core::{ops, sync::atomic::Ordering};
+
+use crate::trace::{record, Operation};
+
+// Operations that do not return the previous value are not used.
+#[allow(dead_code)]
+#[path = "mod.rs"]
+mod inner;
+
+#[cfg(feature = "float")]
+pub(crate) use self::inner::float;
+#[cfg(target_arch = "msp430")]
+pub(crate) use self::inner::msp430;
+#[cfg(feature = "fallback-stats")]
+pub(crate) use self::inner::{fallback_lock_stats, fallback_reset_lock_stats};
+
+// Included here (instead of re-exporting inner::memcpy) so that the chunks
+// are loaded/stored by the AtomicUsize below, which records them.
+#[path = "memcpy.rs"]
+pub(crate) mod memcpy;
+
+macro_rules! atomic_common {
+    ($([$($generics:tt)*])? $atomic_type:ident $(<$t:ident>)?, $value_type:ty) => {
+        #[repr(transparent)]
+        pub(crate) struct $atomic_type $(<$($generics)*>)? {
+            inner: inner::$atomic_type $(<$t>)?,
+        }
+
+        impl $(<$($generics)*>)? $atomic_type $(<$t>)? {
+            #[inline]
+            pub(crate) const fn new(v: $value_type) -> Self {
+                Self { inner: inner::$atomic_type::new(v) }
+            }
+
+            #[inline]
+            pub(crate) fn is_lock_free() -> bool {
+                <inner::$atomic_type $(<$t>)?>::is_lock_free()
+            }
+            pub(crate) const IS_ALWAYS_LOCK_FREE: bool =
+                <inner::$atomic_type $(<$t>)?>::IS_ALWAYS_LOCK_FREE;
+
+            #[inline]
+            pub(crate) const fn as_ptr(&self) -> *mut $value_type {
+                self.inner.as_ptr()
+            }
+
+            #[inline]
+            fn record(
+                &self,
+                operation: Operation,
+                order: Ordering,
+                old: Option<$value_type>,
+                new: Option<$value_type>,
+            ) {
+                record(
+                    self.as_ptr() as usize,
+                    operation,
+                    order,
+                    old.map(Self::to_bits),
+                    new.map(Self::to_bits),
+                );
+            }
+
+            #[inline]
+            pub(crate) fn load(&self, order: Ordering) -> $value_type {
+                let val = self.inner.load(order);
+                self.record(Operation::Load, order, Some(val), None);
+                val
+            }
+
+            #[inline]
+            pub(crate) fn store(&self, val: $value_type, order: Ordering) {
+                self.inner.store(val, order);
+                self.record(Operation::Store, order, None, Some(val));
+            }
+        }
+
+        cfg_has_atomic_cas! {
+        impl $(<$($generics)*>)? $atomic_type $(<$t>)? {
+            #[inline]
+            pub(crate) fn swap(&self, val: $value_type, order: Ordering) -> $value_type {
+                let prev = self.inner.swap(val, order);
+                self.record(Operation::Swap, order, Some(prev), Some(val));
+                prev
+            }
+
+            #[inline]
+            pub(crate) fn compare_exchange(
+                &self,
+                current: $value_type,
+                new: $value_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value_type, $value_type> {
+                let res = self.inner.compare_exchange(current, new, success, failure);
+                self.record_cas(Operation::CompareExchange, new, success, failure, res);
+                res
+            }
+
+            #[inline]
+            pub(crate) fn compare_exchange_weak(
+                &self,
+                current: $value_type,
+                new: $value_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$value_type, $value_type> {
+                let res = self.inner.compare_exchange_weak(current, new, success, failure);
+                self.record_cas(Operation::CompareExchangeWeak, new, success, failure, res);
+                res
+            }
+
+            #[inline]
+            fn record_cas(
+                &self,
+                operation: Operation,
+                new: $value_type,
+                success: Ordering,
+                failure: Ordering,
+                res: Result<$value_type, $value_type>,
+            ) {
+                match res {
+                    Ok(prev) => self.record(operation, success, Some(prev), Some(new)),
+                    Err(prev) => self.record(operation, failure, Some(prev), None),
+                }
+            }
+        }
+        } // cfg_has_atomic_cas!
+
+        // Operations that are not recorded on this target.
+        impl $(<$($generics)*>)? ops::Deref for $atomic_type $(<$t>)? {
+            type Target = inner::$atomic_type $(<$t>)?;
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.inner
+            }
+        }
+    };
+}
+
+macro_rules! atomic_int {
+    ($atomic_type:ident, $int_type:ident, $uint_type:ident) => {
+        atomic_common!($atomic_type, $int_type);
+        impl $atomic_type {
+            #[inline]
+            #[allow(clippy::cast_sign_loss)]
+            fn to_bits(v: $int_type) -> u128 {
+                v as $uint_type as u128
+            }
+        }
+        cfg_has_atomic_cas! {
+        impl $atomic_type {
+            fetch_op!($int_type, fetch_add, add, FetchAdd, |prev, val| prev.wrapping_add(val));
+            fetch_op!($int_type, fetch_sub, sub, FetchSub, |prev, val| prev.wrapping_sub(val));
+            fetch_op!($int_type, fetch_and, and, FetchAnd, |prev, val| prev & val);
+            fetch_op!($int_type, fetch_nand, FetchNand, |prev, val| !(prev & val));
+            fetch_op!($int_type, fetch_or, or, FetchOr, |prev, val| prev | val);
+            fetch_op!($int_type, fetch_xor, xor, FetchXor, |prev, val| prev ^ val);
+            fetch_op!($int_type, fetch_max, FetchMax, |prev, val| core::cmp::max(prev, val));
+            fetch_op!($int_type, fetch_min, FetchMin, |prev, val| core::cmp::min(prev, val));
+            fetch_op!($int_type, fetch_not, not, FetchNot, |prev| !prev);
+            fetch_op!($int_type, fetch_neg, neg, FetchNeg, |prev| prev.wrapping_neg());
+
+            #[inline]
+            pub(crate) fn bit_set(&self, bit: u32, order: Ordering) -> bool {
+                let mask = <$int_type>::wrapping_shl(1, bit);
+                self.fetch_or(mask, order) & mask != 0
+            }
+            #[inline]
+            pub(crate) fn bit_clear(&self, bit: u32, order: Ordering) -> bool {
+                let mask = <$int_type>::wrapping_shl(1, bit);
+                self.fetch_and(!mask, order) & mask != 0
+            }
+            #[inline]
+            pub(crate) fn bit_toggle(&self, bit: u32, order: Ordering) -> bool {
+                let mask = <$int_type>::wrapping_shl(1, bit);
+                self.fetch_xor(mask, order) & mask != 0
+            }
+        }
+        } // cfg_has_atomic_cas!
+    };
+}
+
+macro_rules! fetch_op {
+    (
+        $int_type:ident, $fetch_op:ident, $op:ident, $operation:ident,
+        |$prev:ident, $val:ident| $new:expr
+    ) => {
+        fetch_op!($int_type, $fetch_op, $operation, |$prev, $val| $new);
+        #[inline]
+        pub(crate) fn $op(&self, val: $int_type, order: Ordering) {
+            self.$fetch_op(val, order);
+        }
+    };
+    ($int_type:ident, $fetch_op:ident, $operation:ident, |$prev:ident, $val:ident| $new:expr) => {
+        #[inline]
+        pub(crate) fn $fetch_op(&self, $val: $int_type, order: Ordering) -> $int_type {
+            let $prev = self.inner.$fetch_op($val, order);
+            self.record(Operation::$operation, order, Some($prev), Some($new));
+            $prev
+        }
+    };
+    ($int_type:ident, $fetch_op:ident, $op:ident, $operation:ident, |$prev:ident| $new:expr) => {
+        #[inline]
+        pub(crate) fn $fetch_op(&self, order: Ordering) -> $int_type {
+            let $prev = self.inner.$fetch_op(order);
+            self.record(Operation::$operation, order, Some($prev), Some($new));
+            $prev
+        }
+        #[inline]
+        pub(crate) fn $op(&self, order: Ordering) {
+            self.$fetch_op(order);
+        }
+    };
+}
+
+crate::cfg_has_atomic_ptr! {
+    atomic_common!([T] AtomicPtr<T>, *mut T);
+    impl<T> AtomicPtr<T> {
+        #[inline]
+        fn to_bits(v: *mut T) -> u128 {
+            v as usize as u128
+        }
+    }
+    atomic_int!(AtomicIsize, isize, usize);
+    atomic_int!(AtomicUsize, usize, usize);
+}
+cfg_has_atomic_8! {
+    atomic_int!(AtomicI8, i8, u8);
+    atomic_int!(AtomicU8, u8, u8);
+}
+cfg_has_atomic_16! {
+    atomic_int!(AtomicI16, i16, u16);
+    atomic_int!(AtomicU16, u16, u16);
+}
+cfg_has_atomic_32! {
+    atomic_int!(AtomicI32, i32, u32);
+    atomic_int!(AtomicU32, u32, u32);
+}
+cfg_has_atomic_64! {
+    atomic_int!(AtomicI64, i64, u64);
+    atomic_int!(AtomicU64, u64, u64);
+}
+cfg_has_atomic_128! {
+    atomic_int!(AtomicI128, i128, u128);
+    atomic_int!(AtomicU128, u128, u128);
+}
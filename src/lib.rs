@@ -46,7 +46,7 @@ portable-atomic = { version = "1.3", default-features = false, features = ["requ
 
 ## 128-bit atomics support
 
-Native 128-bit atomic operations are available on x86_64 (Rust 1.59+), AArch64 (Rust 1.59+), riscv64 (Rust 1.59+), Arm64EC (Rust 1.84+), s390x (Rust 1.84+), and powerpc64 (nightly only), otherwise the fallback implementation is used.
+Native 128-bit atomic operations are available on x86_64 (Rust 1.59+), AArch64 (Rust 1.59+), riscv64 (Rust 1.59+), Arm64EC (Rust 1.84+), s390x (Rust 1.84+), LoongArch64 (Rust 1.72+), and powerpc64 (nightly only), otherwise the fallback implementation is used.
 
 On x86_64, even if `cmpxchg16b` is not available at compile-time (note: `cmpxchg16b` target feature is enabled by default only on Apple and Windows (except Windows 7) targets), run-time detection checks whether `cmpxchg16b` is available. If `cmpxchg16b` is not available at either compile-time or run-time detection, the fallback implementation is used. See also [`portable_atomic_no_outline_atomics`](#optional-cfg-no-outline-atomics) cfg.
 
@@ -61,6 +61,15 @@ See the [`atomic128` module's readme](https://github.com/taiki-e/portable-atomic
 
   Disabling this allows only atomic types for which the platform natively supports atomic operations.
 
+- <a name="optional-features-fallback-stats"></a>**`fallback-stats`**<br>
+  Provide `fallback_stats` module to get contention statistics of the locks used by the fallback implementation.
+
+  For each lock slot of the fallback implementation, the number of optimistic read failures, write lock acquisitions, and spin iterations while waiting for the lock are counted. This helps to decide whether the contention on atomic types that are not lock-free (e.g., 128-bit atomics on targets without native 128-bit CAS) matters.
+
+  Note:
+  - This feature implies the `fallback` feature.
+  - Counting adds a few atomic operations to each operation that acquires a lock.
+
 - <a name="optional-features-float"></a>**`float`**<br>
   Provide `AtomicF{32,64}`.
 
@@ -72,12 +81,30 @@ See the [`atomic128` module's readme](https://github.com/taiki-e/portable-atomic
 - <a name="optional-features-require-cas"></a>**`require-cas`**<br>
   Emit compile error if atomic CAS is not available. See [Usage](#usage) section and [#100](https://github.com/taiki-e/portable-atomic/pull/100) for more.
 
+- <a name="optional-features-require-address-free"></a>**`require-address-free`**<br>
+  Do not provide atomic types that may rely on process-local locks.
+
+  The fallback implementation and the implementation based on the [`critical-section` feature](#optional-features-critical-section) use locks whose state is local to the current process, so operations on such atomic types are not atomic with respect to other processes that access the same memory (e.g., shared memory mapped into multiple processes). When this feature is enabled:
+  - Atomic types that would be provided by the fallback implementation are not provided, so using them results in a compile error.
+  - Run-time detection on targets where it may fall back to the fallback implementation is disabled (see also [`portable_atomic_no_outline_atomics`](#optional-cfg-no-outline-atomics) cfg).
+  - Enabling the `critical-section` feature results in a compile error.
+
+  `is_address_free` method of each atomic type can be used to check whether operations on that type are address-free at run-time.
+
+  Note that 64-bit atomics on ESP-IDF targets are provided by the standard library and are not address-free, and this feature does not prevent using them.
+
 - <a name="optional-features-serde"></a>**`serde`**<br>
   Implement `serde::{Serialize,Deserialize}` for atomic types.
 
   Note:
   - The MSRV when this feature is enabled depends on the MSRV of [serde].
 
+- <a name="optional-features-derive"></a>**`derive`**<br>
+  Provide `#[derive(AtomicRepr)]` for field-less enums with `#[repr(u8)]`, `#[repr(u16)]`, or `#[repr(u32)]`, to store them in `AtomicEnum`.
+
+  Note:
+  - The MSRV when this feature is enabled is Rust 1.61 that [syn] 2 requires.
+
 - <a name="optional-features-critical-section"></a>**`critical-section`**<br>
   When this feature is enabled, this crate uses [critical-section] to provide atomic CAS for targets where
   it is not natively available. When enabling it, you should provide a suitable critical section implementation
@@ -158,13 +185,65 @@ RUSTFLAGS="--cfg portable_atomic_no_outline_atomics" cargo ...
 
   Originally, we were providing these as cfgs instead of features, but based on a strong request from the embedded ecosystem, we have agreed to provide them as features as well. See [#94](https://github.com/taiki-e/portable-atomic/pull/94) for more.
 
+- <a name="optional-cfg-unsafe-assume-vmovdqa-atomic"></a>**`--cfg portable_atomic_unsafe_assume_vmovdqa_atomic`**<br>
+  Assume that the program only runs on Intel, AMD, or Zhaoxin CPUs, on which `vmovdqa` with AVX is atomic.
+
+  When this cfg is set and the `avx` target feature is enabled at compile-time (e.g., `-C target-feature=+avx` or `-C target-cpu=x86-64-v3`), 128-bit atomic load/store on x86_64 use `vmovdqa` without run-time CPU feature detection. This is useful for static binaries and environments where run-time detection is not available (e.g., SGX).
+
+  Note:
+  - Enabling this cfg for programs that may run on CPUs of other vendors is **unsound**.
+  - This cfg only affects x86_64.
+
+- <a name="optional-cfg-unsafe-assume-llwp-scwp"></a>**`--cfg portable_atomic_unsafe_assume_llwp_scwp`**<br>
+  Assume that the program only runs on MIPS32r6 CPUs that implement the optional `llwp`/`scwp` instructions.
+
+  When this cfg is set, 64-bit atomics on MIPS32r6 use `llwp`/`scwp` instead of the fallback implementation. Whether these instructions are implemented is not exposed to user space, so run-time detection is not possible.
+
+  Note:
+  - Enabling this cfg for programs that may run on CPUs that do not implement `llwp`/`scwp` is **unsound** (the program will be killed by SIGILL).
+  - This cfg only affects MIPS32r6, and requires nightly due to `#![feature(asm_experimental_arch)]`.
+
+- <a name="optional-cfg-loom"></a>**`--cfg portable_atomic_loom`**<br>
+  Use [loom](https://github.com/tokio-rs/loom) to model-check code that uses portable-atomic's atomic types.
+
+  When this cfg is set, all atomic types, `fence`, and `hint::spin_loop` are implemented on top of loom, so code that uses portable-atomic can be tested with `loom::model` without changing its imports. Operations that loom does not provide (e.g., `fetch_neg`, `bit_set`) are emulated using CAS loops, and 128-bit atomic types are emulated using loom's `Mutex`.
+
+  Note:
+  - This cfg adds a dependency on `loom` and requires the standard library.
+  - `new` of atomic types is not `const fn` when this cfg is set, and atomic values must be created inside `loom::model`. Use `loom::lazy_static!` for atomic types in statics.
+  - `from_ptr`, `from_mut`, `get_mut_slice`, and `from_mut_slice` are not available when this cfg is set.
+  - A value written via `get_mut` or `as_ptr` is passed to loom by the next atomic operation on the same atomic value, as if that operation's thread had written it.
+  - The ordering argument of operations on 128-bit atomic types is ignored, and these operations are always modeled as `SeqCst`.
+
+- <a name="optional-cfg-scheduler-hook"></a>**`--cfg portable_atomic_scheduler_hook`**<br>
+  Call a user-provided hook before each atomic operation, for testing with randomized or deterministic schedulers such as [shuttle](https://github.com/awslabs/shuttle).
+
+  When this cfg is set, `portable_atomic::set_scheduler_hook` is available, and every atomic operation on portable-atomic's atomic types (including loads, stores, and `fence`) calls the function set by it before performing the operation. The scheduler can switch threads in the hook, which allows interleavings to be explored and replayed from a seed.
+
+  Note:
+  - This cfg requires the standard library.
+  - This cfg may not be used together with `portable_atomic_loom` cfg.
+  - Atomic operations performed inside the hook do not call the hook again.
+  - Slice operations (e.g., `load_slice`) call the hook for each element, or for each `usize`-sized chunk in the `*_chunked` variants.
+
+- <a name="optional-cfg-trace"></a>**`--cfg portable_atomic_trace`**<br>
+  Record atomic operations for debugging.
+
+  When this cfg is set, every atomic operation on portable-atomic's atomic types is recorded in a per-thread ring buffer that holds the last 1024 operations of the thread, with the address, the kind of the operation, the memory ordering, and the values read and written. `portable_atomic::trace::dump` returns the operations recorded by the current thread, and `portable_atomic::trace::dump_all` returns the operations recorded by all threads, tagged with the thread ID. This works with all implementations, including the fallback implementation. When this cfg is not set, there is no overhead.
+
+  Note:
+  - This cfg requires the standard library.
+  - This cfg may not be used together with `portable_atomic_loom` or `portable_atomic_scheduler_hook` cfg.
+  - Operations that do not return the previous value (e.g., `add`, `bit_set`) are performed using the corresponding `fetch_*` operation so that the previous value can be recorded.
+  - Slice operations (e.g., `load_slice`) are recorded as an operation on each element, and the `*_chunked` variants record the middle of the slice as operations on `usize`-sized chunks.
+
 - <a name="optional-cfg-no-outline-atomics"></a>**`--cfg portable_atomic_no_outline_atomics`**<br>
   Disable dynamic dispatching by run-time CPU feature detection.
 
   If dynamic dispatching by run-time CPU feature detection is enabled, it allows maintaining support for older CPUs while using features that are not supported on older CPUs, such as CMPXCHG16B (x86_64) and FEAT_LSE/FEAT_LSE2 (AArch64).
 
   Note:
-  - Dynamic detection is currently only supported in x86_64, AArch64, RISC-V (disabled by default on RISC-V), Arm64EC, and powerpc64, otherwise it works the same as when this cfg is set.
+  - Dynamic detection is currently only supported in x86_64, x86, AArch64, Arm, RISC-V (disabled by default on RISC-V), Arm64EC, powerpc64, and LoongArch64, otherwise it works the same as when this cfg is set.
   - If the required target features are enabled at compile-time, the atomic operations are inlined.
   - This is compatible with no-std (as with all features except `std`).
   - On some targets, run-time detection is disabled by default mainly for incomplete build environments, and can be enabled by `--cfg portable_atomic_outline_atomics`. (When both cfg are enabled, `*_no_*` cfg is preferred.)
@@ -183,6 +262,7 @@ RUSTFLAGS="--cfg portable_atomic_no_outline_atomics" cargo ...
 [critical-section]: https://github.com/rust-embedded/critical-section
 [rust-lang/rust#100650]: https://github.com/rust-lang/rust/issues/100650
 [serde]: https://github.com/serde-rs/serde
+[syn]: https://github.com/dtolnay/syn
 
 <!-- tidy:crate-doc:end -->
 */
@@ -215,7 +295,7 @@ RUSTFLAGS="--cfg portable_atomic_no_outline_atomics" cargo ...
 #![allow(clippy::inline_always, clippy::used_underscore_items)]
 // asm_experimental_arch
 // AVR, MSP430, and Xtensa are tier 3 platforms and require nightly anyway.
-// On tier 2 platforms (powerpc64), we use cfg set by build script to
+// On tier 2 platforms (powerpc64) and MIPS32r6, we use cfg set by build script to
 // determine whether this feature is available or not.
 #![cfg_attr(
     all(
@@ -225,6 +305,11 @@ RUSTFLAGS="--cfg portable_atomic_no_outline_atomics" cargo ...
             target_arch = "msp430",
             all(target_arch = "xtensa", portable_atomic_unsafe_assume_single_core),
             all(target_arch = "powerpc64", portable_atomic_unstable_asm_experimental_arch),
+            all(
+                target_arch = "mips32r6",
+                portable_atomic_unsafe_assume_llwp_scwp,
+                portable_atomic_unstable_asm_experimental_arch,
+            ),
         ),
     ),
     feature(asm_experimental_arch)
@@ -368,9 +453,11 @@ compile_error!(
     target_arch = "aarch64",
     target_arch = "arm",
     target_arch = "arm64ec",
+    target_arch = "loongarch64",
     target_arch = "powerpc64",
     target_arch = "riscv32",
     target_arch = "riscv64",
+    target_arch = "x86",
     target_arch = "x86_64",
 )))]
 compile_error!("`portable_atomic_no_outline_atomics` cfg does not compatible with this target");
@@ -382,6 +469,16 @@ compile_error!("`portable_atomic_no_outline_atomics` cfg does not compatible wit
     target_arch = "riscv64",
 )))]
 compile_error!("`portable_atomic_outline_atomics` cfg does not compatible with this target");
+#[cfg(portable_atomic_unsafe_assume_vmovdqa_atomic)]
+#[cfg(not(target_arch = "x86_64"))]
+compile_error!(
+    "`portable_atomic_unsafe_assume_vmovdqa_atomic` cfg does not compatible with this target"
+);
+#[cfg(portable_atomic_unsafe_assume_llwp_scwp)]
+#[cfg(not(target_arch = "mips32r6"))]
+compile_error!(
+    "`portable_atomic_unsafe_assume_llwp_scwp` cfg does not compatible with this target"
+);
 
 #[cfg(portable_atomic_disable_fiq)]
 #[cfg(not(all(
@@ -419,6 +516,11 @@ compile_error!(
     "you may not enable `critical-section` feature and `portable_atomic_unsafe_assume_single_core` cfg (`unsafe-assume-single-core` feature) at the same time"
 );
 
+#[cfg(all(feature = "require-address-free", feature = "critical-section"))]
+compile_error!(
+    "you may not enable `require-address-free` feature and `critical-section` feature at the same time"
+);
+
 #[cfg(feature = "require-cas")]
 #[cfg_attr(
     portable_atomic_no_cfg_target_has_atomic,
@@ -446,7 +548,23 @@ compile_error!(
     see <https://docs.rs/portable-atomic/latest/portable_atomic/#optional-features> for more."
 );
 
-#[cfg(any(test, feature = "std"))]
+#[cfg(all(portable_atomic_loom, portable_atomic_scheduler_hook))]
+compile_error!(
+    "you may not enable `portable_atomic_loom` cfg and `portable_atomic_scheduler_hook` cfg at the same time"
+);
+
+#[cfg(all(portable_atomic_trace, any(portable_atomic_loom, portable_atomic_scheduler_hook)))]
+compile_error!(
+    "you may not enable `portable_atomic_trace` cfg and `portable_atomic_loom` or `portable_atomic_scheduler_hook` cfg at the same time"
+);
+
+#[cfg(any(
+    test,
+    feature = "std",
+    portable_atomic_loom,
+    portable_atomic_scheduler_hook,
+    portable_atomic_trace,
+))]
 extern crate std;
 
 #[macro_use]
@@ -472,12 +590,72 @@ pub use core::sync::atomic::Ordering;
 
 #[doc(no_inline)]
 // LLVM doesn't support fence/compiler_fence for MSP430.
-#[cfg(not(target_arch = "msp430"))]
+#[cfg(not(any(
+    target_arch = "msp430",
+    portable_atomic_loom,
+    portable_atomic_scheduler_hook,
+)))]
 pub use core::sync::atomic::{compiler_fence, fence};
 #[cfg(target_arch = "msp430")]
 pub use imp::msp430::{compiler_fence, fence};
-
+// loom does not model compiler_fence.
+#[cfg(portable_atomic_loom)]
+pub use {core::sync::atomic::compiler_fence, loom::sync::atomic::fence};
+// compiler_fence is not an atomic operation, so it does not call the hook.
+#[cfg(portable_atomic_scheduler_hook)]
+pub use {core::sync::atomic::compiler_fence, imp::fence};
+
+#[cfg(not(any(portable_atomic_loom, portable_atomic_scheduler_hook, portable_atomic_trace)))]
+mod imp;
+#[cfg(portable_atomic_loom)]
+#[path = "imp/loom.rs"]
+mod imp;
+#[cfg(portable_atomic_scheduler_hook)]
+#[path = "imp/scheduler.rs"]
 mod imp;
+#[cfg(portable_atomic_scheduler_hook)]
+pub use imp::set_scheduler_hook;
+#[cfg(portable_atomic_trace)]
+#[path = "imp/trace.rs"]
+mod imp;
+
+#[cfg(portable_atomic_trace)]
+pub mod trace;
+
+#[cfg(feature = "fallback-stats")]
+#[cfg_attr(docsrs, doc(cfg(feature = "fallback-stats")))]
+pub mod fallback_stats;
+
+mod backoff;
+pub use self::backoff::{Backoff, FetchUpdateError};
+
+mod cache_padded;
+pub use self::cache_padded::CachePadded;
+
+cfg_has_atomic_ptr! {
+mod non_null;
+pub use self::non_null::{AtomicNonNull, AtomicOptionNonNull};
+} // cfg_has_atomic_ptr!
+
+mod non_zero;
+pub use self::non_zero::*;
+
+mod atomic_enum;
+pub use self::atomic_enum::{AtomicEnum, AtomicRepr, AtomicReprInt};
+#[cfg(feature = "derive")]
+#[cfg_attr(docsrs, doc(cfg(feature = "derive")))]
+pub use portable_atomic_derive::AtomicRepr;
+
+cfg_has_atomic_64! {
+mod time;
+pub use self::time::AtomicDuration;
+#[cfg(feature = "std")]
+pub use self::time::AtomicInstant;
+cfg_has_atomic_cas! {
+mod sharded_counter;
+pub use self::sharded_counter::ShardedCounter;
+} // cfg_has_atomic_cas!
+} // cfg_has_atomic_64!
 
 pub mod hint {
     //! Re-export of the [`core::hint`] module.
@@ -516,11 +694,17 @@ pub mod hint {
     /// [`thread::yield_now`]: https://doc.rust-lang.org/std/thread/fn.yield_now.html
     #[inline]
     pub fn spin_loop() {
+        // Let loom switch to other threads to avoid spinning forever.
+        #[cfg(portable_atomic_loom)]
+        loom::hint::spin_loop();
+        #[cfg(not(portable_atomic_loom))]
         #[allow(deprecated)]
         core::sync::atomic::spin_loop_hint();
     }
 }
 
+#[cfg(not(portable_atomic_loom))]
+use core::slice;
 #[cfg(doc)]
 use core::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release, SeqCst};
 use core::{fmt, ptr};
@@ -540,7 +724,11 @@ cfg_has_atomic_8! {
 /// assembly.
 #[repr(C, align(1))]
 pub struct AtomicBool {
+    #[cfg(not(portable_atomic_loom))]
     v: core::cell::UnsafeCell<u8>,
+    // loom's atomic types do not have the same layout as the value type.
+    #[cfg(portable_atomic_loom)]
+    v: imp::AtomicU8,
 }
 
 impl Default for AtomicBool {
@@ -573,24 +761,35 @@ impl std::panic::RefUnwindSafe for AtomicBool {}
 impl_debug_and_serde!(AtomicBool);
 
 impl AtomicBool {
-    /// Creates a new `AtomicBool`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use portable_atomic::AtomicBool;
-    ///
-    /// let atomic_true = AtomicBool::new(true);
-    /// let atomic_false = AtomicBool::new(false);
-    /// ```
-    #[inline]
-    #[must_use]
-    pub const fn new(v: bool) -> Self {
-        static_assert_layout!(AtomicBool, bool);
-        Self { v: core::cell::UnsafeCell::new(v as u8) }
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new `AtomicBool`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use portable_atomic::AtomicBool;
+        ///
+        /// let atomic_true = AtomicBool::new(true);
+        /// let atomic_false = AtomicBool::new(false);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub const fn new(v: bool) -> Self {
+            static_assert_layout!(AtomicBool, bool);
+            #[cfg(not(portable_atomic_loom))]
+            {
+                Self { v: core::cell::UnsafeCell::new(v as u8) }
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                Self { v: imp::AtomicU8::new(v as u8) }
+            }
+        }
     }
 
     // TODO: update docs based on https://github.com/rust-lang/rust/pull/116762
+    #[cfg(not(portable_atomic_loom))]
     const_fn! {
         const_if: #[cfg(not(portable_atomic_no_const_mut_refs))];
         /// Creates a new `AtomicBool` from a pointer.
@@ -672,6 +871,31 @@ impl AtomicBool {
     #[cfg(test)]
     const IS_ALWAYS_LOCK_FREE: bool = Self::is_always_lock_free();
 
+    /// Returns `true` if operations on values of this type are address-free.
+    ///
+    /// Address-free atomic operations don't rely on any state other than the value
+    /// itself, so they are atomic even when the same memory is accessed through
+    /// different addresses, e.g., by multiple processes via shared memory.
+    ///
+    /// In this crate, this is currently equivalent to [`is_lock_free`](Self::is_lock_free):
+    /// the locks used by the implementations that are not lock-free are local to
+    /// the current process.
+    ///
+    /// See also the [`require-address-free` feature](crate#optional-features-require-address-free).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::AtomicBool;
+    ///
+    /// let is_address_free = AtomicBool::is_address_free();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_address_free() -> bool {
+        Self::is_lock_free()
+    }
+
     const_fn! {
         const_if: #[cfg(not(portable_atomic_no_const_mut_refs))];
         /// Returns a mutable reference to the underlying [`bool`].
@@ -698,11 +922,106 @@ impl AtomicBool {
         }
     }
 
-    // TODO: Add from_mut/get_mut_slice/from_mut_slice once it is stable on std atomic types.
-    // https://github.com/rust-lang/rust/issues/76314
+    /// Gets an atomic reference to the given `bool`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not aligned to `align_of::<AtomicBool>()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicBool, Ordering};
+    ///
+    /// let mut some_bool = true;
+    /// let a = AtomicBool::from_mut(&mut some_bool);
+    /// a.store(false, Ordering::Relaxed);
+    /// assert_eq!(some_bool, false);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn from_mut(v: &mut bool) -> &mut Self {
+        let ptr: *mut bool = v;
+        assert!(
+            utils::is_aligned_for::<bool, Self>(ptr),
+            "v is not aligned to align_of::<AtomicBool>()"
+        );
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicBool and bool have the same size and in-memory representations.
+        //  - ptr is sufficiently aligned for AtomicBool (checked above).
+        unsafe { &mut *(ptr as *mut Self) }
+    }
+
+    /// Gets non-atomic access to a `&mut [AtomicBool]` slice.
+    ///
+    /// This is safe because the mutable reference guarantees that no other threads are
+    /// concurrently accessing the atomic data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicBool, Ordering};
+    ///
+    /// let mut some_bools = [AtomicBool::new(false), AtomicBool::new(false)];
+    /// AtomicBool::get_mut_slice(&mut some_bools)[1] = true;
+    /// assert_eq!(some_bools[1].load(Ordering::Relaxed), true);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    pub fn get_mut_slice(this: &mut [Self]) -> &mut [bool] {
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicBool and bool have the same size and in-memory representations,
+        //    and the alignment of AtomicBool is not smaller than the alignment of bool.
+        unsafe { &mut *(this as *mut [Self] as *mut [bool]) }
+    }
+
+    /// Gets atomic access to a `&mut [bool]` slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not aligned to `align_of::<AtomicBool>()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicBool, Ordering};
+    ///
+    /// let mut some_bools = [false; 10];
+    /// let a = AtomicBool::from_mut_slice(&mut some_bools);
+    /// a[5].store(true, Ordering::Relaxed);
+    /// assert_eq!(some_bools[5], true);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn from_mut_slice(v: &mut [bool]) -> &mut [Self] {
+        let len = v.len();
+        if len == 0 {
+            return &mut [];
+        }
+        let ptr = v.as_mut_ptr();
+        assert!(
+            utils::is_aligned_for::<bool, Self>(ptr),
+            "v is not aligned to align_of::<AtomicBool>()"
+        );
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicBool and bool have the same size and in-memory representations.
+        //  - ptr is sufficiently aligned for AtomicBool (checked above).
+        unsafe { slice::from_raw_parts_mut(ptr as *mut Self, len) }
+    }
 
     const_fn! {
-        const_if: #[cfg(not(portable_atomic_no_const_transmute))];
+        const_if: #[cfg(not(any(portable_atomic_no_const_transmute, portable_atomic_loom)))];
         /// Consumes the atomic and returns the contained value.
         ///
         /// This is safe because passing `self` by value guarantees that no other threads are
@@ -720,10 +1039,17 @@ impl AtomicBool {
         /// ```
         #[inline]
         pub const fn into_inner(self) -> bool {
+            #[cfg(not(portable_atomic_loom))]
             // SAFETY: AtomicBool and u8 have the same size and in-memory representations,
             // so they can be safely transmuted.
             // (const UnsafeCell::into_inner is unstable)
-            unsafe { core::mem::transmute::<AtomicBool, u8>(self) != 0 }
+            unsafe {
+                core::mem::transmute::<AtomicBool, u8>(self) != 0
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                self.v.into_inner() != 0
+            }
         }
     }
 
@@ -1378,6 +1704,73 @@ impl AtomicBool {
         }
         Err(prev)
     }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value, retrying at most `max_retries` times if the value has been
+    /// changed from other threads in the meantime.
+    ///
+    /// Returns `Ok(previous_value)` if the function returned `Some(_)` and the
+    /// value was updated. Otherwise, returns a [`FetchUpdateError`] that contains
+    /// the value loaded last, and whether the function returned `None` or the
+    /// compare-and-swap failed `max_retries + 1` times.
+    ///
+    /// This is the same as [`fetch_update`](Self::fetch_update), except that
+    /// `backoff` is snoozed after each failed compare-and-swap, and that the
+    /// number of retries is bounded. This is useful in contexts where unbounded
+    /// loops are not acceptable, such as in real-time threads.
+    ///
+    /// Note that the compare-and-swap may also fail spuriously, which counts as a retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`], [`AcqRel`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{Backoff, AtomicBool, Ordering};
+    ///
+    /// let x = AtomicBool::new(false);
+    /// let mut backoff = Backoff::new();
+    /// let err = x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |_| None).unwrap_err();
+    /// assert!(!err.is_exhausted());
+    /// assert_eq!(err.into_inner(), false);
+    /// assert_eq!(x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |x| Some(!x)), Ok(false));
+    /// assert_eq!(x.load(Ordering::SeqCst), true);
+    /// ```
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update_with<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        backoff: &mut Backoff,
+        max_retries: usize,
+        mut f: F,
+    ) -> Result<bool, FetchUpdateError<bool>>
+    where
+        F: FnMut(bool) -> Option<bool>,
+    {
+        let mut prev = self.load(fetch_order);
+        let mut retries = 0;
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                Ok(x) => return Ok(x),
+                Err(next_prev) => {
+                    if retries == max_retries {
+                        return Err(FetchUpdateError::exhausted(next_prev));
+                    }
+                    retries += 1;
+                    backoff.snooze();
+                    prev = next_prev;
+                }
+            }
+        }
+        Err(FetchUpdateError::aborted(prev))
+    }
     } // cfg_has_atomic_cas_or_amo32!
 
     const_fn! {
@@ -1400,15 +1793,29 @@ impl AtomicBool {
         /// This is `const fn` on Rust 1.58+.
         #[inline]
         pub const fn as_ptr(&self) -> *mut bool {
-            self.v.get() as *mut bool
+            #[cfg(not(portable_atomic_loom))]
+            {
+                self.v.get() as *mut bool
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                self.v.as_ptr() as *mut bool
+            }
         }
     }
 
     #[inline(always)]
     fn as_atomic_u8(&self) -> &imp::AtomicU8 {
+        #[cfg(not(portable_atomic_loom))]
         // SAFETY: AtomicBool and imp::AtomicU8 have the same layout,
         // and both access data in the same way.
-        unsafe { &*(self as *const Self as *const imp::AtomicU8) }
+        unsafe {
+            &*(self as *const Self as *const imp::AtomicU8)
+        }
+        #[cfg(portable_atomic_loom)]
+        {
+            &self.v
+        }
     }
 }
 // See https://github.com/taiki-e/portable-atomic/issues/180
@@ -1527,6 +1934,21 @@ impl<'a> AtomicBool {
     {
         unimplemented!()
     }
+    #[inline]
+    pub fn fetch_update_with<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        backoff: &mut Backoff,
+        max_retries: usize,
+        f: F,
+    ) -> Result<bool, FetchUpdateError<bool>>
+    where
+        F: FnMut(bool) -> Option<bool>,
+        &'a Self: HasFetchUpdate,
+    {
+        unimplemented!()
+    }
     } // cfg_no_atomic_cas_or_amo32!
 }
 } // cfg_no_atomic_cas!
@@ -1590,24 +2012,28 @@ impl<T> core::panic::RefUnwindSafe for AtomicPtr<T> {}
 impl<T> std::panic::RefUnwindSafe for AtomicPtr<T> {}
 
 impl<T> AtomicPtr<T> {
-    /// Creates a new `AtomicPtr`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// use portable_atomic::AtomicPtr;
-    ///
-    /// let ptr = &mut 5;
-    /// let atomic_ptr = AtomicPtr::new(ptr);
-    /// ```
-    #[inline]
-    #[must_use]
-    pub const fn new(p: *mut T) -> Self {
-        static_assert_layout!(AtomicPtr<()>, *mut ());
-        Self { inner: imp::AtomicPtr::new(p) }
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new `AtomicPtr`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use portable_atomic::AtomicPtr;
+        ///
+        /// let ptr = &mut 5;
+        /// let atomic_ptr = AtomicPtr::new(ptr);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub const fn new(p: *mut T) -> Self {
+            static_assert_layout!(AtomicPtr<()>, *mut ());
+            Self { inner: imp::AtomicPtr::new(p) }
+        }
     }
 
     // TODO: update docs based on https://github.com/rust-lang/rust/pull/116762
+    #[cfg(not(portable_atomic_loom))]
     const_fn! {
         const_if: #[cfg(not(portable_atomic_no_const_mut_refs))];
         /// Creates a new `AtomicPtr` from a pointer.
@@ -1689,6 +2115,31 @@ impl<T> AtomicPtr<T> {
     #[cfg(test)]
     const IS_ALWAYS_LOCK_FREE: bool = Self::is_always_lock_free();
 
+    /// Returns `true` if operations on values of this type are address-free.
+    ///
+    /// Address-free atomic operations don't rely on any state other than the value
+    /// itself, so they are atomic even when the same memory is accessed through
+    /// different addresses, e.g., by multiple processes via shared memory.
+    ///
+    /// In this crate, this is currently equivalent to [`is_lock_free`](Self::is_lock_free):
+    /// the locks used by the implementations that are not lock-free are local to
+    /// the current process.
+    ///
+    /// See also the [`require-address-free` feature](crate#optional-features-require-address-free).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::AtomicPtr;
+    ///
+    /// let is_address_free = AtomicPtr::<()>::is_address_free();
+    /// ```
+    #[inline]
+    #[must_use]
+    pub fn is_address_free() -> bool {
+        Self::is_lock_free()
+    }
+
     const_fn! {
         const_if: #[cfg(not(portable_atomic_no_const_mut_refs))];
         /// Returns a mutable reference to the underlying pointer.
@@ -1717,33 +2168,141 @@ impl<T> AtomicPtr<T> {
         }
     }
 
-    // TODO: Add from_mut/get_mut_slice/from_mut_slice once it is stable on std atomic types.
-    // https://github.com/rust-lang/rust/issues/76314
-
-    const_fn! {
-        const_if: #[cfg(not(portable_atomic_no_const_transmute))];
-        /// Consumes the atomic and returns the contained value.
-        ///
-        /// This is safe because passing `self` by value guarantees that no other threads are
-        /// concurrently accessing the atomic data.
-        ///
-        /// This is `const fn` on Rust 1.56+.
-        ///
-        /// # Examples
-        ///
-        /// ```
-        /// use portable_atomic::AtomicPtr;
-        ///
-        /// let mut data = 5;
+    /// Gets an atomic reference to the given pointer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not aligned to `align_of::<AtomicPtr<T>>()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicPtr, Ordering};
+    ///
+    /// let mut data = 123;
+    /// let mut some_ptr = &mut data as *mut i32;
+    /// let a = AtomicPtr::from_mut(&mut some_ptr);
+    /// let mut other_data = 456;
+    /// a.store(&mut other_data, Ordering::Relaxed);
+    /// assert_eq!(unsafe { *some_ptr }, 456);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn from_mut(v: &mut *mut T) -> &mut Self {
+        let ptr: *mut *mut T = v;
+        assert!(
+            utils::is_aligned_for::<*mut T, Self>(ptr),
+            "v is not aligned to align_of::<AtomicPtr<T>>()"
+        );
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicPtr<T> and *mut T have the same size and in-memory representations.
+        //  - ptr is sufficiently aligned for AtomicPtr<T> (checked above).
+        unsafe { &mut *(ptr as *mut Self) }
+    }
+
+    /// Gets non-atomic access to a `&mut [AtomicPtr<T>]` slice.
+    ///
+    /// This is safe because the mutable reference guarantees that no other threads are
+    /// concurrently accessing the atomic data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicPtr, Ordering};
+    /// use std::ptr::null_mut;
+    ///
+    /// let mut some_ptrs = [AtomicPtr::<i32>::new(null_mut()), AtomicPtr::new(null_mut())];
+    /// let mut data = 5;
+    /// AtomicPtr::get_mut_slice(&mut some_ptrs)[1] = &mut data;
+    /// assert_eq!(unsafe { *some_ptrs[1].load(Ordering::Relaxed) }, 5);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    pub fn get_mut_slice(this: &mut [Self]) -> &mut [*mut T] {
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicPtr<T> and *mut T have the same size and in-memory representations,
+        //    and the alignment of AtomicPtr<T> is not smaller than the alignment of *mut T.
+        unsafe { &mut *(this as *mut [Self] as *mut [*mut T]) }
+    }
+
+    /// Gets atomic access to a `&mut [*mut T]` slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `v` is not aligned to `align_of::<AtomicPtr<T>>()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicPtr, Ordering};
+    /// use std::ptr::null_mut;
+    ///
+    /// let mut some_ptrs = [null_mut::<i32>(); 10];
+    /// let a = AtomicPtr::from_mut_slice(&mut some_ptrs);
+    /// let mut data = 5;
+    /// a[5].store(&mut data, Ordering::Relaxed);
+    /// assert_eq!(unsafe { *some_ptrs[5] }, 5);
+    /// ```
+    #[cfg(not(portable_atomic_loom))]
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn from_mut_slice(v: &mut [*mut T]) -> &mut [Self] {
+        let len = v.len();
+        if len == 0 {
+            return &mut [];
+        }
+        let ptr = v.as_mut_ptr();
+        assert!(
+            utils::is_aligned_for::<*mut T, Self>(ptr),
+            "v is not aligned to align_of::<AtomicPtr<T>>()"
+        );
+        // SAFETY:
+        //  - the mutable reference guarantees unique ownership.
+        //  - AtomicPtr<T> and *mut T have the same size and in-memory representations.
+        //  - ptr is sufficiently aligned for AtomicPtr<T> (checked above).
+        unsafe { slice::from_raw_parts_mut(ptr as *mut Self, len) }
+    }
+
+    const_fn! {
+        const_if: #[cfg(not(any(portable_atomic_no_const_transmute, portable_atomic_loom)))];
+        /// Consumes the atomic and returns the contained value.
+        ///
+        /// This is safe because passing `self` by value guarantees that no other threads are
+        /// concurrently accessing the atomic data.
+        ///
+        /// This is `const fn` on Rust 1.56+.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use portable_atomic::AtomicPtr;
+        ///
+        /// let mut data = 5;
         /// let atomic_ptr = AtomicPtr::new(&mut data);
         /// assert_eq!(unsafe { *atomic_ptr.into_inner() }, 5);
         /// ```
         #[inline]
         pub const fn into_inner(self) -> *mut T {
+            #[cfg(not(portable_atomic_loom))]
             // SAFETY: AtomicPtr<T> and *mut T have the same size and in-memory representations,
             // so they can be safely transmuted.
             // (const UnsafeCell::into_inner is unstable)
-            unsafe { core::mem::transmute(self) }
+            unsafe {
+                core::mem::transmute(self)
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                self.inner.into_inner()
+            }
         }
     }
 
@@ -2005,6 +2564,77 @@ impl<T> AtomicPtr<T> {
         Err(prev)
     }
 
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value, retrying at most `max_retries` times if the value has been
+    /// changed from other threads in the meantime.
+    ///
+    /// Returns `Ok(previous_value)` if the function returned `Some(_)` and the
+    /// value was updated. Otherwise, returns a [`FetchUpdateError`] that contains
+    /// the value loaded last, and whether the function returned `None` or the
+    /// compare-and-swap failed `max_retries + 1` times.
+    ///
+    /// This is the same as [`fetch_update`](Self::fetch_update), except that
+    /// `backoff` is snoozed after each failed compare-and-swap, and that the
+    /// number of retries is bounded. This is useful in contexts where unbounded
+    /// loops are not acceptable, such as in real-time threads.
+    ///
+    /// Note that the compare-and-swap may also fail spuriously, which counts as a retry.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`], [`AcqRel`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use portable_atomic::{AtomicPtr, Backoff, Ordering};
+    /// use std::ptr;
+    ///
+    /// let mut five = 5;
+    /// let five: *mut i32 = &mut five;
+    ///
+    /// let x = AtomicPtr::<i32>::new(ptr::null_mut());
+    /// let mut backoff = Backoff::new();
+    /// let err = x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |_| None).unwrap_err();
+    /// assert!(!err.is_exhausted());
+    /// assert_eq!(err.into_inner(), ptr::null_mut());
+    /// assert_eq!(x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |x| Some(five)), Ok(ptr::null_mut()));
+    /// assert_eq!(x.load(Ordering::SeqCst), five);
+    /// ```
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update_with<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        backoff: &mut Backoff,
+        max_retries: usize,
+        mut f: F,
+    ) -> Result<*mut T, FetchUpdateError<*mut T>>
+    where
+        F: FnMut(*mut T) -> Option<*mut T>,
+    {
+        let mut prev = self.load(fetch_order);
+        let mut retries = 0;
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                Ok(x) => return Ok(x),
+                Err(next_prev) => {
+                    if retries == max_retries {
+                        return Err(FetchUpdateError::exhausted(next_prev));
+                    }
+                    retries += 1;
+                    backoff.snooze();
+                    prev = next_prev;
+                }
+            }
+        }
+        Err(FetchUpdateError::aborted(prev))
+    }
+
     #[cfg(miri)]
     #[inline]
     #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
@@ -2588,6 +3218,21 @@ impl<'a, T: 'a> AtomicPtr<T> {
     {
         unimplemented!()
     }
+    #[inline]
+    pub fn fetch_update_with<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        backoff: &mut Backoff,
+        max_retries: usize,
+        f: F,
+    ) -> Result<*mut T, FetchUpdateError<*mut T>>
+    where
+        F: FnMut(*mut T) -> Option<*mut T>,
+        &'a Self: HasFetchUpdate,
+    {
+        unimplemented!()
+    }
     cfg_no_atomic_cas_or_amo32! {
     #[inline]
     pub fn fetch_ptr_add(&self, val: usize, order: Ordering) -> *mut T
@@ -2715,6 +3360,7 @@ atomic instructions or locks will be used.
         impl_debug_and_serde!($atomic_type);
 
         impl $atomic_type {
+            #[cfg(not(portable_atomic_loom))]
             doc_comment! {
                 concat!(
                     "Creates a new atomic integer.
@@ -2734,9 +3380,28 @@ let atomic_forty_two = ", stringify!($atomic_type), "::new(42);
                     Self { inner: imp::$atomic_type::new(v) }
                 }
             }
+            #[cfg(portable_atomic_loom)]
+            doc_comment! {
+                concat!(
+                    "Creates a new atomic integer.
+
+# Examples
+
+```
+use portable_atomic::", stringify!($atomic_type), ";
+
+let atomic_forty_two = ", stringify!($atomic_type), "::new(42);
+```"
+                ),
+                #[inline]
+                #[must_use]
+                pub fn new(v: $int_type) -> Self {
+                    Self { inner: imp::$atomic_type::new(v) }
+                }
+            }
 
             // TODO: update docs based on https://github.com/rust-lang/rust/pull/116762
-            #[cfg(not(portable_atomic_no_const_mut_refs))]
+            #[cfg(not(any(portable_atomic_no_const_mut_refs, portable_atomic_loom)))]
             doc_comment! {
                 concat!("Creates a new reference to an atomic integer from a pointer.
 
@@ -2774,7 +3439,7 @@ This is `const fn` on Rust 1.83+.
                     unsafe { &*(ptr as *mut Self) }
                 }
             }
-            #[cfg(portable_atomic_no_const_mut_refs)]
+            #[cfg(all(portable_atomic_no_const_mut_refs, not(portable_atomic_loom)))]
             doc_comment! {
                 concat!("Creates a new reference to an atomic integer from a pointer.
 
@@ -2860,6 +3525,33 @@ const IS_ALWAYS_LOCK_FREE: bool = ", stringify!($atomic_type), "::is_always_lock
             #[cfg(test)]
             const IS_ALWAYS_LOCK_FREE: bool = Self::is_always_lock_free();
 
+            doc_comment! {
+                concat!("Returns `true` if operations on values of this type are address-free.
+
+Address-free atomic operations don't rely on any state other than the value
+itself, so they are atomic even when the same memory is accessed through
+different addresses, e.g., by multiple processes via shared memory.
+
+In this crate, this is currently equivalent to [`is_lock_free`](Self::is_lock_free):
+the locks used by the implementations that are not lock-free are local to
+the current process.
+
+See also the [`require-address-free` feature](crate#optional-features-require-address-free).
+
+# Examples
+
+```
+use portable_atomic::", stringify!($atomic_type), ";
+
+let is_address_free = ", stringify!($atomic_type), "::is_address_free();
+```"),
+                #[inline]
+                #[must_use]
+                pub fn is_address_free() -> bool {
+                    Self::is_lock_free()
+                }
+            }
+
             #[cfg(not(portable_atomic_no_const_mut_refs))]
             doc_comment! {
                 concat!("Returns a mutable reference to the underlying integer.\n
@@ -2910,10 +3602,115 @@ assert_eq!(some_var.load(Ordering::SeqCst), 5);
                 }
             }
 
-            // TODO: Add from_mut/get_mut_slice/from_mut_slice once it is stable on std atomic types.
-            // https://github.com/rust-lang/rust/issues/76314
+            #[cfg(not(portable_atomic_loom))]
+            doc_comment! {
+                concat!("Gets an atomic reference to the given integer.
+
+# Panics
+
+Panics if `v` is not aligned to `align_of::<", stringify!($atomic_type), ">()`.
+Note that on some platforms the alignment of `", stringify!($atomic_type), "` can be
+bigger than the alignment of `", stringify!($int_type), "` (e.g., 64-bit integers on 32-bit x86).
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let mut some_int = 123;
+let a = ", stringify!($atomic_type), "::from_mut(&mut some_int);
+a.store(100, Ordering::Relaxed);
+assert_eq!(some_int, 100);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn from_mut(v: &mut $int_type) -> &mut Self {
+                    let ptr: *mut $int_type = v;
+                    assert!(
+                        utils::is_aligned_for::<$int_type, Self>(ptr),
+                        concat!("v is not aligned to align_of::<", stringify!($atomic_type), ">()")
+                    );
+                    // SAFETY:
+                    //  - the mutable reference guarantees unique ownership.
+                    //  - $atomic_type and $int_type have the same size and in-memory representations.
+                    //  - ptr is sufficiently aligned for $atomic_type (checked above).
+                    unsafe { &mut *(ptr as *mut Self) }
+                }
+            }
+
+            #[cfg(not(portable_atomic_loom))]
+            doc_comment! {
+                concat!("Gets non-atomic access to a `&mut [", stringify!($atomic_type), "]` slice.
+
+This is safe because the mutable reference guarantees that no other threads are
+concurrently accessing the atomic data.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let mut some_ints = [", stringify!($atomic_type), "::new(0), ", stringify!($atomic_type), "::new(0)];
+", stringify!($atomic_type), "::get_mut_slice(&mut some_ints)[1] = 5;
+assert_eq!(some_ints[1].load(Ordering::Relaxed), 5);
+```"),
+                #[inline]
+                pub fn get_mut_slice(this: &mut [Self]) -> &mut [$int_type] {
+                    // SAFETY:
+                    //  - the mutable reference guarantees unique ownership.
+                    //  - $atomic_type and $int_type have the same size and in-memory representations,
+                    //    and the alignment of $atomic_type is not smaller than the alignment of $int_type.
+                    unsafe { &mut *(this as *mut [Self] as *mut [$int_type]) }
+                }
+            }
+
+            #[cfg(not(portable_atomic_loom))]
+            doc_comment! {
+                concat!("Gets atomic access to a `&mut [", stringify!($int_type), "]` slice.
+
+# Panics
+
+Panics if `v` is not aligned to `align_of::<", stringify!($atomic_type), ">()`.
+Note that on some platforms the alignment of `", stringify!($atomic_type), "` can be
+bigger than the alignment of `", stringify!($int_type), "` (e.g., 64-bit integers on 32-bit x86).
 
-            #[cfg(not(portable_atomic_no_const_transmute))]
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let mut some_ints = [0; 10];
+let a = ", stringify!($atomic_type), "::from_mut_slice(&mut some_ints);
+a[5].store(5, Ordering::Relaxed);
+assert_eq!(some_ints[5], 5);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn from_mut_slice(v: &mut [$int_type]) -> &mut [Self] {
+                    let len = v.len();
+                    if len == 0 {
+                        return &mut [];
+                    }
+                    let ptr = v.as_mut_ptr();
+                    assert!(
+                        utils::is_aligned_for::<$int_type, Self>(ptr),
+                        concat!("v is not aligned to align_of::<", stringify!($atomic_type), ">()")
+                    );
+                    // SAFETY:
+                    //  - the mutable reference guarantees unique ownership.
+                    //  - $atomic_type and $int_type have the same size and in-memory representations.
+                    //  - ptr is sufficiently aligned for $atomic_type (checked above).
+                    unsafe { slice::from_raw_parts_mut(ptr as *mut Self, len) }
+                }
+            }
+
+            #[cfg(not(any(portable_atomic_no_const_transmute, portable_atomic_loom)))]
             doc_comment! {
                 concat!("Consumes the atomic and returns the contained value.
 
@@ -2938,7 +3735,7 @@ assert_eq!(some_var.into_inner(), 5);
                     unsafe { core::mem::transmute(self) }
                 }
             }
-            #[cfg(portable_atomic_no_const_transmute)]
+            #[cfg(any(portable_atomic_no_const_transmute, portable_atomic_loom))]
             doc_comment! {
                 concat!("Consumes the atomic and returns the contained value.
 
@@ -2957,10 +3754,17 @@ assert_eq!(some_var.into_inner(), 5);
 ```"),
                 #[inline]
                 pub fn into_inner(self) -> $int_type {
+                    #[cfg(not(portable_atomic_loom))]
                     // SAFETY: $atomic_type and $int_type have the same size and in-memory representations,
                     // so they can be safely transmuted.
                     // (const UnsafeCell::into_inner is unstable)
-                    unsafe { core::mem::transmute(self) }
+                    unsafe {
+                        core::mem::transmute(self)
+                    }
+                    #[cfg(portable_atomic_loom)]
+                    {
+                        self.inner.into_inner()
+                    }
                 }
             }
 
@@ -3023,6 +3827,164 @@ assert_eq!(some_var.load(Ordering::Relaxed), 10);
                 }
             }
 
+            doc_comment! {
+                concat!("Loads values from a slice of atomic integers into `dst`.
+
+Each element is loaded by a separate atomic operation, so this is not a single
+atomic operation on the whole slice. `Acquire` is implemented by a single fence
+after all elements have been loaded.
+
+`load_slice` takes an [`Ordering`] argument which describes the memory ordering of this operation.
+Possible values are [`SeqCst`], [`Acquire`] and [`Relaxed`].
+
+# Panics
+
+Panics if `order` is [`Release`] or [`AcqRel`], or if `this` and `dst` have different lengths.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let a = [", stringify!($atomic_type), "::new(1), ", stringify!($atomic_type), "::new(2)];
+let mut buf = [0; 2];
+", stringify!($atomic_type), "::load_slice(&a, &mut buf, Ordering::Acquire);
+assert_eq!(buf, [1, 2]);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn load_slice(this: &[Self], dst: &mut [$int_type], order: Ordering) {
+                    imp::memcpy::load(this, dst, order, Self::load)
+                }
+            }
+
+            doc_comment! {
+                concat!("Stores values from `src` into a slice of atomic integers.
+
+Each element is stored by a separate atomic operation, so this is not a single
+atomic operation on the whole slice. `Release` is implemented by a single fence
+before any element is stored.
+
+`store_slice` takes an [`Ordering`] argument which describes the memory ordering of this operation.
+Possible values are [`SeqCst`], [`Release`] and [`Relaxed`].
+
+# Panics
+
+Panics if `order` is [`Acquire`] or [`AcqRel`], or if `this` and `src` have different lengths.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let a = [", stringify!($atomic_type), "::new(0), ", stringify!($atomic_type), "::new(0)];
+", stringify!($atomic_type), "::store_slice(&a, &[1, 2], Ordering::Release);
+assert_eq!(a[0].load(Ordering::Relaxed), 1);
+assert_eq!(a[1].load(Ordering::Relaxed), 2);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn store_slice(this: &[Self], src: &[$int_type], order: Ordering) {
+                    imp::memcpy::store(this, src, order, Self::store)
+                }
+            }
+
+            doc_comment! {
+                concat!("Loads values from a slice of atomic integers into `dst`, using
+pointer-sized atomic loads for the aligned middle part of the slice.
+
+This has the same semantics as [`load_slice`](Self::load_slice), except that
+adjacent elements in the middle of the slice may be loaded by a single wider
+atomic load. This usually makes copying large slices of small integers faster.
+
+# Panics
+
+Panics if `order` is [`Release`] or [`AcqRel`], or if `this` and `dst` have different lengths.
+
+# Safety
+
+The elements of `this` must not be accessed concurrently by atomic operations of
+other sizes, including `load_slice_chunked` or [`store_slice_chunked`](Self::store_slice_chunked)
+on a different range that overlaps with `this`. Calls on exactly the same range are fine,
+because the chunk boundaries are determined by the address.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let a = [", stringify!($atomic_type), "::new(1), ", stringify!($atomic_type), "::new(2)];
+let mut buf = [0; 2];
+// SAFETY: there are no concurrent accesses to `a`.
+unsafe { ", stringify!($atomic_type), "::load_slice_chunked(&a, &mut buf, Ordering::Acquire) }
+assert_eq!(buf, [1, 2]);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub unsafe fn load_slice_chunked(
+                    this: &[Self],
+                    dst: &mut [$int_type],
+                    order: Ordering,
+                ) {
+                    // SAFETY: the caller must uphold the safety contract.
+                    unsafe { imp::memcpy::load_chunked(this, dst, order, Self::load) }
+                }
+            }
+
+            doc_comment! {
+                concat!("Stores values from `src` into a slice of atomic integers, using
+pointer-sized atomic stores for the aligned middle part of the slice.
+
+This has the same semantics as [`store_slice`](Self::store_slice), except that
+adjacent elements in the middle of the slice may be stored by a single wider
+atomic store. This usually makes copying large slices of small integers faster.
+
+# Panics
+
+Panics if `order` is [`Acquire`] or [`AcqRel`], or if `this` and `src` have different lengths.
+
+# Safety
+
+The elements of `this` must not be accessed concurrently by atomic operations of
+other sizes, including [`load_slice_chunked`](Self::load_slice_chunked) or `store_slice_chunked`
+on a different range that overlaps with `this`. Calls on exactly the same range are fine,
+because the chunk boundaries are determined by the address.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let a = [", stringify!($atomic_type), "::new(0), ", stringify!($atomic_type), "::new(0)];
+// SAFETY: there are no concurrent accesses to `a`.
+unsafe { ", stringify!($atomic_type), "::store_slice_chunked(&a, &[1, 2], Ordering::Release) }
+assert_eq!(a[0].load(Ordering::Relaxed), 1);
+assert_eq!(a[1].load(Ordering::Relaxed), 2);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub unsafe fn store_slice_chunked(
+                    this: &[Self],
+                    src: &[$int_type],
+                    order: Ordering,
+                ) {
+                    // SAFETY: the caller must uphold the safety contract.
+                    unsafe { imp::memcpy::store_chunked(this, src, order, Self::store) }
+                }
+            }
+
             cfg_has_atomic_cas_or_amo32! {
             $cfg_has_atomic_cas_or_amo32_or_8! {
             doc_comment! {
@@ -3434,149 +4396,380 @@ where the result is unused.
 ```
 use portable_atomic::{", stringify!($atomic_type), ", Ordering};
 
-let foo = ", stringify!($atomic_type), "::new(0b101101);
-assert_eq!(foo.fetch_or(0b110011, Ordering::SeqCst), 0b101101);
-assert_eq!(foo.load(Ordering::SeqCst), 0b111111);
+let foo = ", stringify!($atomic_type), "::new(0b101101);
+assert_eq!(foo.fetch_or(0b110011, Ordering::SeqCst), 0b101101);
+assert_eq!(foo.load(Ordering::SeqCst), 0b111111);
+```"),
+                #[inline]
+                #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+                pub fn or(&self, val: $int_type, order: Ordering) {
+                    self.inner.or(val, order);
+                }
+            }
+
+            doc_comment! {
+                concat!("Bitwise \"xor\" with the current value.
+
+Performs a bitwise \"xor\" operation on the current value and the argument `val`, and
+sets the new value to the result.
+
+Returns the previous value.
+
+`fetch_xor` takes an [`Ordering`] argument which describes the memory ordering
+of this operation. All ordering modes are possible. Note that using
+[`Acquire`] makes the store part of this operation [`Relaxed`], and
+using [`Release`] makes the load part [`Relaxed`].
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let foo = ", stringify!($atomic_type), "::new(0b101101);
+assert_eq!(foo.fetch_xor(0b110011, Ordering::SeqCst), 0b101101);
+assert_eq!(foo.load(Ordering::SeqCst), 0b011110);
+```"),
+                #[inline]
+                #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+                pub fn fetch_xor(&self, val: $int_type, order: Ordering) -> $int_type {
+                    self.inner.fetch_xor(val, order)
+                }
+            }
+
+            doc_comment! {
+                concat!("Bitwise \"xor\" with the current value.
+
+Performs a bitwise \"xor\" operation on the current value and the argument `val`, and
+sets the new value to the result.
+
+Unlike `fetch_xor`, this does not return the previous value.
+
+`xor` takes an [`Ordering`] argument which describes the memory ordering
+of this operation. All ordering modes are possible. Note that using
+[`Acquire`] makes the store part of this operation [`Relaxed`], and
+using [`Release`] makes the load part [`Relaxed`].
+
+This function may generate more efficient code than `fetch_xor` on some platforms.
+
+- x86/x86_64: `lock xor` instead of `cmpxchg` loop ({8,16,32}-bit atomics on x86, but additionally 64-bit atomics on x86_64)
+- MSP430: `xor` instead of disabling interrupts ({8,16}-bit atomics)
+
+Note: On x86/x86_64, the use of either function should not usually
+affect the generated code, because LLVM can properly optimize the case
+where the result is unused.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let foo = ", stringify!($atomic_type), "::new(0b101101);
+foo.xor(0b110011, Ordering::SeqCst);
+assert_eq!(foo.load(Ordering::SeqCst), 0b011110);
+```"),
+                #[inline]
+                #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+                pub fn xor(&self, val: $int_type, order: Ordering) {
+                    self.inner.xor(val, order);
+                }
+            }
+
+            cfg_has_atomic_cas! {
+            doc_comment! {
+                concat!("Fetches the value, and applies a function to it that returns an optional
+new value. Returns a `Result` of `Ok(previous_value)` if the function returned `Some(_)`, else
+`Err(previous_value)`.
+
+Note: This may call the function multiple times if the value has been changed from other threads in
+the meantime, as long as the function returns `Some(_)`, but the function will have been applied
+only once to the stored value.
+
+`fetch_update` takes two [`Ordering`] arguments to describe the memory ordering of this operation.
+The first describes the required ordering for when the operation finally succeeds while the second
+describes the required ordering for loads. These correspond to the success and failure orderings of
+[`compare_exchange`](Self::compare_exchange) respectively.
+
+Using [`Acquire`] as success ordering makes the store part
+of this operation [`Relaxed`], and using [`Release`] makes the final successful load
+[`Relaxed`]. The (failed) load ordering can only be [`SeqCst`], [`Acquire`] or [`Relaxed`].
+
+# Panics
+
+Panics if `fetch_order` is [`Release`], [`AcqRel`].
+
+# Considerations
+
+This method is not magic; it is not provided by the hardware.
+It is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
+and suffers from the same drawbacks.
+In particular, this method will not circumvent the [ABA Problem].
+
+[ABA Problem]: https://en.wikipedia.org/wiki/ABA_problem
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let x = ", stringify!($atomic_type), "::new(7);
+assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| None), Err(7));
+assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(7));
+assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(8));
+assert_eq!(x.load(Ordering::SeqCst), 9);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn fetch_update<F>(
+                    &self,
+                    set_order: Ordering,
+                    fetch_order: Ordering,
+                    mut f: F,
+                ) -> Result<$int_type, $int_type>
+                where
+                    F: FnMut($int_type) -> Option<$int_type>,
+                {
+                    let mut prev = self.load(fetch_order);
+                    while let Some(next) = f(prev) {
+                        match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                            x @ Ok(_) => return x,
+                            Err(next_prev) => prev = next_prev,
+                        }
+                    }
+                    Err(prev)
+                }
+            }
+
+            doc_comment! {
+                concat!("Fetches the value, and applies a function to it that returns an optional
+new value, retrying at most `max_retries` times if the value has been
+changed from other threads in the meantime.
+
+Returns `Ok(previous_value)` if the function returned `Some(_)` and the
+value was updated. Otherwise, returns a [`FetchUpdateError`] that contains
+the value loaded last, and whether the function returned `None` or the
+compare-and-swap failed `max_retries + 1` times.
+
+This is the same as [`fetch_update`](Self::fetch_update), except that
+`backoff` is snoozed after each failed compare-and-swap, and that the
+number of retries is bounded. This is useful in contexts where unbounded
+loops are not acceptable, such as in real-time threads.
+
+Note that the compare-and-swap may also fail spuriously, which counts as a retry.
+
+# Panics
+
+Panics if `fetch_order` is [`Release`], [`AcqRel`].
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Backoff, Ordering};
+
+let x = ", stringify!($atomic_type), "::new(7);
+let mut backoff = Backoff::new();
+let err = x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |_| None).unwrap_err();
+assert!(!err.is_exhausted());
+assert_eq!(err.into_inner(), 7);
+assert_eq!(x.fetch_update_with(Ordering::SeqCst, Ordering::SeqCst, &mut backoff, 3, |x| Some(x + 1)), Ok(7));
+assert_eq!(x.load(Ordering::SeqCst), 8);
+```"),
+                #[inline]
+                #[cfg_attr(
+                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                    track_caller
+                )]
+                pub fn fetch_update_with<F>(
+                    &self,
+                    set_order: Ordering,
+                    fetch_order: Ordering,
+                    backoff: &mut Backoff,
+                    max_retries: usize,
+                    mut f: F,
+                ) -> Result<$int_type, FetchUpdateError<$int_type>>
+                where
+                    F: FnMut($int_type) -> Option<$int_type>,
+                {
+                    let mut prev = self.load(fetch_order);
+                    let mut retries = 0;
+                    while let Some(next) = f(prev) {
+                        match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                            Ok(x) => return Ok(x),
+                            Err(next_prev) => {
+                                if retries == max_retries {
+                                    return Err(FetchUpdateError::exhausted(next_prev));
+                                }
+                                retries += 1;
+                                backoff.snooze();
+                                prev = next_prev;
+                            }
+                        }
+                    }
+                    Err(FetchUpdateError::aborted(prev))
+                }
+            }
+
+            doc_comment! {
+                concat!("Adds to the current value, saturating at the numeric bounds instead of
+overflowing, returning the previous value.
+
+`fetch_saturating_add` takes an [`Ordering`] argument which describes the memory ordering
+of this operation. All ordering modes are possible. Note that using
+[`Acquire`] makes the store part of this operation [`Relaxed`], and
+using [`Release`] makes the load part [`Relaxed`].
+
+# Considerations
+
+This method is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
+and suffers from the same drawbacks.
+
+# Examples
+
+```
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let foo = ", stringify!($atomic_type), "::new(", stringify!($int_type), "::MAX - 1);
+assert_eq!(foo.fetch_saturating_add(2, Ordering::SeqCst), ", stringify!($int_type), "::MAX - 1);
+assert_eq!(foo.load(Ordering::SeqCst), ", stringify!($int_type), "::MAX);
 ```"),
                 #[inline]
                 #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
-                pub fn or(&self, val: $int_type, order: Ordering) {
-                    self.inner.or(val, order);
+                pub fn fetch_saturating_add(&self, val: $int_type, order: Ordering) -> $int_type {
+                    // There is no per-architecture LL/SC implementation of this: on
+                    // LL/SC architectures compare_exchange_weak already compiles to a
+                    // single LL/SC pair, so the loop below is what such an
+                    // implementation would look like.
+                    // The operation always completes with a successful CAS, so there is
+                    // no need to synchronize the first load/failed CAS.
+                    let mut prev = self.load(Ordering::Relaxed);
+                    loop {
+                        let next = prev.saturating_add(val);
+                        match self.compare_exchange_weak(prev, next, order, Ordering::Relaxed) {
+                            Ok(x) => return x,
+                            Err(next_prev) => prev = next_prev,
+                        }
+                    }
                 }
             }
 
             doc_comment! {
-                concat!("Bitwise \"xor\" with the current value.
-
-Performs a bitwise \"xor\" operation on the current value and the argument `val`, and
-sets the new value to the result.
-
-Returns the previous value.
+                concat!("Subtracts from the current value, saturating at the numeric bounds instead of
+overflowing, returning the previous value.
 
-`fetch_xor` takes an [`Ordering`] argument which describes the memory ordering
+`fetch_saturating_sub` takes an [`Ordering`] argument which describes the memory ordering
 of this operation. All ordering modes are possible. Note that using
 [`Acquire`] makes the store part of this operation [`Relaxed`], and
 using [`Release`] makes the load part [`Relaxed`].
 
+# Considerations
+
+This method is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
+and suffers from the same drawbacks.
+
 # Examples
 
 ```
 use portable_atomic::{", stringify!($atomic_type), ", Ordering};
 
-let foo = ", stringify!($atomic_type), "::new(0b101101);
-assert_eq!(foo.fetch_xor(0b110011, Ordering::SeqCst), 0b101101);
-assert_eq!(foo.load(Ordering::SeqCst), 0b011110);
+let foo = ", stringify!($atomic_type), "::new(", stringify!($int_type), "::MIN + 1);
+assert_eq!(foo.fetch_saturating_sub(2, Ordering::SeqCst), ", stringify!($int_type), "::MIN + 1);
+assert_eq!(foo.load(Ordering::SeqCst), ", stringify!($int_type), "::MIN);
 ```"),
                 #[inline]
                 #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
-                pub fn fetch_xor(&self, val: $int_type, order: Ordering) -> $int_type {
-                    self.inner.fetch_xor(val, order)
+                pub fn fetch_saturating_sub(&self, val: $int_type, order: Ordering) -> $int_type {
+                    // The operation always completes with a successful CAS, so there is
+                    // no need to synchronize the first load/failed CAS.
+                    let mut prev = self.load(Ordering::Relaxed);
+                    loop {
+                        let next = prev.saturating_sub(val);
+                        match self.compare_exchange_weak(prev, next, order, Ordering::Relaxed) {
+                            Ok(x) => return x,
+                            Err(next_prev) => prev = next_prev,
+                        }
+                    }
                 }
             }
 
             doc_comment! {
-                concat!("Bitwise \"xor\" with the current value.
-
-Performs a bitwise \"xor\" operation on the current value and the argument `val`, and
-sets the new value to the result.
+                concat!("Adds to the current value if the result does not overflow.
 
-Unlike `fetch_xor`, this does not return the previous value.
+Returns `Ok(previous_value)` if the value was updated, else `Err(previous_value)`
+and the value is left unchanged.
 
-`xor` takes an [`Ordering`] argument which describes the memory ordering
+`fetch_checked_add` takes an [`Ordering`] argument which describes the memory ordering
 of this operation. All ordering modes are possible. Note that using
 [`Acquire`] makes the store part of this operation [`Relaxed`], and
-using [`Release`] makes the load part [`Relaxed`].
-
-This function may generate more efficient code than `fetch_xor` on some platforms.
+using [`Release`] makes the load part [`Relaxed`]. If the addition overflows,
+the value is only loaded, with the same ordering as the failure ordering of
+[`compare_exchange`](Self::compare_exchange) corresponding to `order`.
 
-- x86/x86_64: `lock xor` instead of `cmpxchg` loop ({8,16,32}-bit atomics on x86, but additionally 64-bit atomics on x86_64)
-- MSP430: `xor` instead of disabling interrupts ({8,16}-bit atomics)
+# Considerations
 
-Note: On x86/x86_64, the use of either function should not usually
-affect the generated code, because LLVM can properly optimize the case
-where the result is unused.
+This method is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
+and suffers from the same drawbacks.
 
 # Examples
 
 ```
 use portable_atomic::{", stringify!($atomic_type), ", Ordering};
 
-let foo = ", stringify!($atomic_type), "::new(0b101101);
-foo.xor(0b110011, Ordering::SeqCst);
-assert_eq!(foo.load(Ordering::SeqCst), 0b011110);
+let foo = ", stringify!($atomic_type), "::new(", stringify!($int_type), "::MAX - 1);
+assert_eq!(foo.fetch_checked_add(1, Ordering::SeqCst), Ok(", stringify!($int_type), "::MAX - 1));
+assert_eq!(foo.fetch_checked_add(1, Ordering::SeqCst), Err(", stringify!($int_type), "::MAX));
+assert_eq!(foo.load(Ordering::SeqCst), ", stringify!($int_type), "::MAX);
 ```"),
                 #[inline]
                 #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
-                pub fn xor(&self, val: $int_type, order: Ordering) {
-                    self.inner.xor(val, order);
+                pub fn fetch_checked_add(
+                    &self,
+                    val: $int_type,
+                    order: Ordering,
+                ) -> Result<$int_type, $int_type> {
+                    let failure = utils::strongest_failure_ordering(order);
+                    self.fetch_update(order, failure, |x| x.checked_add(val))
                 }
             }
 
-            cfg_has_atomic_cas! {
             doc_comment! {
-                concat!("Fetches the value, and applies a function to it that returns an optional
-new value. Returns a `Result` of `Ok(previous_value)` if the function returned `Some(_)`, else
-`Err(previous_value)`.
-
-Note: This may call the function multiple times if the value has been changed from other threads in
-the meantime, as long as the function returns `Some(_)`, but the function will have been applied
-only once to the stored value.
-
-`fetch_update` takes two [`Ordering`] arguments to describe the memory ordering of this operation.
-The first describes the required ordering for when the operation finally succeeds while the second
-describes the required ordering for loads. These correspond to the success and failure orderings of
-[`compare_exchange`](Self::compare_exchange) respectively.
+                concat!("Subtracts from the current value if the result does not overflow.
 
-Using [`Acquire`] as success ordering makes the store part
-of this operation [`Relaxed`], and using [`Release`] makes the final successful load
-[`Relaxed`]. The (failed) load ordering can only be [`SeqCst`], [`Acquire`] or [`Relaxed`].
-
-# Panics
+Returns `Ok(previous_value)` if the value was updated, else `Err(previous_value)`
+and the value is left unchanged.
 
-Panics if `fetch_order` is [`Release`], [`AcqRel`].
+`fetch_checked_sub` takes an [`Ordering`] argument which describes the memory ordering
+of this operation. All ordering modes are possible. Note that using
+[`Acquire`] makes the store part of this operation [`Relaxed`], and
+using [`Release`] makes the load part [`Relaxed`]. If the subtraction overflows,
+the value is only loaded, with the same ordering as the failure ordering of
+[`compare_exchange`](Self::compare_exchange) corresponding to `order`.
 
 # Considerations
 
-This method is not magic; it is not provided by the hardware.
-It is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
+This method is implemented in terms of [`compare_exchange_weak`](Self::compare_exchange_weak),
 and suffers from the same drawbacks.
-In particular, this method will not circumvent the [ABA Problem].
-
-[ABA Problem]: https://en.wikipedia.org/wiki/ABA_problem
 
 # Examples
 
 ```
 use portable_atomic::{", stringify!($atomic_type), ", Ordering};
 
-let x = ", stringify!($atomic_type), "::new(7);
-assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |_| None), Err(7));
-assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(7));
-assert_eq!(x.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |x| Some(x + 1)), Ok(8));
-assert_eq!(x.load(Ordering::SeqCst), 9);
+let foo = ", stringify!($atomic_type), "::new(", stringify!($int_type), "::MIN + 1);
+assert_eq!(foo.fetch_checked_sub(1, Ordering::SeqCst), Ok(", stringify!($int_type), "::MIN + 1));
+assert_eq!(foo.fetch_checked_sub(1, Ordering::SeqCst), Err(", stringify!($int_type), "::MIN));
+assert_eq!(foo.load(Ordering::SeqCst), ", stringify!($int_type), "::MIN);
 ```"),
                 #[inline]
-                #[cfg_attr(
-                    any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
-                    track_caller
-                )]
-                pub fn fetch_update<F>(
+                #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+                pub fn fetch_checked_sub(
                     &self,
-                    set_order: Ordering,
-                    fetch_order: Ordering,
-                    mut f: F,
-                ) -> Result<$int_type, $int_type>
-                where
-                    F: FnMut($int_type) -> Option<$int_type>,
-                {
-                    let mut prev = self.load(fetch_order);
-                    while let Some(next) = f(prev) {
-                        match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
-                            x @ Ok(_) => return x,
-                            Err(next_prev) => prev = next_prev,
-                        }
-                    }
-                    Err(prev)
+                    val: $int_type,
+                    order: Ordering,
+                ) -> Result<$int_type, $int_type> {
+                    let failure = utils::strongest_failure_ordering(order);
+                    self.fetch_update(order, failure, |x| x.checked_sub(val))
                 }
             }
             } // cfg_has_atomic_cas!
@@ -4032,6 +5225,57 @@ assert_eq!(foo.load(Ordering::Relaxed), 5);
             {
                 unimplemented!()
             }
+            #[inline]
+            pub fn fetch_update_with<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                backoff: &mut Backoff,
+                max_retries: usize,
+                f: F,
+            ) -> Result<$int_type, FetchUpdateError<$int_type>>
+            where
+                F: FnMut($int_type) -> Option<$int_type>,
+                &'a Self: HasFetchUpdate,
+            {
+                unimplemented!()
+            }
+            #[inline]
+            pub fn fetch_saturating_add(&self, val: $int_type, order: Ordering) -> $int_type
+            where
+                &'a Self: HasFetchSaturatingAdd,
+            {
+                unimplemented!()
+            }
+            #[inline]
+            pub fn fetch_saturating_sub(&self, val: $int_type, order: Ordering) -> $int_type
+            where
+                &'a Self: HasFetchSaturatingSub,
+            {
+                unimplemented!()
+            }
+            #[inline]
+            pub fn fetch_checked_add(
+                &self,
+                val: $int_type,
+                order: Ordering,
+            ) -> Result<$int_type, $int_type>
+            where
+                &'a Self: HasFetchCheckedAdd,
+            {
+                unimplemented!()
+            }
+            #[inline]
+            pub fn fetch_checked_sub(
+                &self,
+                val: $int_type,
+                order: Ordering,
+            ) -> Result<$int_type, $int_type>
+            where
+                &'a Self: HasFetchCheckedSub,
+            {
+                unimplemented!()
+            }
             $cfg_no_atomic_cas_or_amo32_or_8! {
             #[inline]
             pub fn fetch_max(&self, val: $int_type, order: Ordering) -> $int_type
@@ -4154,16 +5398,19 @@ This type has the same in-memory representation as the underlying floating point
         impl_debug_and_serde!($atomic_type);
 
         impl $atomic_type {
-            /// Creates a new atomic float.
-            #[inline]
-            #[must_use]
-            pub const fn new(v: $float_type) -> Self {
-                static_assert_layout!($atomic_type, $float_type);
-                Self { inner: imp::float::$atomic_type::new(v) }
+            const_fn! {
+                const_if: #[cfg(not(portable_atomic_loom))];
+                /// Creates a new atomic float.
+                #[inline]
+                #[must_use]
+                pub const fn new(v: $float_type) -> Self {
+                    static_assert_layout!($atomic_type, $float_type);
+                    Self { inner: imp::float::$atomic_type::new(v) }
+                }
             }
 
             // TODO: update docs based on https://github.com/rust-lang/rust/pull/116762
-            #[cfg(not(portable_atomic_no_const_mut_refs))]
+            #[cfg(not(any(portable_atomic_no_const_mut_refs, portable_atomic_loom)))]
             doc_comment! {
                 concat!("Creates a new reference to an atomic float from a pointer.
 
@@ -4201,7 +5448,7 @@ This is `const fn` on Rust 1.83+.
                     unsafe { &*(ptr as *mut Self) }
                 }
             }
-            #[cfg(portable_atomic_no_const_mut_refs)]
+            #[cfg(all(portable_atomic_no_const_mut_refs, not(portable_atomic_loom)))]
             doc_comment! {
                 concat!("Creates a new reference to an atomic float from a pointer.
 
@@ -4267,6 +5514,23 @@ This is `const fn` on Rust 1.83+.
             #[cfg(test)]
             const IS_ALWAYS_LOCK_FREE: bool = Self::is_always_lock_free();
 
+            /// Returns `true` if operations on values of this type are address-free.
+            ///
+            /// Address-free atomic operations don't rely on any state other than the value
+            /// itself, so they are atomic even when the same memory is accessed through
+            /// different addresses, e.g., by multiple processes via shared memory.
+            ///
+            /// In this crate, this is currently equivalent to [`is_lock_free`](Self::is_lock_free):
+            /// the locks used by the implementations that are not lock-free are local to
+            /// the current process.
+            ///
+            /// See also the [`require-address-free` feature](crate#optional-features-require-address-free).
+            #[inline]
+            #[must_use]
+            pub fn is_address_free() -> bool {
+                Self::is_lock_free()
+            }
+
             const_fn! {
                 const_if: #[cfg(not(portable_atomic_no_const_mut_refs))];
                 /// Returns a mutable reference to the underlying float.
@@ -4282,11 +5546,81 @@ This is `const fn` on Rust 1.83+.
                 }
             }
 
-            // TODO: Add from_mut/get_mut_slice/from_mut_slice once it is stable on std atomic types.
-            // https://github.com/rust-lang/rust/issues/76314
+            /// Gets an atomic reference to the given float.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `v` is not aligned to `align_of::<Self>()`.
+            /// Note that on some platforms the alignment of this type can be
+            /// bigger than the alignment of the float type (e.g., `f64` on 32-bit x86).
+            #[cfg(not(portable_atomic_loom))]
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn from_mut(v: &mut $float_type) -> &mut Self {
+                let ptr: *mut $float_type = v;
+                assert!(
+                    utils::is_aligned_for::<$float_type, Self>(ptr),
+                    concat!("v is not aligned to align_of::<", stringify!($atomic_type), ">()")
+                );
+                // SAFETY:
+                //  - the mutable reference guarantees unique ownership.
+                //  - $atomic_type and $float_type have the same size and in-memory representations.
+                //  - ptr is sufficiently aligned for $atomic_type (checked above).
+                unsafe { &mut *(ptr as *mut Self) }
+            }
+
+            /// Gets non-atomic access to a `&mut [Self]` slice.
+            ///
+            /// This is safe because the mutable reference guarantees that no other threads are
+            /// concurrently accessing the atomic data.
+            #[cfg(not(portable_atomic_loom))]
+            #[inline]
+            pub fn get_mut_slice(this: &mut [Self]) -> &mut [$float_type] {
+                // SAFETY:
+                //  - the mutable reference guarantees unique ownership.
+                //  - $atomic_type and $float_type have the same size and in-memory representations,
+                //    and the alignment of $atomic_type is not smaller than the alignment of $float_type.
+                unsafe { &mut *(this as *mut [Self] as *mut [$float_type]) }
+            }
+
+            /// Gets atomic access to a slice of floats.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `v` is not aligned to `align_of::<Self>()`.
+            /// Note that on some platforms the alignment of this type can be
+            /// bigger than the alignment of the float type (e.g., `f64` on 32-bit x86).
+            #[cfg(not(portable_atomic_loom))]
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn from_mut_slice(v: &mut [$float_type]) -> &mut [Self] {
+                let len = v.len();
+                if len == 0 {
+                    return &mut [];
+                }
+                let ptr = v.as_mut_ptr();
+                assert!(
+                    utils::is_aligned_for::<$float_type, Self>(ptr),
+                    concat!("v is not aligned to align_of::<", stringify!($atomic_type), ">()")
+                );
+                // SAFETY:
+                //  - the mutable reference guarantees unique ownership.
+                //  - $atomic_type and $float_type have the same size and in-memory representations.
+                //  - ptr is sufficiently aligned for $atomic_type (checked above).
+                unsafe { slice::from_raw_parts_mut(ptr as *mut Self, len) }
+            }
 
             const_fn! {
-                const_if: #[cfg(not(portable_atomic_no_const_transmute))];
+                const_if: #[cfg(not(any(
+                    portable_atomic_no_const_transmute,
+                    portable_atomic_loom,
+                )))];
                 /// Consumes the atomic and returns the contained value.
                 ///
                 /// This is safe because passing `self` by value guarantees that no other threads are
@@ -4295,10 +5629,17 @@ This is `const fn` on Rust 1.83+.
                 /// This is `const fn` on Rust 1.56+.
                 #[inline]
                 pub const fn into_inner(self) -> $float_type {
+                    #[cfg(not(portable_atomic_loom))]
                     // SAFETY: $atomic_type and $float_type have the same size and in-memory representations,
                     // so they can be safely transmuted.
                     // (const UnsafeCell::into_inner is unstable)
-                    unsafe { core::mem::transmute(self) }
+                    unsafe {
+                        core::mem::transmute(self)
+                    }
+                    #[cfg(portable_atomic_loom)]
+                    {
+                        self.inner.into_inner()
+                    }
                 }
             }
 
@@ -4500,6 +5841,59 @@ This is `const fn` on Rust 1.83+.
                 Err(prev)
             }
 
+            /// Fetches the value, and applies a function to it that returns an optional
+            /// new value, retrying at most `max_retries` times if the value has been
+            /// changed from other threads in the meantime.
+            ///
+            /// Returns `Ok(previous_value)` if the function returned `Some(_)` and the
+            /// value was updated. Otherwise, returns a [`FetchUpdateError`] that contains
+            /// the value loaded last, and whether the function returned `None` or the
+            /// compare-and-swap failed `max_retries + 1` times.
+            ///
+            /// This is the same as [`fetch_update`](Self::fetch_update), except that
+            /// `backoff` is snoozed after each failed compare-and-swap, and that the
+            /// number of retries is bounded. This is useful in contexts where unbounded
+            /// loops are not acceptable, such as in real-time threads.
+            ///
+            /// Note that the compare-and-swap may also fail spuriously, which counts as a retry.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `fetch_order` is [`Release`], [`AcqRel`].
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn fetch_update_with<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                backoff: &mut Backoff,
+                max_retries: usize,
+                mut f: F,
+            ) -> Result<$float_type, FetchUpdateError<$float_type>>
+            where
+                F: FnMut($float_type) -> Option<$float_type>,
+            {
+                let mut prev = self.load(fetch_order);
+                let mut retries = 0;
+                while let Some(next) = f(prev) {
+                    match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                        Ok(x) => return Ok(x),
+                        Err(next_prev) => {
+                            if retries == max_retries {
+                                return Err(FetchUpdateError::exhausted(next_prev));
+                            }
+                            retries += 1;
+                            backoff.snooze();
+                            prev = next_prev;
+                        }
+                    }
+                }
+                Err(FetchUpdateError::aborted(prev))
+            }
+
             /// Maximum with the current value.
             ///
             /// Finds the maximum of the current value and the argument `val`, and
@@ -4683,6 +6077,21 @@ This is `const fn` on Rust 1.58+."),
                 unimplemented!()
             }
             #[inline]
+            pub fn fetch_update_with<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                backoff: &mut Backoff,
+                max_retries: usize,
+                f: F,
+            ) -> Result<$float_type, FetchUpdateError<$float_type>>
+            where
+                F: FnMut($float_type) -> Option<$float_type>,
+                &'a Self: HasFetchUpdate,
+            {
+                unimplemented!()
+            }
+            #[inline]
             pub fn fetch_max(&self, val: $float_type, order: Ordering) -> $float_type
             where
                 &'a Self: HasFetchMax,
@@ -4779,8 +6188,9 @@ use diagnostic_helper::{HasAdd, HasSub, HasSwap};
 } // cfg_no_atomic_cas_or_amo8!
 #[cfg_attr(not(feature = "float"), allow(unused_imports))]
 use diagnostic_helper::{
-    HasCompareExchange, HasCompareExchangeWeak, HasFetchAdd, HasFetchMax, HasFetchMin,
-    HasFetchNand, HasFetchNeg, HasFetchSub, HasFetchUpdate, HasNeg,
+    HasCompareExchange, HasCompareExchangeWeak, HasFetchAdd, HasFetchCheckedAdd,
+    HasFetchCheckedSub, HasFetchMax, HasFetchMin, HasFetchNand, HasFetchNeg,
+    HasFetchSaturatingAdd, HasFetchSaturatingSub, HasFetchSub, HasFetchUpdate, HasNeg,
 };
 #[cfg_attr(
     any(
@@ -5100,6 +6510,50 @@ mod diagnostic_helper {
         )
     )]
     pub trait HasFetchUpdate {}
+    #[doc(hidden)]
+    #[cfg_attr(
+        not(portable_atomic_no_diagnostic_namespace),
+        diagnostic::on_unimplemented(
+            message = "`fetch_saturating_add` requires atomic CAS but not available on this target by default",
+            label = "this associated function is not available on this target by default",
+            note = "consider enabling one of the `unsafe-assume-single-core` or `critical-section` Cargo features",
+            note = "see <https://docs.rs/portable-atomic/latest/portable_atomic/#optional-features> for more."
+        )
+    )]
+    pub trait HasFetchSaturatingAdd {}
+    #[doc(hidden)]
+    #[cfg_attr(
+        not(portable_atomic_no_diagnostic_namespace),
+        diagnostic::on_unimplemented(
+            message = "`fetch_saturating_sub` requires atomic CAS but not available on this target by default",
+            label = "this associated function is not available on this target by default",
+            note = "consider enabling one of the `unsafe-assume-single-core` or `critical-section` Cargo features",
+            note = "see <https://docs.rs/portable-atomic/latest/portable_atomic/#optional-features> for more."
+        )
+    )]
+    pub trait HasFetchSaturatingSub {}
+    #[doc(hidden)]
+    #[cfg_attr(
+        not(portable_atomic_no_diagnostic_namespace),
+        diagnostic::on_unimplemented(
+            message = "`fetch_checked_add` requires atomic CAS but not available on this target by default",
+            label = "this associated function is not available on this target by default",
+            note = "consider enabling one of the `unsafe-assume-single-core` or `critical-section` Cargo features",
+            note = "see <https://docs.rs/portable-atomic/latest/portable_atomic/#optional-features> for more."
+        )
+    )]
+    pub trait HasFetchCheckedAdd {}
+    #[doc(hidden)]
+    #[cfg_attr(
+        not(portable_atomic_no_diagnostic_namespace),
+        diagnostic::on_unimplemented(
+            message = "`fetch_checked_sub` requires atomic CAS but not available on this target by default",
+            label = "this associated function is not available on this target by default",
+            note = "consider enabling one of the `unsafe-assume-single-core` or `critical-section` Cargo features",
+            note = "see <https://docs.rs/portable-atomic/latest/portable_atomic/#optional-features> for more."
+        )
+    )]
+    pub trait HasFetchCheckedSub {}
     cfg_no_atomic_cas_or_amo32! {
     #[doc(hidden)]
     #[cfg_attr(
@@ -0,0 +1,504 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Atomic `NonNull<T>` and `Option<NonNull<T>>` types, implemented on top of AtomicPtr.
+
+use core::{fmt, ptr, ptr::NonNull};
+
+use crate::{AtomicPtr, Ordering};
+
+#[inline]
+fn from_option<T>(ptr: Option<NonNull<T>>) -> *mut T {
+    match ptr {
+        Some(ptr) => ptr.as_ptr(),
+        None => ptr::null_mut(),
+    }
+}
+
+/// A non-null raw pointer type which can be safely shared between threads.
+///
+/// This type has the same in-memory representation as a `*mut T`, and is
+/// implemented using [`AtomicPtr`], so it is available on all targets where
+/// `AtomicPtr` is available.
+///
+/// # Examples
+///
+/// ```
+/// use core::ptr::NonNull;
+/// use portable_atomic::{AtomicNonNull, Ordering};
+///
+/// let mut a = 1;
+/// let mut b = 2;
+/// let p = AtomicNonNull::new(NonNull::from(&mut a));
+/// let prev = p.swap(NonNull::from(&mut b), Ordering::AcqRel);
+/// assert_eq!(unsafe { *prev.as_ptr() }, 1);
+/// assert_eq!(unsafe { *p.load(Ordering::Acquire).as_ptr() }, 2);
+/// ```
+#[repr(transparent)]
+pub struct AtomicNonNull<T> {
+    // Invariant: this is never null.
+    inner: AtomicPtr<T>,
+}
+
+impl<T> From<NonNull<T>> for AtomicNonNull<T> {
+    #[inline]
+    fn from(p: NonNull<T>) -> Self {
+        Self::new(p)
+    }
+}
+
+impl<T> fmt::Debug for AtomicNonNull<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<T> fmt::Pointer for AtomicNonNull<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<T> AtomicNonNull<T> {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new `AtomicNonNull`.
+        #[inline]
+        #[must_use]
+        pub const fn new(p: NonNull<T>) -> Self {
+            Self { inner: AtomicPtr::new(p.as_ptr()) }
+        }
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// This is the same as [`AtomicPtr::is_lock_free`].
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicPtr::<T>::is_lock_free()
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// This is the same as [`AtomicPtr::is_always_lock_free`].
+    #[inline]
+    #[must_use]
+    pub const fn is_always_lock_free() -> bool {
+        AtomicPtr::<T>::is_always_lock_free()
+    }
+
+    /// Consumes the atomic and returns the contained value.
+    #[inline]
+    pub fn into_inner(self) -> NonNull<T> {
+        // SAFETY: the pointer is never null.
+        unsafe { NonNull::new_unchecked(self.inner.into_inner()) }
+    }
+
+    /// Loads a value from the pointer.
+    ///
+    /// `load` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn load(&self, order: Ordering) -> NonNull<T> {
+        // SAFETY: the pointer is never null.
+        unsafe { NonNull::new_unchecked(self.inner.load(order)) }
+    }
+
+    /// Stores a value into the pointer.
+    ///
+    /// `store` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn store(&self, p: NonNull<T>, order: Ordering) {
+        self.inner.store(p.as_ptr(), order);
+    }
+
+    cfg_has_atomic_cas! {
+    /// Stores a value into the pointer, returning the previous value.
+    ///
+    /// See [`AtomicPtr::swap`] for details.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn swap(&self, p: NonNull<T>, order: Ordering) -> NonNull<T> {
+        // SAFETY: the pointer is never null.
+        unsafe { NonNull::new_unchecked(self.inner.swap(p.as_ptr(), order)) }
+    }
+
+    /// Stores a value into the pointer if the current value is the same as
+    /// the `current` value.
+    ///
+    /// See [`AtomicPtr::compare_exchange`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange(
+        &self,
+        current: NonNull<T>,
+        new: NonNull<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<NonNull<T>, NonNull<T>> {
+        match self.inner.compare_exchange(current.as_ptr(), new.as_ptr(), success, failure) {
+            // SAFETY: the pointer is never null.
+            Ok(p) => Ok(unsafe { NonNull::new_unchecked(p) }),
+            // SAFETY: the pointer is never null.
+            Err(p) => Err(unsafe { NonNull::new_unchecked(p) }),
+        }
+    }
+
+    /// Stores a value into the pointer if the current value is the same as
+    /// the `current` value.
+    ///
+    /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+    /// allowed to spuriously fail even when the comparison succeeds.
+    /// See [`AtomicPtr::compare_exchange_weak`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange_weak(
+        &self,
+        current: NonNull<T>,
+        new: NonNull<T>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<NonNull<T>, NonNull<T>> {
+        match self.inner.compare_exchange_weak(current.as_ptr(), new.as_ptr(), success, failure) {
+            // SAFETY: the pointer is never null.
+            Ok(p) => Ok(unsafe { NonNull::new_unchecked(p) }),
+            // SAFETY: the pointer is never null.
+            Err(p) => Err(unsafe { NonNull::new_unchecked(p) }),
+        }
+    }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+    /// returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// See [`AtomicPtr::fetch_update`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<NonNull<T>, NonNull<T>>
+    where
+        F: FnMut(NonNull<T>) -> Option<NonNull<T>>,
+    {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+    } // cfg_has_atomic_cas!
+}
+
+/// A nullable raw pointer type which can be safely shared between threads,
+/// represented as `Option<NonNull<T>>`.
+///
+/// This type has the same in-memory representation as a `*mut T`, and is
+/// implemented using [`AtomicPtr`], so it is available on all targets where
+/// `AtomicPtr` is available.
+///
+/// # Examples
+///
+/// ```
+/// use core::ptr::NonNull;
+/// use portable_atomic::{AtomicOptionNonNull, Ordering};
+///
+/// let mut a = 1;
+/// let p = AtomicOptionNonNull::new(None);
+/// assert_eq!(p.swap(Some(NonNull::from(&mut a)), Ordering::AcqRel), None);
+/// assert!(p.load(Ordering::Acquire).is_some());
+/// ```
+#[repr(transparent)]
+pub struct AtomicOptionNonNull<T> {
+    inner: AtomicPtr<T>,
+}
+
+impl<T> Default for AtomicOptionNonNull<T> {
+    /// Creates an `AtomicOptionNonNull<T>` that contains `None`.
+    #[inline]
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+impl<T> From<Option<NonNull<T>>> for AtomicOptionNonNull<T> {
+    #[inline]
+    fn from(p: Option<NonNull<T>>) -> Self {
+        Self::new(p)
+    }
+}
+
+impl<T> From<NonNull<T>> for AtomicOptionNonNull<T> {
+    #[inline]
+    fn from(p: NonNull<T>) -> Self {
+        Self::new(Some(p))
+    }
+}
+
+impl<T> fmt::Debug for AtomicOptionNonNull<T> {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl<T> AtomicOptionNonNull<T> {
+    /// Creates a new `AtomicOptionNonNull`.
+    #[inline]
+    #[must_use]
+    pub fn new(p: Option<NonNull<T>>) -> Self {
+        Self { inner: AtomicPtr::new(from_option(p)) }
+    }
+
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new `AtomicOptionNonNull` that contains `None`.
+        ///
+        /// This is equivalent to `AtomicOptionNonNull::new(None)`, but can be used in
+        /// const context such as statics.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use portable_atomic::{AtomicOptionNonNull, Ordering};
+        ///
+        /// static P: AtomicOptionNonNull<u8> = AtomicOptionNonNull::none();
+        /// assert_eq!(P.load(Ordering::Relaxed), None);
+        /// ```
+        #[inline]
+        #[must_use]
+        pub const fn none() -> Self {
+            Self { inner: AtomicPtr::new(ptr::null_mut()) }
+        }
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// This is the same as [`AtomicPtr::is_lock_free`].
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicPtr::<T>::is_lock_free()
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// This is the same as [`AtomicPtr::is_always_lock_free`].
+    #[inline]
+    #[must_use]
+    pub const fn is_always_lock_free() -> bool {
+        AtomicPtr::<T>::is_always_lock_free()
+    }
+
+    /// Returns a reference to the underlying [`AtomicPtr`].
+    ///
+    /// This is sound because every value of `*mut T` is a valid `Option<NonNull<T>>`.
+    #[inline]
+    pub fn as_atomic_ptr(&self) -> &AtomicPtr<T> {
+        &self.inner
+    }
+
+    /// Consumes the atomic and returns the contained value.
+    #[inline]
+    pub fn into_inner(self) -> Option<NonNull<T>> {
+        NonNull::new(self.inner.into_inner())
+    }
+
+    /// Loads a value from the pointer.
+    ///
+    /// `load` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn load(&self, order: Ordering) -> Option<NonNull<T>> {
+        NonNull::new(self.inner.load(order))
+    }
+
+    /// Stores a value into the pointer.
+    ///
+    /// `store` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn store(&self, p: Option<NonNull<T>>, order: Ordering) {
+        self.inner.store(from_option(p), order);
+    }
+
+    cfg_has_atomic_cas! {
+    /// Stores a value into the pointer, returning the previous value.
+    ///
+    /// See [`AtomicPtr::swap`] for details.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn swap(&self, p: Option<NonNull<T>>, order: Ordering) -> Option<NonNull<T>> {
+        NonNull::new(self.inner.swap(from_option(p), order))
+    }
+
+    /// Takes the value out of the pointer, leaving `None` in its place.
+    ///
+    /// This is equivalent to `self.swap(None, order)`.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn take(&self, order: Ordering) -> Option<NonNull<T>> {
+        self.swap(None, order)
+    }
+
+    /// Stores a value into the pointer if the current value is the same as
+    /// the `current` value.
+    ///
+    /// See [`AtomicPtr::compare_exchange`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange(
+        &self,
+        current: Option<NonNull<T>>,
+        new: Option<NonNull<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Option<NonNull<T>>, Option<NonNull<T>>> {
+        match self.inner.compare_exchange(from_option(current), from_option(new), success, failure)
+        {
+            Ok(p) => Ok(NonNull::new(p)),
+            Err(p) => Err(NonNull::new(p)),
+        }
+    }
+
+    /// Stores a value into the pointer if the current value is the same as
+    /// the `current` value.
+    ///
+    /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+    /// allowed to spuriously fail even when the comparison succeeds.
+    /// See [`AtomicPtr::compare_exchange_weak`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange_weak(
+        &self,
+        current: Option<NonNull<T>>,
+        new: Option<NonNull<T>>,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Option<NonNull<T>>, Option<NonNull<T>>> {
+        match self.inner.compare_exchange_weak(
+            from_option(current),
+            from_option(new),
+            success,
+            failure,
+        ) {
+            Ok(p) => Ok(NonNull::new(p)),
+            Err(p) => Err(NonNull::new(p)),
+        }
+    }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+    /// returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// See [`AtomicPtr::fetch_update`] for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<Option<NonNull<T>>, Option<NonNull<T>>>
+    where
+        F: FnMut(Option<NonNull<T>>) -> Option<Option<NonNull<T>>>,
+    {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+    } // cfg_has_atomic_cas!
+}
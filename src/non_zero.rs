@@ -0,0 +1,287 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Atomic `NonZero*` integer types, implemented on top of the atomic integer types.
+
+#![allow(unused_imports)] // not all types are available on all targets
+
+use core::{
+    fmt,
+    num::{
+        NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize, NonZeroU128,
+        NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize,
+    },
+};
+
+use crate::Ordering;
+
+macro_rules! atomic_non_zero {
+    ($atomic_type:ident, $non_zero_type:ident, $atomic_int_type:ident) => {
+        doc_comment! {
+            concat!("A [`", stringify!($non_zero_type), "`](core::num::", stringify!($non_zero_type),
+") which can be safely shared between threads.
+
+This type has the same in-memory representation as the underlying integer type,
+and is implemented using [`", stringify!($atomic_int_type), "`](crate::", stringify!($atomic_int_type), "),
+so it is available on all targets where `", stringify!($atomic_int_type), "` is available.
+
+All operations that return a value return it as a `", stringify!($non_zero_type), "`.
+
+# Examples
+
+```
+use core::num::", stringify!($non_zero_type), ";
+use portable_atomic::{", stringify!($atomic_type), ", Ordering};
+
+let one = ", stringify!($non_zero_type), "::new(1).unwrap();
+let two = ", stringify!($non_zero_type), "::new(2).unwrap();
+let a = ", stringify!($atomic_type), "::new(one);
+assert_eq!(a.swap(two, Ordering::AcqRel), one);
+assert_eq!(a.load(Ordering::Acquire), two);
+```"),
+            #[repr(transparent)]
+            pub struct $atomic_type {
+                // Invariant: this is never zero.
+                inner: crate::$atomic_int_type,
+            }
+        }
+
+        impl From<$non_zero_type> for $atomic_type {
+            #[inline]
+            fn from(v: $non_zero_type) -> Self {
+                Self::new(v)
+            }
+        }
+
+        impl fmt::Debug for $atomic_type {
+            #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+                fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+            }
+        }
+
+        impl $atomic_type {
+            const_fn! {
+                const_if: #[cfg(not(portable_atomic_loom))];
+                /// Creates a new atomic non-zero integer.
+                #[inline]
+                #[must_use]
+                pub const fn new(v: $non_zero_type) -> Self {
+                    Self { inner: crate::$atomic_int_type::new(v.get()) }
+                }
+            }
+
+            /// Returns `true` if operations on values of this type are lock-free.
+            #[inline]
+            #[must_use]
+            pub fn is_lock_free() -> bool {
+                crate::$atomic_int_type::is_lock_free()
+            }
+
+            /// Returns `true` if operations on values of this type are lock-free.
+            ///
+            /// **Note:** If the atomic operation relies on dynamic CPU feature detection,
+            /// this type may be lock-free even if the function returns false.
+            #[inline]
+            #[must_use]
+            pub const fn is_always_lock_free() -> bool {
+                crate::$atomic_int_type::is_always_lock_free()
+            }
+
+            /// Consumes the atomic and returns the contained value.
+            #[inline]
+            pub fn into_inner(self) -> $non_zero_type {
+                // SAFETY: the value is never zero.
+                unsafe { $non_zero_type::new_unchecked(self.inner.into_inner()) }
+            }
+
+            /// Loads a value from the atomic integer.
+            ///
+            /// `load` takes an [`Ordering`] argument which describes the memory ordering
+            /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+            /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+            ///
+            /// # Panics
+            ///
+            /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn load(&self, order: Ordering) -> $non_zero_type {
+                // SAFETY: the value is never zero.
+                unsafe { $non_zero_type::new_unchecked(self.inner.load(order)) }
+            }
+
+            /// Stores a value into the atomic integer.
+            ///
+            /// `store` takes an [`Ordering`] argument which describes the memory ordering
+            /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+            /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+            ///
+            /// # Panics
+            ///
+            /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn store(&self, val: $non_zero_type, order: Ordering) {
+                self.inner.store(val.get(), order);
+            }
+
+            cfg_has_atomic_cas! {
+            /// Stores a value into the atomic integer, returning the previous value.
+            ///
+            /// `swap` takes an [`Ordering`] argument which describes the memory ordering
+            /// of this operation. All ordering modes are possible.
+            #[inline]
+            #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+            pub fn swap(&self, val: $non_zero_type, order: Ordering) -> $non_zero_type {
+                // SAFETY: the value is never zero.
+                unsafe { $non_zero_type::new_unchecked(self.inner.swap(val.get(), order)) }
+            }
+
+            /// Stores a value into the atomic integer if the current value is the same as
+            /// the `current` value.
+            ///
+            /// The return value is a result indicating whether the new value was written and
+            /// containing the previous value.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn compare_exchange(
+                &self,
+                current: $non_zero_type,
+                new: $non_zero_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$non_zero_type, $non_zero_type> {
+                match self.inner.compare_exchange(current.get(), new.get(), success, failure) {
+                    // SAFETY: the value is never zero.
+                    Ok(v) => Ok(unsafe { $non_zero_type::new_unchecked(v) }),
+                    // SAFETY: the value is never zero.
+                    Err(v) => Err(unsafe { $non_zero_type::new_unchecked(v) }),
+                }
+            }
+
+            /// Stores a value into the atomic integer if the current value is the same as
+            /// the `current` value.
+            ///
+            /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+            /// allowed to spuriously fail even when the comparison succeeds.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn compare_exchange_weak(
+                &self,
+                current: $non_zero_type,
+                new: $non_zero_type,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<$non_zero_type, $non_zero_type> {
+                match self.inner.compare_exchange_weak(current.get(), new.get(), success, failure) {
+                    // SAFETY: the value is never zero.
+                    Ok(v) => Ok(unsafe { $non_zero_type::new_unchecked(v) }),
+                    // SAFETY: the value is never zero.
+                    Err(v) => Err(unsafe { $non_zero_type::new_unchecked(v) }),
+                }
+            }
+
+            /// Fetches the value, and applies a function to it that returns an optional
+            /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+            /// returned `Some(_)`, else `Err(previous_value)`.
+            ///
+            /// See also the `fetch_update` method of the underlying atomic integer type.
+            ///
+            /// # Panics
+            ///
+            /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+            #[inline]
+            #[cfg_attr(
+                any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+                track_caller
+            )]
+            pub fn fetch_update<F>(
+                &self,
+                set_order: Ordering,
+                fetch_order: Ordering,
+                mut f: F,
+            ) -> Result<$non_zero_type, $non_zero_type>
+            where
+                F: FnMut($non_zero_type) -> Option<$non_zero_type>,
+            {
+                let mut prev = self.load(fetch_order);
+                while let Some(next) = f(prev) {
+                    match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                        x @ Ok(_) => return x,
+                        Err(next_prev) => prev = next_prev,
+                    }
+                }
+                Err(prev)
+            }
+
+            /// Maximum with the current value, returning the previous value.
+            ///
+            /// `fetch_max` takes an [`Ordering`] argument which describes the memory ordering
+            /// of this operation. All ordering modes are possible.
+            #[inline]
+            #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+            pub fn fetch_max(&self, val: $non_zero_type, order: Ordering) -> $non_zero_type {
+                // SAFETY: both the current value and val are non-zero, so the result is too.
+                unsafe { $non_zero_type::new_unchecked(self.inner.fetch_max(val.get(), order)) }
+            }
+
+            /// Minimum with the current value, returning the previous value.
+            ///
+            /// `fetch_min` takes an [`Ordering`] argument which describes the memory ordering
+            /// of this operation. All ordering modes are possible.
+            #[inline]
+            #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+            pub fn fetch_min(&self, val: $non_zero_type, order: Ordering) -> $non_zero_type {
+                // SAFETY: both the current value and val are non-zero, so the result is too.
+                unsafe { $non_zero_type::new_unchecked(self.inner.fetch_min(val.get(), order)) }
+            }
+            } // cfg_has_atomic_cas!
+        }
+    };
+}
+
+crate::cfg_has_atomic_ptr! {
+    atomic_non_zero!(AtomicNonZeroIsize, NonZeroIsize, AtomicIsize);
+    atomic_non_zero!(AtomicNonZeroUsize, NonZeroUsize, AtomicUsize);
+}
+cfg_has_atomic_8! {
+    atomic_non_zero!(AtomicNonZeroI8, NonZeroI8, AtomicI8);
+    atomic_non_zero!(AtomicNonZeroU8, NonZeroU8, AtomicU8);
+}
+cfg_has_atomic_16! {
+    atomic_non_zero!(AtomicNonZeroI16, NonZeroI16, AtomicI16);
+    atomic_non_zero!(AtomicNonZeroU16, NonZeroU16, AtomicU16);
+}
+cfg_has_atomic_32! {
+    atomic_non_zero!(AtomicNonZeroI32, NonZeroI32, AtomicI32);
+    atomic_non_zero!(AtomicNonZeroU32, NonZeroU32, AtomicU32);
+}
+cfg_has_atomic_64! {
+    atomic_non_zero!(AtomicNonZeroI64, NonZeroI64, AtomicI64);
+    atomic_non_zero!(AtomicNonZeroU64, NonZeroU64, AtomicU64);
+}
+cfg_has_atomic_128! {
+    atomic_non_zero!(AtomicNonZeroI128, NonZeroI128, AtomicI128);
+    atomic_non_zero!(AtomicNonZeroU128, NonZeroU128, AtomicU128);
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+use core::fmt;
+
+use crate::{cache_padded::CachePadded, AtomicU64, Ordering};
+
+// The number of shards. This is a power of two so that the shard index can be
+// computed by masking. See the "Number of shards" section of the ShardedCounter
+// docs for why this is not a const generic parameter.
+const SHARDS: usize = 16;
+
+/// A counter that spreads updates over multiple cache lines to avoid contention.
+///
+/// Incrementing a single [`AtomicU64`] from many threads makes the cache line
+/// that contains it bounce between CPU cores. `ShardedCounter` instead has 16
+/// shards, each of which is an `AtomicU64` padded to the length of a cache line,
+/// and [`add`](Self::add) updates the shard selected by the current thread.
+/// [`sum`](Self::sum) reads all shards, so it is slower than a load of a single
+/// atomic integer.
+///
+/// If the `std` feature is enabled, each thread is assigned a shard in
+/// round-robin order when it first updates a `ShardedCounter`. Otherwise, there
+/// is no portable way to identify the current thread or CPU, so the shard is
+/// selected by a best-effort hash of the address of the current stack. This
+/// usually differs between threads, but there is no guarantee about how
+/// threads are distributed over the shards: in the worst case, all threads use
+/// the same shard. The result of [`sum`](Self::sum) is correct regardless.
+///
+/// All operations use [`Relaxed`](Ordering::Relaxed) ordering, and arithmetic
+/// wraps around on overflow.
+///
+/// # Number of shards
+///
+/// The number of shards is fixed at 16. This is enough to spread the updates of
+/// typical numbers of concurrently running threads, while keeping the counter at
+/// 2 KiB on targets with 128-byte cache lines and [`sum`](Self::sum) at 16 loads.
+/// The number of shards is not a const generic parameter because const generic
+/// parameters with defaults require Rust 1.59, and this crate supports Rust 1.34.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use portable_atomic::ShardedCounter;
+///
+/// static REQUESTS: ShardedCounter = ShardedCounter::new();
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|_| {
+///         thread::spawn(|| {
+///             for _ in 0..100 {
+///                 REQUESTS.add(1);
+///             }
+///         })
+///     })
+///     .collect();
+/// for h in handles {
+///     h.join().unwrap();
+/// }
+/// assert_eq!(REQUESTS.sum(), 400);
+/// ```
+pub struct ShardedCounter {
+    shards: [CachePadded<AtomicU64>; SHARDS],
+}
+
+impl Default for ShardedCounter {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for ShardedCounter {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedCounter").field("sum", &self.sum()).finish()
+    }
+}
+
+impl ShardedCounter {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new counter with the value `0`.
+        #[inline]
+        #[must_use]
+        pub const fn new() -> Self {
+            #[cfg(not(portable_atomic_loom))]
+            {
+                const S: CachePadded<AtomicU64> = CachePadded::new(AtomicU64::new(0));
+                Self { shards: [S, S, S, S, S, S, S, S, S, S, S, S, S, S, S, S] }
+            }
+            #[cfg(portable_atomic_loom)]
+            {
+                Self { shards: [(); SHARDS].map(|()| CachePadded::new(AtomicU64::new(0))) }
+            }
+        }
+    }
+
+    /// Adds `val` to the counter.
+    #[inline]
+    pub fn add(&self, val: u64) {
+        self.shards[shard_index() & (SHARDS - 1)].fetch_add(val, Ordering::Relaxed);
+    }
+
+    /// Returns the sum of all shards.
+    ///
+    /// Updates that happen concurrently with this call may or may not be
+    /// included in the result.
+    #[inline]
+    #[must_use]
+    pub fn sum(&self) -> u64 {
+        self.shards.iter().fold(0, |sum, shard| sum.wrapping_add(shard.load(Ordering::Relaxed)))
+    }
+
+    /// Resets the counter to `0`.
+    ///
+    /// Updates that happen concurrently with this call may or may not be
+    /// reset.
+    #[inline]
+    pub fn reset(&self) {
+        for shard in &self.shards {
+            shard.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+fn shard_index() -> usize {
+    #[cfg(not(portable_atomic_loom))]
+    use crate::AtomicUsize;
+    // The shard assignment is not a part of the state that loom needs to model.
+    #[cfg(portable_atomic_loom)]
+    use core::sync::atomic::AtomicUsize;
+
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    std::thread_local! {
+        static INDEX: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+    // try_with fails only while thread-local variables are being destroyed.
+    INDEX.try_with(|index| *index).unwrap_or(0)
+}
+#[cfg(not(feature = "std"))]
+#[inline]
+fn shard_index() -> usize {
+    // Best-effort: threads have different stacks, so the address of a local
+    // variable usually differs between threads. Ignore the lower bits, which
+    // change with the call depth, and fold the higher bits in, since thread
+    // stacks are often aligned to large boundaries. This is not guaranteed to
+    // distribute threads over the shards (see the ShardedCounter docs).
+    let local = 0_u8;
+    let addr = &local as *const u8 as usize;
+    (addr >> 10) ^ (addr >> 16) ^ (addr >> 22)
+}
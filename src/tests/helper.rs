@@ -38,6 +38,18 @@ macro_rules! __test_atomic_pub_common {
             assert_eq!(<$atomic_type>::IS_ALWAYS_LOCK_FREE, <$atomic_type>::is_always_lock_free());
         }
         #[test]
+        fn is_address_free() {
+            let is_address_free = <$atomic_type>::is_address_free();
+            if <$atomic_type>::is_always_lock_free() {
+                // Types implemented using native atomic instructions are always address-free.
+                assert!(is_address_free);
+            }
+            if cfg!(feature = "require-address-free") {
+                // Types that may rely on process-local locks are not provided.
+                assert!(is_address_free);
+            }
+        }
+        #[test]
         fn assert_ref_unwind_safe() {
             #[cfg(not(all(portable_atomic_no_core_unwind_safe, not(feature = "std"))))]
             static_assertions::assert_impl_all!($atomic_type: std::panic::RefUnwindSafe);
@@ -1450,6 +1462,77 @@ macro_rules! __test_atomic_int_pub {
             }
         }
         #[test]
+        fn fetch_saturating_checked() {
+            for &order in &helper::SWAP_ORDERINGS {
+                let a = <$atomic_type>::new(<$int_type>::MAX - 1);
+                assert_eq!(a.fetch_saturating_add(1, order), <$int_type>::MAX - 1);
+                assert_eq!(a.fetch_saturating_add(1, order), <$int_type>::MAX);
+                assert_eq!(a.load(Ordering::Relaxed), <$int_type>::MAX);
+                assert_eq!(a.fetch_checked_add(1, order), Err(<$int_type>::MAX));
+                assert_eq!(a.load(Ordering::Relaxed), <$int_type>::MAX);
+                assert_eq!(a.fetch_checked_sub(1, order), Ok(<$int_type>::MAX));
+                assert_eq!(a.fetch_checked_add(1, order), Ok(<$int_type>::MAX - 1));
+                let a = <$atomic_type>::new(<$int_type>::MIN + 1);
+                assert_eq!(a.fetch_saturating_sub(1, order), <$int_type>::MIN + 1);
+                assert_eq!(a.fetch_saturating_sub(1, order), <$int_type>::MIN);
+                assert_eq!(a.load(Ordering::Relaxed), <$int_type>::MIN);
+                assert_eq!(a.fetch_checked_sub(1, order), Err(<$int_type>::MIN));
+                assert_eq!(a.load(Ordering::Relaxed), <$int_type>::MIN);
+                assert_eq!(a.fetch_checked_add(1, order), Ok(<$int_type>::MIN));
+                assert_eq!(a.fetch_checked_sub(1, order), Ok(<$int_type>::MIN + 1));
+            }
+        }
+        ::quickcheck::quickcheck! {
+            fn quickcheck_fetch_saturating_checked(x: $int_type, y: $int_type) -> bool {
+                for &order in &helper::SWAP_ORDERINGS {
+                    let a = <$atomic_type>::new(x);
+                    assert_eq!(a.fetch_saturating_add(y, order), x);
+                    assert_eq!(a.load(Ordering::Relaxed), x.saturating_add(y));
+                    let a = <$atomic_type>::new(x);
+                    assert_eq!(a.fetch_saturating_sub(y, order), x);
+                    assert_eq!(a.load(Ordering::Relaxed), x.saturating_sub(y));
+                    let a = <$atomic_type>::new(x);
+                    assert_eq!(a.fetch_checked_add(y, order), x.checked_add(y).map(|_| x).ok_or(x));
+                    assert_eq!(a.load(Ordering::Relaxed), x.checked_add(y).unwrap_or(x));
+                    let a = <$atomic_type>::new(x);
+                    assert_eq!(a.fetch_checked_sub(y, order), x.checked_sub(y).map(|_| x).ok_or(x));
+                    assert_eq!(a.load(Ordering::Relaxed), x.checked_sub(y).unwrap_or(x));
+                }
+                true
+            }
+        }
+        #[test]
+        fn fetch_update_with() {
+            let a = <$atomic_type>::new(7);
+            test_compare_exchange_ordering(|set, fetch| {
+                a.fetch_update_with(set, fetch, &mut crate::Backoff::new(), 0, |x| Some(x))
+            });
+            for &(success, failure) in &helper::COMPARE_EXCHANGE_ORDERINGS {
+                let mut backoff = crate::Backoff::new();
+                let a = <$atomic_type>::new(7);
+                let err = a.fetch_update_with(success, failure, &mut backoff, 0, |_| None);
+                assert!(!err.unwrap_err().is_exhausted());
+                assert_eq!(err.unwrap_err().into_inner(), 7);
+                assert_eq!(
+                    a.fetch_update_with(success, failure, &mut backoff, 0, |x| Some(x + 1)),
+                    Ok(7)
+                );
+                // The value is changed by the function itself, so every CAS fails.
+                let mut calls = 0;
+                let err = a
+                    .fetch_update_with(success, failure, &mut backoff, 2, |x| {
+                        calls += 1;
+                        a.store(x + 1, Ordering::Relaxed);
+                        Some(x)
+                    })
+                    .unwrap_err();
+                assert!(err.is_exhausted());
+                assert_eq!(err.into_inner(), 11);
+                assert_eq!(calls, 3);
+                assert_eq!(a.load(Ordering::SeqCst), 11);
+            }
+        }
+        #[test]
         fn impls() {
             #[cfg(not(portable_atomic_no_const_transmute))]
             const INTO_INNER: $int_type = {
@@ -1489,6 +1572,102 @@ macro_rules! __test_atomic_int_pub {
                 drop(Box::from_raw(ptr));
             }
         }
+        #[test]
+        fn mut_conversions() {
+            let mut v = Align16(1);
+            <$atomic_type>::from_mut(&mut v.0).store(5, Ordering::Relaxed);
+            assert_eq!(v.0, 5);
+            let mut v = Align16([0 as $int_type; 4]);
+            {
+                let a = <$atomic_type>::from_mut_slice(&mut v.0);
+                a[1].store(1, Ordering::Relaxed);
+                assert_eq!(<$atomic_type>::get_mut_slice(a)[1], 1);
+                <$atomic_type>::get_mut_slice(a)[2] = 5;
+                assert_eq!(a[2].load(Ordering::Relaxed), 5);
+            }
+            assert_eq!(v.0[1], 1);
+            assert_eq!(v.0[2], 5);
+            assert!(<$atomic_type>::from_mut_slice(&mut []).is_empty());
+            if mem::align_of::<$int_type>() < mem::align_of::<$atomic_type>()
+                && !skip_should_panic_test()
+            {
+                let mut buf = Align16([0 as $int_type; 4]);
+                // aligned for $int_type, but not for $atomic_type
+                let ptr = unsafe {
+                    (buf.0.as_mut_ptr() as *mut u8).add(mem::align_of::<$int_type>())
+                        as *mut $int_type
+                };
+                let msg =
+                    std::format!("v is not aligned to align_of::<{}>()", stringify!($atomic_type));
+                assert_eq!(assert_panic(|| <$atomic_type>::from_mut(unsafe { &mut *ptr })), msg);
+                assert_eq!(
+                    assert_panic(|| <$atomic_type>::from_mut_slice(unsafe {
+                        std::slice::from_raw_parts_mut(ptr, 2)
+                    })),
+                    msg
+                );
+            }
+        }
+        #[test]
+        fn slice_load_store() {
+            let a: std::vec::Vec<$atomic_type> =
+                (0..40).map(|i| <$atomic_type>::new(i as $int_type)).collect();
+            let mut buf = [0 as $int_type; 40];
+            for start in 0..9 {
+                for end in start..a.len() {
+                    let a = &a[start..end];
+                    for &order in &helper::LOAD_ORDERINGS {
+                        let buf = &mut buf[..a.len()];
+                        <$atomic_type>::load_slice(a, buf, order);
+                        assert!(buf.iter().zip(a).all(|(&b, a)| b == a.load(Ordering::Relaxed)));
+                        let buf = &mut [0 as $int_type; 40][..a.len()];
+                        unsafe { <$atomic_type>::load_slice_chunked(a, buf, order) }
+                        assert!(buf.iter().zip(a).all(|(&b, a)| b == a.load(Ordering::Relaxed)));
+                    }
+                    for &order in &helper::STORE_ORDERINGS {
+                        let src: std::vec::Vec<$int_type> =
+                            (0..a.len()).map(|_| fastrand::$int_type(..)).collect();
+                        <$atomic_type>::store_slice(a, &src, order);
+                        assert!(src.iter().zip(a).all(|(&s, a)| s == a.load(Ordering::Relaxed)));
+                        let src: std::vec::Vec<$int_type> =
+                            (0..a.len()).map(|_| fastrand::$int_type(..)).collect();
+                        unsafe { <$atomic_type>::store_slice_chunked(a, &src, order) }
+                        assert!(src.iter().zip(a).all(|(&s, a)| s == a.load(Ordering::Relaxed)));
+                    }
+                }
+            }
+            if !skip_should_panic_test() {
+                let msg = "source slice length (2) does not match destination slice length (1)";
+                assert_eq!(
+                    assert_panic(|| <$atomic_type>::load_slice(
+                        &a[..2],
+                        &mut [0],
+                        Ordering::Relaxed
+                    )),
+                    msg
+                );
+                assert_eq!(
+                    assert_panic(|| <$atomic_type>::store_slice(
+                        &a[..1],
+                        &[0, 0],
+                        Ordering::Relaxed
+                    )),
+                    msg
+                );
+                assert_eq!(
+                    assert_panic(|| unsafe {
+                        <$atomic_type>::load_slice_chunked(&a[..2], &mut [0], Ordering::Relaxed)
+                    }),
+                    msg
+                );
+                assert_eq!(
+                    assert_panic(|| unsafe {
+                        <$atomic_type>::store_slice_chunked(&a[..1], &[0, 0], Ordering::Relaxed)
+                    }),
+                    msg
+                );
+            }
+        }
         ::quickcheck::quickcheck! {
             fn quickcheck_fetch_update(x: $int_type, y: $int_type) -> bool {
                 let z = loop {
@@ -1539,6 +1718,37 @@ macro_rules! __test_atomic_float_pub {
             }
         }
         #[test]
+        fn fetch_update_with() {
+            let a = <$atomic_type>::new(7.);
+            test_compare_exchange_ordering(|set, fetch| {
+                a.fetch_update_with(set, fetch, &mut crate::Backoff::new(), 0, |x| Some(x))
+            });
+            for &(success, failure) in &helper::COMPARE_EXCHANGE_ORDERINGS {
+                let mut backoff = crate::Backoff::new();
+                let a = <$atomic_type>::new(7.);
+                let err = a.fetch_update_with(success, failure, &mut backoff, 0, |_| None);
+                assert!(!err.unwrap_err().is_exhausted());
+                assert_eq!(err.unwrap_err().into_inner(), 7.);
+                assert_eq!(
+                    a.fetch_update_with(success, failure, &mut backoff, 0, |x| Some(x + 1.)),
+                    Ok(7.)
+                );
+                // The value is changed by the function itself, so every CAS fails.
+                let mut calls = 0;
+                let err = a
+                    .fetch_update_with(success, failure, &mut backoff, 2, |x| {
+                        calls += 1;
+                        a.store(x + 1, Ordering::Relaxed);
+                        Some(x)
+                    })
+                    .unwrap_err();
+                assert!(err.is_exhausted());
+                assert_eq!(err.into_inner(), 11.);
+                assert_eq!(calls, 3);
+                assert_eq!(a.load(Ordering::SeqCst), 11.);
+            }
+        }
+        #[test]
         fn impls() {
             #[cfg(not(portable_atomic_no_const_transmute))]
             const INTO_INNER: $float_type = {
@@ -1578,6 +1788,42 @@ macro_rules! __test_atomic_float_pub {
                 drop(Box::from_raw(ptr));
             }
         }
+        #[test]
+        fn mut_conversions() {
+            let mut v = Align16(1.);
+            <$atomic_type>::from_mut(&mut v.0).store(5., Ordering::Relaxed);
+            assert_eq!(v.0, 5.);
+            let mut v = Align16([0. as $float_type; 4]);
+            {
+                let a = <$atomic_type>::from_mut_slice(&mut v.0);
+                a[1].store(1., Ordering::Relaxed);
+                assert_eq!(<$atomic_type>::get_mut_slice(a)[1], 1.);
+                <$atomic_type>::get_mut_slice(a)[2] = 5.;
+                assert_eq!(a[2].load(Ordering::Relaxed), 5.);
+            }
+            assert_eq!(v.0[1], 1.);
+            assert_eq!(v.0[2], 5.);
+            assert!(<$atomic_type>::from_mut_slice(&mut []).is_empty());
+            if mem::align_of::<$float_type>() < mem::align_of::<$atomic_type>()
+                && !skip_should_panic_test()
+            {
+                let mut buf = Align16([0. as $float_type; 4]);
+                // aligned for $float_type, but not for $atomic_type
+                let ptr = unsafe {
+                    (buf.0.as_mut_ptr() as *mut u8).add(mem::align_of::<$float_type>())
+                        as *mut $float_type
+                };
+                let msg =
+                    std::format!("v is not aligned to align_of::<{}>()", stringify!($atomic_type));
+                assert_eq!(assert_panic(|| <$atomic_type>::from_mut(unsafe { &mut *ptr })), msg);
+                assert_eq!(
+                    assert_panic(|| <$atomic_type>::from_mut_slice(unsafe {
+                        std::slice::from_raw_parts_mut(ptr, 2)
+                    })),
+                    msg
+                );
+            }
+        }
     };
 }
 macro_rules! __test_atomic_bool_pub {
@@ -1643,6 +1889,36 @@ macro_rules! __test_atomic_bool_pub {
             }
         }
         #[test]
+        fn fetch_update_with() {
+            let a = <$atomic_type>::new(false);
+            test_compare_exchange_ordering(|set, fetch| {
+                a.fetch_update_with(set, fetch, &mut crate::Backoff::new(), 0, |x| Some(x))
+            });
+            for &(success, failure) in &helper::COMPARE_EXCHANGE_ORDERINGS {
+                let mut backoff = crate::Backoff::new();
+                let a = <$atomic_type>::new(false);
+                let err = a.fetch_update_with(success, failure, &mut backoff, 0, |_| None);
+                assert!(!err.unwrap_err().is_exhausted());
+                assert_eq!(err.unwrap_err().into_inner(), false);
+                assert_eq!(
+                    a.fetch_update_with(success, failure, &mut backoff, 0, |x| Some(!x)),
+                    Ok(false)
+                );
+                // The value is changed by the function itself, so every CAS fails.
+                let mut calls = 0;
+                let err = a
+                    .fetch_update_with(success, failure, &mut backoff, 2, |x| {
+                        calls += 1;
+                        a.store(!x, Ordering::Relaxed);
+                        Some(x)
+                    })
+                    .unwrap_err();
+                assert!(err.is_exhausted());
+                assert_eq!(err.into_inner(), false);
+                assert_eq!(calls, 3);
+            }
+        }
+        #[test]
         fn impls() {
             #[cfg(not(portable_atomic_no_const_transmute))]
             const INTO_INNER: bool = {
@@ -1682,6 +1958,22 @@ macro_rules! __test_atomic_bool_pub {
                 drop(Box::from_raw(ptr));
             }
         }
+        #[test]
+        fn mut_conversions() {
+            let mut v = false;
+            <$atomic_type>::from_mut(&mut v).store(true, Ordering::Relaxed);
+            assert_eq!(v, true);
+            let mut v = [false; 4];
+            {
+                let a = <$atomic_type>::from_mut_slice(&mut v);
+                a[1].store(true, Ordering::Relaxed);
+                assert_eq!(<$atomic_type>::get_mut_slice(a)[1], true);
+                <$atomic_type>::get_mut_slice(a)[2] = true;
+                assert_eq!(a[2].load(Ordering::Relaxed), true);
+            }
+            assert_eq!(v, [false, true, true, false]);
+            assert!(<$atomic_type>::from_mut_slice(&mut []).is_empty());
+        }
     };
 }
 macro_rules! __test_atomic_ptr_pub {
@@ -1705,6 +1997,27 @@ macro_rules! __test_atomic_ptr_pub {
             }
         }
         #[test]
+        fn fetch_update_with() {
+            let a = <$atomic_type>::new(ptr::null_mut());
+            test_compare_exchange_ordering(|set, fetch| {
+                a.fetch_update_with(set, fetch, &mut crate::Backoff::new(), 0, |x| Some(x))
+            });
+            for &(success, failure) in &helper::COMPARE_EXCHANGE_ORDERINGS {
+                let mut backoff = crate::Backoff::new();
+                let a = <$atomic_type>::new(ptr::null_mut());
+                let err = a.fetch_update_with(success, failure, &mut backoff, 0, |_| None);
+                assert!(!err.unwrap_err().is_exhausted());
+                assert_eq!(err.unwrap_err().into_inner(), ptr::null_mut());
+                assert_eq!(
+                    a.fetch_update_with(success, failure, &mut backoff, 0, |_| {
+                        Some(&a as *const _ as *mut _)
+                    }),
+                    Ok(ptr::null_mut())
+                );
+                assert_eq!(a.load(Ordering::SeqCst), &a as *const _ as *mut _);
+            }
+        }
+        #[test]
         fn impls() {
             #[cfg(not(portable_atomic_no_const_transmute))]
             const INTO_INNER: *mut u8 = {
@@ -1745,6 +2058,25 @@ macro_rules! __test_atomic_ptr_pub {
                 drop(Box::from_raw(ptr));
             }
         }
+        #[test]
+        fn mut_conversions() {
+            let mut x = 1_u8;
+            let mut v = ptr::null_mut::<u8>();
+            <$atomic_type>::from_mut(&mut v).store(&mut x, Ordering::Relaxed);
+            assert_eq!(v, &mut x as *mut u8);
+            let mut v = [ptr::null_mut::<u8>(); 4];
+            {
+                let a = <$atomic_type>::from_mut_slice(&mut v);
+                a[1].store(&mut x, Ordering::Relaxed);
+                assert_eq!(<$atomic_type>::get_mut_slice(a)[1], &mut x as *mut u8);
+                <$atomic_type>::get_mut_slice(a)[2] = &mut x;
+                assert_eq!(a[2].load(Ordering::Relaxed), &mut x as *mut u8);
+            }
+            assert!(v[0].is_null());
+            assert_eq!(v[1], &mut x as *mut u8);
+            assert_eq!(v[2], &mut x as *mut u8);
+            assert!(<$atomic_type>::from_mut_slice(&mut []).is_empty());
+        }
         // https://github.com/rust-lang/rust/blob/1.80.0/library/core/tests/atomic.rs#L130-L213
         #[test]
         fn ptr_add_null() {
@@ -2170,7 +2502,7 @@ pub(crate) fn stress_test_config() -> (usize, usize) {
     std::eprintln!("threads={}", threads);
     (iterations, threads)
 }
-fn skip_should_panic_test() -> bool {
+pub(crate) fn skip_should_panic_test() -> bool {
     // Miri's panic handling is slow
     // MSAN false positive: https://gist.github.com/taiki-e/dd6269a8ffec46284fdc764a4849f884
     is_panic_abort()
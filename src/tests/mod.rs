@@ -54,6 +54,10 @@ extern "C" {
     fn _atomic_u32_ffi_safety(_: AtomicU32);
     fn _atomic_i64_ffi_safety(_: AtomicI64);
     fn _atomic_u64_ffi_safety(_: AtomicU64);
+    fn _atomic_non_null_ffi_safety(_: AtomicNonNull<u8>);
+    fn _atomic_option_non_null_ffi_safety(_: AtomicOptionNonNull<u8>);
+    fn _atomic_non_zero_usize_ffi_safety(_: AtomicNonZeroUsize);
+    fn _atomic_non_zero_u32_ffi_safety(_: AtomicNonZeroU32);
     // TODO: 128-bit integers are not FFI safe
     // https://github.com/rust-lang/unsafe-code-guidelines/issues/119
     // https://github.com/rust-lang/rust/issues/54341
@@ -65,6 +69,174 @@ extern "C" {
     fn _atomic_f64_ffi_safety(_: AtomicF64);
 }
 
+#[test]
+fn test_non_null() {
+    let mut a = 1_u8;
+    let mut b = 2_u8;
+    let pa = core::ptr::NonNull::from(&mut a);
+    let pb = core::ptr::NonNull::from(&mut b);
+
+    let x = AtomicNonNull::new(pa);
+    assert_eq!(x.load(Ordering::Relaxed), pa);
+    x.store(pb, Ordering::Relaxed);
+    assert_eq!(x.swap(pa, Ordering::AcqRel), pb);
+    assert_eq!(x.compare_exchange(pb, pb, Ordering::AcqRel, Ordering::Acquire), Err(pa));
+    assert_eq!(x.compare_exchange(pa, pb, Ordering::AcqRel, Ordering::Acquire), Ok(pa));
+    assert_eq!(x.fetch_update(Ordering::AcqRel, Ordering::Acquire, |_| Some(pa)), Ok(pb));
+    assert_eq!(std::format!("{:?}", x), std::format!("{:?}", pa));
+    assert_eq!(x.into_inner(), pa);
+
+    static NONE: AtomicOptionNonNull<u8> = AtomicOptionNonNull::none();
+    assert_eq!(NONE.load(Ordering::Relaxed), None);
+    let x = AtomicOptionNonNull::default();
+    assert_eq!(x.load(Ordering::Relaxed), None);
+    assert_eq!(x.swap(Some(pa), Ordering::AcqRel), None);
+    assert_eq!(x.as_atomic_ptr().load(Ordering::Relaxed), pa.as_ptr());
+    assert_eq!(
+        x.compare_exchange(None, Some(pb), Ordering::AcqRel, Ordering::Acquire),
+        Err(Some(pa))
+    );
+    assert_eq!(
+        x.compare_exchange(Some(pa), Some(pb), Ordering::AcqRel, Ordering::Acquire),
+        Ok(Some(pa))
+    );
+    assert_eq!(x.take(Ordering::AcqRel), Some(pb));
+    assert_eq!(x.fetch_update(Ordering::AcqRel, Ordering::Acquire, |_| None), Err(None));
+    x.store(Some(pa), Ordering::Relaxed);
+    assert_eq!(x.into_inner(), Some(pa));
+}
+
+#[test]
+fn test_non_zero() {
+    macro_rules! t {
+        ($atomic_type:ident, $non_zero_type:ident, $int_type:ident) => {{
+            let one = core::num::$non_zero_type::new(1).unwrap();
+            let two = core::num::$non_zero_type::new(2).unwrap();
+            let max = core::num::$non_zero_type::new($int_type::MAX).unwrap();
+            let x = $atomic_type::new(one);
+            assert_eq!(x.load(Ordering::Relaxed), one);
+            x.store(two, Ordering::Relaxed);
+            assert_eq!(x.swap(one, Ordering::AcqRel), two);
+            assert_eq!(x.compare_exchange(two, two, Ordering::AcqRel, Ordering::Acquire), Err(one));
+            assert_eq!(x.compare_exchange(one, two, Ordering::AcqRel, Ordering::Acquire), Ok(one));
+            assert_eq!(x.fetch_max(max, Ordering::AcqRel), two);
+            assert_eq!(x.fetch_min(one, Ordering::AcqRel), max);
+            assert_eq!(
+                x.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| {
+                    core::num::$non_zero_type::new(v.get() + 1)
+                }),
+                Ok(one)
+            );
+            assert_eq!(std::format!("{:?}", x), std::format!("{:?}", two));
+            assert_eq!(x.into_inner(), two);
+        }};
+    }
+    t!(AtomicNonZeroIsize, NonZeroIsize, isize);
+    t!(AtomicNonZeroUsize, NonZeroUsize, usize);
+    t!(AtomicNonZeroI8, NonZeroI8, i8);
+    t!(AtomicNonZeroU8, NonZeroU8, u8);
+    t!(AtomicNonZeroI16, NonZeroI16, i16);
+    t!(AtomicNonZeroU16, NonZeroU16, u16);
+    t!(AtomicNonZeroI32, NonZeroI32, i32);
+    t!(AtomicNonZeroU32, NonZeroU32, u32);
+    t!(AtomicNonZeroI64, NonZeroI64, i64);
+    t!(AtomicNonZeroU64, NonZeroU64, u64);
+    t!(AtomicNonZeroI128, NonZeroI128, i128);
+    t!(AtomicNonZeroU128, NonZeroU128, u128);
+}
+
+#[test]
+fn test_duration() {
+    use core::time::Duration;
+    let one = Duration::new(1, 1);
+    let two = Duration::new(2, 0);
+    let max = Duration::from_nanos(u64::MAX);
+    let x = AtomicDuration::new(one);
+    assert_eq!(x.load(Ordering::Relaxed), one);
+    x.store(two, Ordering::Relaxed);
+    assert_eq!(x.swap(one, Ordering::AcqRel), two);
+    assert_eq!(x.compare_exchange(two, two, Ordering::AcqRel, Ordering::Acquire), Err(one));
+    assert_eq!(x.compare_exchange(one, two, Ordering::AcqRel, Ordering::Acquire), Ok(one));
+    assert_eq!(x.fetch_max(one, Ordering::AcqRel), two);
+    assert_eq!(x.fetch_max(max, Ordering::AcqRel), two);
+    assert_eq!(x.fetch_min(one, Ordering::AcqRel), max);
+    assert_eq!(
+        x.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| v.checked_add(one)),
+        Ok(one)
+    );
+    assert_eq!(std::format!("{:?}", x), std::format!("{:?}", one + one));
+    assert_eq!(x.into_inner(), one + one);
+    assert_eq!(AtomicDuration::default().into_inner(), Duration::default());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_instant() {
+    use std::time::{Duration, Instant};
+    let base = Instant::now();
+    let before = base.checked_sub(Duration::from_secs(1)).unwrap_or(base);
+    let after = base + Duration::new(1, 1);
+    let x = AtomicInstant::new(base);
+    assert_eq!(x.load(Ordering::Relaxed), base);
+    x.store(before, Ordering::Relaxed);
+    assert_eq!(x.load(Ordering::Relaxed), before);
+    assert_eq!(x.swap(after, Ordering::AcqRel), before);
+    assert_eq!(x.compare_exchange(base, base, Ordering::AcqRel, Ordering::Acquire), Err(after));
+    assert_eq!(x.compare_exchange(after, base, Ordering::AcqRel, Ordering::Acquire), Ok(after));
+    assert_eq!(x.fetch_max(before, Ordering::AcqRel), base);
+    assert_eq!(x.fetch_max(after, Ordering::AcqRel), base);
+    assert_eq!(x.fetch_min(before, Ordering::AcqRel), after);
+    assert_eq!(
+        x.fetch_update(Ordering::AcqRel, Ordering::Acquire, |v| Some(v + Duration::from_secs(1))),
+        Ok(before)
+    );
+    assert_eq!(std::format!("{:?}", x), std::format!("{:?}", before + Duration::from_secs(1)));
+    assert_eq!(x.into_inner(), before + Duration::from_secs(1));
+}
+
+#[test]
+fn test_cache_padded() {
+    use core::mem;
+    let align = mem::align_of::<CachePadded<u8>>();
+    assert!(align.is_power_of_two());
+    assert!(align >= 16);
+    assert_eq!(mem::size_of::<CachePadded<u8>>(), align);
+    assert_eq!(mem::size_of::<CachePadded<[u8; 17]>>(), align * ((17 + align - 1) / align));
+    assert_eq!(mem::align_of::<CachePadded<AtomicU64>>(), align.max(mem::align_of::<AtomicU64>()));
+    let mut x = CachePadded::new(1_u32);
+    assert_eq!(*x, 1);
+    *x = 2;
+    assert_eq!(x, CachePadded::from(2));
+    assert_eq!(std::format!("{:?}", x), "CachePadded { value: 2 }");
+    assert_eq!(std::format!("{}", x), "2");
+    assert_eq!(x.into_inner(), 2);
+}
+
+#[test]
+fn test_sharded_counter() {
+    let counter = ShardedCounter::new();
+    assert_eq!(counter.sum(), 0);
+    counter.add(1);
+    counter.add(2);
+    assert_eq!(counter.sum(), 3);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..1000 {
+                    counter.add(1);
+                }
+            });
+        }
+    });
+    assert_eq!(counter.sum(), 4003);
+    assert_eq!(std::format!("{:?}", counter), "ShardedCounter { sum: 4003 }");
+    counter.reset();
+    assert_eq!(counter.sum(), 0);
+    counter.add(u64::MAX);
+    counter.add(2);
+    assert_eq!(counter.sum(), 1);
+}
+
 #[test]
 fn test_is_lock_free() {
     assert!(AtomicI8::is_always_lock_free());
@@ -170,6 +342,9 @@ fn test_is_lock_free() {
         {
             assert!(!AtomicI128::is_lock_free());
             assert!(!AtomicU128::is_lock_free());
+            // The lock-based fallback implementation is not address-free.
+            assert!(!AtomicI128::is_address_free());
+            assert!(!AtomicU128::is_address_free());
         }
         #[cfg(target_arch = "x86_64")]
         {
@@ -181,6 +356,10 @@ fn test_is_lock_free() {
             )) && std::is_x86_feature_detected!("cmpxchg16b");
             assert_eq!(AtomicI128::is_lock_free(), has_cmpxchg16b);
             assert_eq!(AtomicU128::is_lock_free(), has_cmpxchg16b);
+            // The lock-based fallback implementation (used if cmpxchg16b is not
+            // available) is not address-free.
+            assert_eq!(AtomicI128::is_address_free(), has_cmpxchg16b);
+            assert_eq!(AtomicU128::is_address_free(), has_cmpxchg16b);
         }
         #[cfg(target_arch = "powerpc64")]
         {
@@ -0,0 +1,557 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Atomic `Duration` and `Instant` types.
+//
+// A duration is packed into an integer so that the order of durations is the
+// same as the order of the packed integers, which makes fetch_max/fetch_min
+// work on the underlying atomic integer type.
+//
+// - If AtomicU128 is available, a duration is packed as `secs << 32 | nanos`,
+//   which can represent all durations.
+// - Otherwise, a duration is packed as the number of nanoseconds in AtomicU64,
+//   and durations that do not fit saturate.
+
+use core::{fmt, time::Duration};
+#[cfg(feature = "std")]
+use std::time::Instant;
+
+use crate::Ordering;
+
+cfg_has_atomic_128! {
+    type Packed = u128;
+    type AtomicPacked = crate::AtomicU128;
+
+    // Instants are packed as `INSTANT_BIAS + offset` or `INSTANT_BIAS - offset`,
+    // where offset is a packed duration, which is less than INSTANT_BIAS.
+    #[cfg(feature = "std")]
+    const INSTANT_BIAS: Packed = 1 << 96;
+
+    #[inline]
+    const fn pack(d: Duration) -> Packed {
+        ((d.as_secs() as u128) << 32) | d.subsec_nanos() as u128
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline]
+    fn unpack(v: Packed) -> Duration {
+        // The lower 32 bits are always less than 1_000_000_000, so this never panics.
+        Duration::new((v >> 32) as u64, v as u32)
+    }
+}
+cfg_no_atomic_128! {
+    type Packed = u64;
+    type AtomicPacked = crate::AtomicU64;
+
+    // Instants are packed as `INSTANT_BIAS + offset` or `INSTANT_BIAS - offset`,
+    // where offset is a packed duration, which saturates at INSTANT_BIAS - 1.
+    #[cfg(feature = "std")]
+    const INSTANT_BIAS: Packed = 1 << 63;
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[inline]
+    const fn pack(d: Duration) -> Packed {
+        let nanos = d.as_nanos();
+        // Saturate at u64::MAX without branches, which are not allowed in const fn on old compilers.
+        nanos as u64 | (((nanos >> 64) != 0) as u64).wrapping_neg()
+    }
+    #[inline]
+    fn unpack(v: Packed) -> Duration {
+        Duration::from_nanos(v)
+    }
+}
+
+/// A [`Duration`] which can be safely shared between threads.
+///
+/// If [`AtomicU128`](crate::AtomicU128) is available, this type is implemented
+/// using it, and can represent all durations. Otherwise, this type is
+/// implemented using [`AtomicU64`](crate::AtomicU64), which stores the number
+/// of nanoseconds, so durations longer than `u64::MAX` nanoseconds (about 584
+/// years) saturate.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use portable_atomic::{AtomicDuration, Ordering};
+///
+/// // Tracks the longest latency seen so far.
+/// static MAX_LATENCY: AtomicDuration = AtomicDuration::new(Duration::from_secs(0));
+///
+/// MAX_LATENCY.fetch_max(Duration::from_millis(5), Ordering::Relaxed);
+/// MAX_LATENCY.fetch_max(Duration::from_millis(3), Ordering::Relaxed);
+/// assert_eq!(MAX_LATENCY.load(Ordering::Relaxed), Duration::from_millis(5));
+/// ```
+#[repr(transparent)]
+pub struct AtomicDuration {
+    inner: AtomicPacked,
+}
+
+impl Default for AtomicDuration {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Duration::default())
+    }
+}
+
+impl From<Duration> for AtomicDuration {
+    #[inline]
+    fn from(v: Duration) -> Self {
+        Self::new(v)
+    }
+}
+
+impl fmt::Debug for AtomicDuration {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+impl AtomicDuration {
+    const_fn! {
+        const_if: #[cfg(not(portable_atomic_loom))];
+        /// Creates a new atomic duration.
+        #[inline]
+        #[must_use]
+        pub const fn new(v: Duration) -> Self {
+            Self { inner: AtomicPacked::new(pack(v)) }
+        }
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicPacked::is_lock_free()
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// **Note:** If the atomic operation relies on dynamic CPU feature detection,
+    /// this type may be lock-free even if the function returns false.
+    #[inline]
+    #[must_use]
+    pub const fn is_always_lock_free() -> bool {
+        AtomicPacked::is_always_lock_free()
+    }
+
+    /// Consumes the atomic and returns the contained value.
+    #[inline]
+    pub fn into_inner(self) -> Duration {
+        unpack(self.inner.into_inner())
+    }
+
+    /// Loads a value from the atomic duration.
+    ///
+    /// `load` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn load(&self, order: Ordering) -> Duration {
+        unpack(self.inner.load(order))
+    }
+
+    /// Stores a value into the atomic duration.
+    ///
+    /// `store` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn store(&self, val: Duration, order: Ordering) {
+        self.inner.store(pack(val), order);
+    }
+
+    cfg_has_atomic_cas! {
+    /// Stores a value into the atomic duration, returning the previous value.
+    ///
+    /// `swap` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn swap(&self, val: Duration, order: Ordering) -> Duration {
+        unpack(self.inner.swap(pack(val), order))
+    }
+
+    /// Stores a value into the atomic duration if the current value is the same as
+    /// the `current` value.
+    ///
+    /// The return value is a result indicating whether the new value was written and
+    /// containing the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange(
+        &self,
+        current: Duration,
+        new: Duration,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Duration, Duration> {
+        match self.inner.compare_exchange(pack(current), pack(new), success, failure) {
+            Ok(v) => Ok(unpack(v)),
+            Err(v) => Err(unpack(v)),
+        }
+    }
+
+    /// Stores a value into the atomic duration if the current value is the same as
+    /// the `current` value.
+    ///
+    /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+    /// allowed to spuriously fail even when the comparison succeeds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange_weak(
+        &self,
+        current: Duration,
+        new: Duration,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Duration, Duration> {
+        match self.inner.compare_exchange_weak(pack(current), pack(new), success, failure) {
+            Ok(v) => Ok(unpack(v)),
+            Err(v) => Err(unpack(v)),
+        }
+    }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+    /// returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// See also the `fetch_update` method of the underlying atomic integer type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<Duration, Duration>
+    where
+        F: FnMut(Duration) -> Option<Duration>,
+    {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+
+    /// Maximum with the current value, returning the previous value.
+    ///
+    /// `fetch_max` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn fetch_max(&self, val: Duration, order: Ordering) -> Duration {
+        unpack(self.inner.fetch_max(pack(val), order))
+    }
+
+    /// Minimum with the current value, returning the previous value.
+    ///
+    /// `fetch_min` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn fetch_min(&self, val: Duration, order: Ordering) -> Duration {
+        unpack(self.inner.fetch_min(pack(val), order))
+    }
+    } // cfg_has_atomic_cas!
+}
+
+/// An [`Instant`] which can be safely shared between threads.
+///
+/// This type stores the offset from the instant passed to [`new`](Self::new),
+/// using the same representation as [`AtomicDuration`]. If
+/// [`AtomicU128`](crate::AtomicU128) is not available, offsets longer than
+/// about 292 years saturate.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use portable_atomic::{AtomicInstant, Ordering};
+///
+/// let start = Instant::now();
+/// // Tracks the last time an event was seen.
+/// let last_seen = AtomicInstant::new(start);
+///
+/// last_seen.fetch_max(start + Duration::from_secs(2), Ordering::Relaxed);
+/// last_seen.fetch_max(start + Duration::from_secs(1), Ordering::Relaxed);
+/// assert_eq!(last_seen.load(Ordering::Relaxed), start + Duration::from_secs(2));
+/// ```
+#[cfg(feature = "std")]
+#[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+pub struct AtomicInstant {
+    base: Instant,
+    offset: AtomicPacked,
+}
+
+#[cfg(feature = "std")]
+impl From<Instant> for AtomicInstant {
+    #[inline]
+    fn from(v: Instant) -> Self {
+        Self::new(v)
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Debug for AtomicInstant {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // std atomic types use Relaxed in Debug::fmt: https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L2166
+        fmt::Debug::fmt(&self.load(Ordering::Relaxed), f)
+    }
+}
+
+#[cfg(feature = "std")]
+impl AtomicInstant {
+    /// Creates a new atomic instant.
+    #[inline]
+    #[must_use]
+    pub fn new(v: Instant) -> Self {
+        Self { base: v, offset: AtomicPacked::new(INSTANT_BIAS) }
+    }
+
+    #[inline]
+    fn pack(&self, v: Instant) -> Packed {
+        if v >= self.base {
+            INSTANT_BIAS + pack(v - self.base).min(INSTANT_BIAS - 1)
+        } else {
+            INSTANT_BIAS - pack(self.base - v).min(INSTANT_BIAS - 1)
+        }
+    }
+    #[inline]
+    fn unpack(&self, v: Packed) -> Instant {
+        if v >= INSTANT_BIAS {
+            self.base + unpack(v - INSTANT_BIAS)
+        } else {
+            // The offset is never larger than the offset of an existing instant, so this never fails.
+            self.base
+                .checked_sub(unpack(INSTANT_BIAS - v))
+                .expect("overflow when subtracting duration from instant")
+        }
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    #[inline]
+    #[must_use]
+    pub fn is_lock_free() -> bool {
+        AtomicPacked::is_lock_free()
+    }
+
+    /// Returns `true` if operations on values of this type are lock-free.
+    ///
+    /// **Note:** If the atomic operation relies on dynamic CPU feature detection,
+    /// this type may be lock-free even if the function returns false.
+    #[inline]
+    #[must_use]
+    pub const fn is_always_lock_free() -> bool {
+        AtomicPacked::is_always_lock_free()
+    }
+
+    /// Consumes the atomic and returns the contained value.
+    #[inline]
+    pub fn into_inner(self) -> Instant {
+        let offset = self.offset.into_inner();
+        Self { base: self.base, offset: AtomicPacked::new(0) }.unpack(offset)
+    }
+
+    /// Loads a value from the atomic instant.
+    ///
+    /// `load` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Acquire`](Ordering::Acquire) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Release`](Ordering::Release) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn load(&self, order: Ordering) -> Instant {
+        self.unpack(self.offset.load(order))
+    }
+
+    /// Stores a value into the atomic instant.
+    ///
+    /// `store` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. Possible values are [`SeqCst`](Ordering::SeqCst),
+    /// [`Release`](Ordering::Release) and [`Relaxed`](Ordering::Relaxed).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is [`Acquire`](Ordering::Acquire) or [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn store(&self, val: Instant, order: Ordering) {
+        self.offset.store(self.pack(val), order);
+    }
+
+    cfg_has_atomic_cas! {
+    /// Stores a value into the atomic instant, returning the previous value.
+    ///
+    /// `swap` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn swap(&self, val: Instant, order: Ordering) -> Instant {
+        self.unpack(self.offset.swap(self.pack(val), order))
+    }
+
+    /// Stores a value into the atomic instant if the current value is the same as
+    /// the `current` value.
+    ///
+    /// The return value is a result indicating whether the new value was written and
+    /// containing the previous value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange(
+        &self,
+        current: Instant,
+        new: Instant,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Instant, Instant> {
+        match self.offset.compare_exchange(self.pack(current), self.pack(new), success, failure) {
+            Ok(v) => Ok(self.unpack(v)),
+            Err(v) => Err(self.unpack(v)),
+        }
+    }
+
+    /// Stores a value into the atomic instant if the current value is the same as
+    /// the `current` value.
+    ///
+    /// Unlike [`compare_exchange`](Self::compare_exchange), this function is
+    /// allowed to spuriously fail even when the comparison succeeds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `failure` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn compare_exchange_weak(
+        &self,
+        current: Instant,
+        new: Instant,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Instant, Instant> {
+        let (current, new) = (self.pack(current), self.pack(new));
+        match self.offset.compare_exchange_weak(current, new, success, failure) {
+            Ok(v) => Ok(self.unpack(v)),
+            Err(v) => Err(self.unpack(v)),
+        }
+    }
+
+    /// Fetches the value, and applies a function to it that returns an optional
+    /// new value. Returns a `Result` of `Ok(previous_value)` if the function
+    /// returned `Some(_)`, else `Err(previous_value)`.
+    ///
+    /// See also the `fetch_update` method of the underlying atomic integer type.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fetch_order` is [`Release`](Ordering::Release), [`AcqRel`](Ordering::AcqRel).
+    #[inline]
+    #[cfg_attr(
+        any(all(debug_assertions, not(portable_atomic_no_track_caller)), miri),
+        track_caller
+    )]
+    pub fn fetch_update<F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        mut f: F,
+    ) -> Result<Instant, Instant>
+    where
+        F: FnMut(Instant) -> Option<Instant>,
+    {
+        let mut prev = self.load(fetch_order);
+        while let Some(next) = f(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+
+    /// Maximum with the current value, returning the previous value.
+    ///
+    /// This is useful for tracking the last time an event was seen by multiple threads.
+    ///
+    /// `fetch_max` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn fetch_max(&self, val: Instant, order: Ordering) -> Instant {
+        self.unpack(self.offset.fetch_max(self.pack(val), order))
+    }
+
+    /// Minimum with the current value, returning the previous value.
+    ///
+    /// `fetch_min` takes an [`Ordering`] argument which describes the memory ordering
+    /// of this operation. All ordering modes are possible.
+    #[inline]
+    #[cfg_attr(miri, track_caller)] // even without panics, this helps for Miri backtraces
+    pub fn fetch_min(&self, val: Instant, order: Ordering) -> Instant {
+        self.unpack(self.offset.fetch_min(self.pack(val), order))
+    }
+    } // cfg_has_atomic_cas!
+}
@@ -0,0 +1,415 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+//! Tracing of atomic operations.
+//!
+//! This module is only available when `--cfg portable_atomic_trace` is set.
+//! See [the crate-level documentation](crate#optional-cfg-trace) for details.
+
+use core::{
+    cell::RefCell,
+    cmp, fmt, mem, ptr,
+    sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering},
+};
+use std::{
+    boxed::Box,
+    thread::{self, ThreadId},
+    vec::Vec,
+};
+
+// The maximum number of events recorded per thread. Older events are overwritten.
+const CAPACITY: usize = 1024;
+
+/// The kind of an atomic operation recorded in an [`Event`].
+///
+/// Operations that do not return the previous value (e.g., `add`, `bit_set`)
+/// are performed and recorded as the corresponding `fetch_*` operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// `load`
+    Load,
+    /// `store`
+    Store,
+    /// `swap`
+    Swap,
+    /// `compare_exchange`
+    CompareExchange,
+    /// `compare_exchange_weak`
+    CompareExchangeWeak,
+    /// `fetch_add` (or `add`)
+    FetchAdd,
+    /// `fetch_sub` (or `sub`)
+    FetchSub,
+    /// `fetch_and` (or `and`, `bit_clear`)
+    FetchAnd,
+    /// `fetch_nand`
+    FetchNand,
+    /// `fetch_or` (or `or`, `bit_set`)
+    FetchOr,
+    /// `fetch_xor` (or `xor`, `bit_toggle`)
+    FetchXor,
+    /// `fetch_max`
+    FetchMax,
+    /// `fetch_min`
+    FetchMin,
+    /// `fetch_not` (or `not`)
+    FetchNot,
+    /// `fetch_neg` (or `neg`)
+    FetchNeg,
+}
+
+/// An atomic operation recorded by the current thread.
+///
+/// Values are recorded as the bits of the integer (or the address of the
+/// pointer) zero-extended to `u128`. Operations on [`AtomicBool`](crate::AtomicBool)
+/// and atomic floats are recorded as operations on the integer type of the same
+/// size, and operations that are implemented using CAS loops (e.g., `fetch_update`)
+/// are recorded as the individual loads and CAS operations.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    addr: usize,
+    operation: Operation,
+    ordering: Ordering,
+    old: Option<u128>,
+    new: Option<u128>,
+}
+
+impl Event {
+    /// Returns the address of the atomic value.
+    #[inline]
+    #[must_use]
+    pub fn addr(&self) -> usize {
+        self.addr
+    }
+
+    /// Returns the kind of the operation.
+    #[inline]
+    #[must_use]
+    pub fn operation(&self) -> Operation {
+        self.operation
+    }
+
+    /// Returns the memory ordering of the operation.
+    ///
+    /// For a failed compare-and-exchange, this is the failure ordering.
+    #[inline]
+    #[must_use]
+    pub fn ordering(&self) -> Ordering {
+        self.ordering
+    }
+
+    /// Returns the value read by the operation, or `None` if the operation is
+    /// a store.
+    #[inline]
+    #[must_use]
+    pub fn old_value(&self) -> Option<u128> {
+        self.old
+    }
+
+    /// Returns the value written by the operation, or `None` if the operation
+    /// is a load or a failed compare-and-exchange.
+    #[inline]
+    #[must_use]
+    pub fn new_value(&self) -> Option<u128> {
+        self.new
+    }
+}
+
+impl fmt::Debug for Event {
+    #[inline] // fmt is not hot path, but #[inline] on fmt seems to still be useful: https://github.com/rust-lang/rust/pull/117727
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Event")
+            .field("addr", &format_args!("{:#x}", self.addr))
+            .field("operation", &self.operation)
+            .field("ordering", &self.ordering)
+            .field("old_value", &self.old)
+            .field("new_value", &self.new)
+            .finish()
+    }
+}
+
+// The number of usize words in a u128.
+const WORDS: usize = 16 / mem::size_of::<usize>();
+const WORD_BITS: usize = mem::size_of::<usize>() * 8;
+
+const OPERATIONS: [Operation; 15] = [
+    Operation::Load,
+    Operation::Store,
+    Operation::Swap,
+    Operation::CompareExchange,
+    Operation::CompareExchangeWeak,
+    Operation::FetchAdd,
+    Operation::FetchSub,
+    Operation::FetchAnd,
+    Operation::FetchNand,
+    Operation::FetchOr,
+    Operation::FetchXor,
+    Operation::FetchMax,
+    Operation::FetchMin,
+    Operation::FetchNot,
+    Operation::FetchNeg,
+];
+const ORDERINGS: [Ordering; 5] =
+    [Ordering::Relaxed, Ordering::Release, Ordering::Acquire, Ordering::AcqRel, Ordering::SeqCst];
+
+// Bits of Slot::meta.
+const HAS_OLD: usize = 1 << 8;
+const HAS_NEW: usize = 1 << 9;
+
+// A slot of a ring buffer, which holds one event.
+//
+// A slot is written only by the thread that owns the ring, and may be read by
+// other threads (dump_all). The fields are atomics (core's, so that recording
+// does not record itself) and are protected by a seqlock: while the i-th event
+// of the ring is written to the slot, seq is 2*i+1, and after that, seq is
+// 2*i+2. A reader that wants the i-th event discards the slot if seq is not
+// 2*i+2 before and after reading the fields, which happens only if the event
+// has been (or is being) overwritten by a newer event. Therefore, neither
+// recording nor reading waits for other threads.
+struct Slot {
+    seq: AtomicUsize,
+    addr: AtomicUsize,
+    // The index of the operation in OPERATIONS, the index of the ordering in
+    // ORDERINGS (shifted by 4), HAS_OLD, and HAS_NEW.
+    meta: AtomicUsize,
+    old: [AtomicUsize; WORDS],
+    new: [AtomicUsize; WORDS],
+}
+
+impl Slot {
+    fn new() -> Self {
+        Self {
+            seq: AtomicUsize::new(0),
+            addr: AtomicUsize::new(0),
+            meta: AtomicUsize::new(0),
+            old: Default::default(),
+            new: Default::default(),
+        }
+    }
+
+    fn write(&self, index: usize, event: &Event) {
+        #[allow(clippy::cast_possible_truncation)]
+        fn write_value(dst: &[AtomicUsize; WORDS], v: Option<u128>) {
+            let v = v.unwrap_or(0);
+            for (i, w) in dst.iter().enumerate() {
+                w.store((v >> (i * WORD_BITS)) as usize, Ordering::Relaxed);
+            }
+        }
+        let mut meta = OPERATIONS.iter().position(|&o| o == event.operation).unwrap()
+            | ORDERINGS.iter().position(|&o| o == event.ordering).unwrap() << 4;
+        if event.old.is_some() {
+            meta |= HAS_OLD;
+        }
+        if event.new.is_some() {
+            meta |= HAS_NEW;
+        }
+        self.seq.store(index.wrapping_mul(2).wrapping_add(1), Ordering::Relaxed);
+        atomic::fence(Ordering::Release);
+        self.addr.store(event.addr, Ordering::Relaxed);
+        self.meta.store(meta, Ordering::Relaxed);
+        write_value(&self.old, event.old);
+        write_value(&self.new, event.new);
+        self.seq.store(index.wrapping_mul(2).wrapping_add(2), Ordering::Release);
+    }
+
+    fn read(&self, index: usize) -> Option<Event> {
+        fn read_value(src: &[AtomicUsize; WORDS]) -> u128 {
+            src.iter()
+                .enumerate()
+                .fold(0, |v, (i, w)| v | (w.load(Ordering::Relaxed) as u128) << (i * WORD_BITS))
+        }
+        let seq = index.wrapping_mul(2).wrapping_add(2);
+        if self.seq.load(Ordering::Acquire) != seq {
+            return None;
+        }
+        let addr = self.addr.load(Ordering::Relaxed);
+        let meta = self.meta.load(Ordering::Relaxed);
+        let old = read_value(&self.old);
+        let new = read_value(&self.new);
+        atomic::fence(Ordering::Acquire);
+        if self.seq.load(Ordering::Relaxed) != seq {
+            return None;
+        }
+        Some(Event {
+            addr,
+            operation: OPERATIONS[meta & 0xf],
+            ordering: ORDERINGS[(meta >> 4) & 0xf],
+            old: if meta & HAS_OLD != 0 { Some(old) } else { None },
+            new: if meta & HAS_NEW != 0 { Some(new) } else { None },
+        })
+    }
+}
+
+// The ring buffer of a thread.
+//
+// Rings are never freed, so that dump_all can return the events of threads
+// that have exited.
+struct Ring {
+    thread: ThreadId,
+    slots: Box<[Slot]>,
+    // The number of events recorded by the thread (wrapping). The i-th event is
+    // stored in slots[i % CAPACITY]. Only the owner thread writes this.
+    len: AtomicUsize,
+    // The value of len when the buffer was last cleared.
+    cleared: AtomicUsize,
+    // The next ring in RINGS.
+    next: *const Ring,
+}
+
+// SAFETY: next is only written before the ring is shared, and other fields are Sync.
+unsafe impl Sync for Ring {}
+
+impl Ring {
+    fn push(&self, event: &Event) {
+        let len = self.len.load(Ordering::Relaxed);
+        self.slots[len % CAPACITY].write(len, event);
+        self.len.store(len.wrapping_add(1), Ordering::Release);
+    }
+
+    // Returns the events in the ring, oldest first.
+    fn events(&self) -> Vec<Event> {
+        let len = self.len.load(Ordering::Acquire);
+        let count = cmp::min(len.wrapping_sub(self.cleared.load(Ordering::Acquire)), CAPACITY);
+        let start = len.wrapping_sub(count);
+        (0..count)
+            .map(|i| start.wrapping_add(i))
+            .filter_map(|i| self.slots[i % CAPACITY].read(i))
+            .collect()
+    }
+
+    fn clear(&self) {
+        self.cleared.store(self.len.load(Ordering::Relaxed), Ordering::Release);
+    }
+}
+
+// The list of the rings of all threads that have recorded events.
+static RINGS: AtomicPtr<Ring> = AtomicPtr::new(ptr::null_mut());
+
+std::thread_local! {
+    static RING: RefCell<Option<&'static Ring>> = RefCell::new(None);
+}
+
+// Creates a ring for the current thread and adds it to RINGS.
+#[cold]
+fn register() -> &'static Ring {
+    let ring = Box::leak(Box::new(Ring {
+        thread: thread::current().id(),
+        slots: (0..CAPACITY).map(|_| Slot::new()).collect(),
+        len: AtomicUsize::new(0),
+        cleared: AtomicUsize::new(0),
+        next: ptr::null(),
+    }));
+    let mut head = RINGS.load(Ordering::Relaxed);
+    loop {
+        ring.next = head;
+        match RINGS.compare_exchange_weak(head, ring, Ordering::Release, Ordering::Relaxed) {
+            Ok(_) => return ring,
+            Err(h) => head = h,
+        }
+    }
+}
+
+#[inline]
+pub(crate) fn record(
+    addr: usize,
+    operation: Operation,
+    ordering: Ordering,
+    old: Option<u128>,
+    new: Option<u128>,
+) {
+    let event = Event { addr, operation, ordering, old, new };
+    // try_with fails only while thread-local variables are being destroyed, and
+    // try_borrow_mut fails only if an atomic operation is performed while
+    // recording (e.g., by a global allocator that uses portable-atomic while
+    // the ring is allocated, or by a signal handler). Events are dropped in
+    // both cases.
+    let _ = RING.try_with(|ring| {
+        if let Ok(mut ring) = ring.try_borrow_mut() {
+            ring.get_or_insert_with(register).push(&event);
+        }
+    });
+}
+
+/// Returns the atomic operations recently performed by the current thread,
+/// oldest first.
+///
+/// This only returns the operations of the current thread. Use [`dump_all`]
+/// to get the operations of all threads.
+///
+/// Each thread records up to the last 1024 operations in its own buffer;
+/// older operations are overwritten. This does not clear the buffer.
+///
+/// # Examples
+///
+/// ```
+/// use portable_atomic::{trace, AtomicUsize, Ordering};
+///
+/// let a = AtomicUsize::new(1);
+/// a.fetch_add(2, Ordering::Relaxed);
+///
+/// let event = *trace::dump().last().unwrap();
+/// assert_eq!(event.addr(), a.as_ptr() as usize);
+/// assert_eq!(event.operation(), trace::Operation::FetchAdd);
+/// assert_eq!(event.ordering(), Ordering::Relaxed);
+/// assert_eq!(event.old_value(), Some(1));
+/// assert_eq!(event.new_value(), Some(3));
+/// ```
+#[inline]
+#[must_use]
+pub fn dump() -> Vec<Event> {
+    RING.try_with(|ring| ring.borrow().map(Ring::events).unwrap_or_default()).unwrap_or_default()
+}
+
+/// Returns the atomic operations recently performed by each thread, oldest
+/// first, together with the ID of the thread.
+///
+/// This includes threads that have exited. The buffers of other threads are
+/// read without waiting for them, so operations that are overwritten while
+/// reading are omitted, and operations performed concurrently with this call
+/// may or may not be included. The order of operations of different threads
+/// is not recorded.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+///
+/// use portable_atomic::{trace, AtomicUsize, Ordering};
+///
+/// let id = thread::spawn(|| {
+///     let a = AtomicUsize::new(0);
+///     a.store(1, Ordering::Release);
+///     thread::current().id()
+/// })
+/// .join()
+/// .unwrap();
+///
+/// let (_, events) = trace::dump_all().into_iter().find(|(thread, _)| *thread == id).unwrap();
+/// assert_eq!(events.len(), 1);
+/// assert_eq!(events[0].new_value(), Some(1));
+/// ```
+#[inline]
+#[must_use]
+pub fn dump_all() -> Vec<(ThreadId, Vec<Event>)> {
+    let mut rings = Vec::new();
+    let mut ring = RINGS.load(Ordering::Acquire);
+    while !ring.is_null() {
+        // SAFETY: rings in RINGS are never freed.
+        let r = unsafe { &*ring };
+        rings.push((r.thread, r.events()));
+        ring = r.next as *mut Ring;
+    }
+    // RINGS has the newest ring first.
+    rings.reverse();
+    rings
+}
+
+/// Clears the buffer of the current thread.
+#[inline]
+pub fn clear() {
+    let _ = RING.try_with(|ring| {
+        if let Some(ring) = *ring.borrow() {
+            ring.clear();
+        }
+    });
+}
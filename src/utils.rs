@@ -19,10 +19,16 @@ pub(crate) const fn _assert_is_bool(v: bool) -> bool {
 
 macro_rules! static_assert_layout {
     ($atomic_type:ty, $value_type:ty) => {
-        static_assert!(
-            core::mem::align_of::<$atomic_type>() == core::mem::size_of::<$atomic_type>()
-        );
-        static_assert!(core::mem::size_of::<$atomic_type>() == core::mem::size_of::<$value_type>());
+        // loom's atomic types do not have the same layout as the value type.
+        #[cfg(not(portable_atomic_loom))]
+        {
+            static_assert!(
+                core::mem::align_of::<$atomic_type>() == core::mem::size_of::<$atomic_type>()
+            );
+            static_assert!(
+                core::mem::size_of::<$atomic_type>() == core::mem::size_of::<$value_type>()
+            );
+        }
     };
 }
 
@@ -48,9 +54,11 @@ macro_rules! doc_comment {
     target_arch = "aarch64",
     target_arch = "arm",
     target_arch = "arm64ec",
+    target_arch = "loongarch64",
     target_arch = "powerpc64",
     target_arch = "riscv32",
     target_arch = "riscv64",
+    all(target_arch = "x86", not(miri)),
     all(target_arch = "x86_64", not(any(target_env = "sgx", miri))),
 ))]
 macro_rules! ifunc {
@@ -84,9 +92,11 @@ macro_rules! ifunc {
     target_arch = "aarch64",
     target_arch = "arm",
     target_arch = "arm64ec",
+    target_arch = "loongarch64",
     target_arch = "powerpc64",
     target_arch = "riscv32",
     target_arch = "riscv64",
+    all(target_arch = "x86", not(miri)),
     all(target_arch = "x86_64", not(any(target_env = "sgx", miri))),
 ))]
 macro_rules! fn_alias {
@@ -313,6 +323,29 @@ pub(crate) fn assert_compare_exchange_ordering(success: Ordering, failure: Order
     }
 }
 
+// Used by from_mut/from_mut_slice to check that a reference to the value type can be
+// reinterpreted as a reference to the atomic type. This is constant-folded to `true` when the
+// alignment of the value type is not smaller than the alignment of the atomic type, which is
+// the case for most types on most targets, except for, e.g., 64-bit integers on 32-bit x86 or
+// 128-bit integers on targets where they are only 8-byte aligned.
+#[inline]
+pub(crate) fn is_aligned_for<T, A>(ptr: *const T) -> bool {
+    core::mem::align_of::<T>() >= core::mem::align_of::<A>()
+        || ptr as usize % core::mem::align_of::<A>() == 0
+}
+
+// https://github.com/rust-lang/rust/blob/1.80.0/library/core/src/sync/atomic.rs#L3267
+#[allow(dead_code)]
+#[inline]
+pub(crate) fn strongest_failure_ordering(order: Ordering) -> Ordering {
+    match order {
+        Ordering::Release | Ordering::Relaxed => Ordering::Relaxed,
+        Ordering::SeqCst => Ordering::SeqCst,
+        Ordering::Acquire | Ordering::AcqRel => Ordering::Acquire,
+        _ => unreachable!(),
+    }
+}
+
 // https://www.open-std.org/jtc1/sc22/wg21/docs/papers/2016/p0418r2.html
 // https://github.com/rust-lang/rust/pull/98383
 #[allow(dead_code)]
@@ -350,6 +383,7 @@ pub(crate) fn zero_extend64_ptr(v: *mut ()) -> core::mem::MaybeUninit<u64> {
 #[cfg(any(
     target_arch = "aarch64",
     target_arch = "arm64ec",
+    target_arch = "loongarch64",
     target_arch = "powerpc64",
     target_arch = "riscv64",
     target_arch = "s390x",
@@ -366,7 +400,12 @@ pub(crate) union U128 {
     pub(crate) pair: Pair<u64>,
 }
 #[allow(dead_code)]
-#[cfg(any(target_arch = "arm", target_arch = "riscv32"))]
+#[cfg(any(
+    target_arch = "arm",
+    target_arch = "mips32r6",
+    target_arch = "riscv32",
+    target_arch = "x86",
+))]
 /// A 64-bit value represented as a pair of 32-bit values.
 ///
 /// This type is `#[repr(C)]`, both fields have the same in-memory representation
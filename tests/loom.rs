@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Tests for `--cfg portable_atomic_loom`.
+//
+// Run with:
+//   RUSTFLAGS="--cfg portable_atomic_loom" cargo test --release --test loom
+
+#![cfg(portable_atomic_loom)]
+
+use loom::{sync::Arc, thread};
+use portable_atomic::{AtomicBool, AtomicU128, AtomicUsize, Ordering};
+
+#[test]
+fn counter() {
+    loom::model(|| {
+        let a = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..2)
+            .map(|_| {
+                let a = a.clone();
+                thread::spawn(move || {
+                    a.fetch_add(1, Ordering::Relaxed);
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        assert_eq!(a.load(Ordering::Relaxed), 2);
+    });
+}
+
+fn message_passing(store: Ordering, load: Ordering) {
+    loom::model(move || {
+        let data = Arc::new(AtomicUsize::new(0));
+        let ready = Arc::new(AtomicBool::new(false));
+        let t = {
+            let data = data.clone();
+            let ready = ready.clone();
+            thread::spawn(move || {
+                data.store(1, Ordering::Relaxed);
+                ready.store(true, store);
+            })
+        };
+        if ready.load(load) {
+            assert!(data.load(Ordering::Relaxed) == 1, "data is not visible");
+        }
+        t.join().unwrap();
+    });
+}
+
+#[test]
+fn message_passing_release_acquire() {
+    message_passing(Ordering::Release, Ordering::Acquire);
+}
+
+// loom must find the execution in which the reader sees `ready` but not `data`.
+#[test]
+#[should_panic(expected = "data is not visible")]
+fn message_passing_relaxed() {
+    message_passing(Ordering::Relaxed, Ordering::Relaxed);
+}
+
+// Each atomic is a separate loom object even if it reuses the address of a
+// previous one.
+#[test]
+fn reuse_address() {
+    loom::model(|| {
+        for i in 0..3 {
+            let a = AtomicUsize::new(0);
+            assert_eq!(a.fetch_add(1, Ordering::Relaxed), 0, "iteration {}", i);
+        }
+    });
+}
+
+#[test]
+fn u128() {
+    loom::model(|| {
+        let a = Arc::new(AtomicU128::new(u64::MAX as u128));
+        let t = {
+            let a = a.clone();
+            thread::spawn(move || {
+                a.fetch_add(1, Ordering::AcqRel);
+            })
+        };
+        let _ = a.compare_exchange(u64::MAX as u128, 1 << 100, Ordering::AcqRel, Ordering::Acquire);
+        t.join().unwrap();
+        let v = a.load(Ordering::Acquire);
+        // Either the CAS or fetch_add happens first.
+        assert!(v == (1 << 100) + 1 || v == 1 << 64, "{:#x}", v);
+    });
+}
+
+#[test]
+fn get_mut() {
+    loom::model(|| {
+        let mut a = AtomicUsize::new(1);
+        a.fetch_add(1, Ordering::Relaxed);
+        // The value written by the atomic operation is visible to get_mut.
+        assert_eq!(*a.get_mut(), 2);
+        // And the value written by get_mut is visible to atomic operations.
+        *a.get_mut() = 5;
+        assert_eq!(a.fetch_add(1, Ordering::Relaxed), 5);
+        assert_eq!(a.into_inner(), 6);
+
+        let a = Arc::new(AtomicUsize::new(0));
+        let t = {
+            let a = a.clone();
+            thread::spawn(move || {
+                a.store(3, Ordering::Release);
+            })
+        };
+        t.join().unwrap();
+        let mut a = Arc::try_unwrap(a).unwrap();
+        assert_eq!(*a.get_mut(), 3);
+    });
+}
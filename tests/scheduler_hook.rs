@@ -0,0 +1,162 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Tests for `--cfg portable_atomic_scheduler_hook`.
+//
+// Run with:
+//   RUSTFLAGS="--cfg portable_atomic_scheduler_hook" cargo test --test scheduler_hook
+
+#![cfg(portable_atomic_scheduler_hook)]
+
+use std::{
+    cell::Cell,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread,
+    vec::Vec,
+};
+
+use portable_atomic::{set_scheduler_hook, AtomicBool, AtomicU8, AtomicUsize, Ordering};
+
+// The hook is shared by all threads, so tests that set it must not run concurrently.
+static HOOK_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock<T>(m: &Mutex<T>) -> MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[test]
+fn hook_called() {
+    thread_local! {
+        static CALLS: Cell<usize> = Cell::new(0);
+    }
+    fn hook() {
+        CALLS.with(|calls| calls.set(calls.get() + 1));
+    }
+    fn calls(f: impl FnOnce()) -> usize {
+        let before = CALLS.with(Cell::get);
+        f();
+        CALLS.with(Cell::get) - before
+    }
+    #[repr(align(16))]
+    struct Aligned([AtomicU8; 16]);
+
+    let _guard = lock(&HOOK_LOCK);
+    assert!(set_scheduler_hook(Some(hook)).is_none());
+
+    let a = AtomicUsize::new(0);
+    assert_eq!(calls(|| assert_eq!(a.load(Ordering::Relaxed), 0)), 1);
+    assert_eq!(calls(|| a.store(1, Ordering::Relaxed)), 1);
+    assert_eq!(calls(|| assert_eq!(a.fetch_add(1, Ordering::AcqRel), 1)), 1);
+    assert_eq!(calls(|| a.add(1, Ordering::AcqRel)), 1);
+    assert_eq!(calls(|| portable_atomic::fence(Ordering::SeqCst)), 1);
+    let b = AtomicBool::new(false);
+    assert_eq!(calls(|| assert!(!b.fetch_not(Ordering::AcqRel))), 1);
+
+    // Slice operations call the hook for each element or usize-sized chunk.
+    let s = [AtomicU8::new(1), AtomicU8::new(2), AtomicU8::new(3)];
+    let mut buf = [0; 3];
+    assert_eq!(calls(|| AtomicU8::load_slice(&s, &mut buf, Ordering::Relaxed)), 3);
+    assert_eq!(buf, [1, 2, 3]);
+    let mut s = Aligned(Default::default());
+    let chunks = 16 / core::mem::size_of::<usize>();
+    assert_eq!(
+        // SAFETY: s is not accessed by atomic operations of other sizes.
+        calls(|| unsafe { AtomicU8::store_slice_chunked(&s.0, &[1; 16], Ordering::Relaxed) }),
+        chunks
+    );
+    assert_eq!(AtomicU8::get_mut_slice(&mut s.0), [1; 16]);
+
+    // The hook is not called when it is not set.
+    assert!(set_scheduler_hook(None).is_some());
+    assert_eq!(calls(|| a.store(0, Ordering::Relaxed)), 0);
+}
+
+// A minimal deterministic scheduler: only one thread runs at a time, and the
+// hook passes control to a thread picked by a pseudo-random number generator.
+struct State {
+    current: usize,
+    running: Vec<bool>,
+    rng: u64,
+}
+
+static STATE: Mutex<Option<State>> = Mutex::new(None);
+static CONDVAR: Condvar = Condvar::new();
+
+thread_local! {
+    static ID: Cell<Option<usize>> = Cell::new(None);
+}
+
+// Picks the next thread among the running threads, and waits until `id` is picked.
+fn switch(mut guard: MutexGuard<'_, Option<State>>, id: Option<usize>) {
+    let state = guard.as_mut().unwrap();
+    let running: Vec<_> = (0..state.running.len()).filter(|&i| state.running[i]).collect();
+    if !running.is_empty() {
+        // xorshift64
+        state.rng ^= state.rng << 13;
+        state.rng ^= state.rng >> 7;
+        state.rng ^= state.rng << 17;
+        #[allow(clippy::cast_possible_truncation)] // less than running.len()
+        let i = (state.rng % running.len() as u64) as usize;
+        state.current = running[i];
+    }
+    CONDVAR.notify_all();
+    if let Some(id) = id {
+        wait(guard, id);
+    }
+}
+
+fn wait(mut guard: MutexGuard<'_, Option<State>>, id: usize) {
+    while guard.as_ref().unwrap().current != id {
+        guard = CONDVAR.wait(guard).unwrap_or_else(PoisonError::into_inner);
+    }
+}
+
+fn schedule() {
+    if let Some(id) = ID.with(Cell::get) {
+        switch(lock(&STATE), Some(id));
+    }
+}
+
+// Runs 3 threads that increment a shared counter under the scheduler with the
+// given seed, and returns the value observed by each increment.
+fn run(seed: u64) -> Vec<(usize, usize)> {
+    const THREADS: usize = 3;
+    let counter = AtomicUsize::new(0);
+    let observed = Mutex::new(Vec::new());
+    *lock(&STATE) = Some(State { current: 0, running: vec![true; THREADS], rng: seed });
+    thread::scope(|s| {
+        for id in 0..THREADS {
+            let counter = &counter;
+            let observed = &observed;
+            s.spawn(move || {
+                wait(lock(&STATE), id);
+                ID.with(|v| v.set(Some(id)));
+                for _ in 0..4 {
+                    let v = counter.fetch_add(1, Ordering::Relaxed);
+                    lock(observed).push((id, v));
+                }
+                ID.with(|v| v.set(None));
+                let mut guard = lock(&STATE);
+                guard.as_mut().unwrap().running[id] = false;
+                switch(guard, None);
+            });
+        }
+    });
+    *lock(&STATE) = None;
+    observed.into_inner().unwrap()
+}
+
+#[test]
+fn seeded_replay() {
+    let _guard = lock(&HOOK_LOCK);
+    set_scheduler_hook(Some(schedule));
+    let runs: Vec<_> = (1..=8).map(|seed| (run(seed), run(seed))).collect();
+    set_scheduler_hook(None);
+
+    // The same seed always results in the same interleaving.
+    for (first, replay) in &runs {
+        assert_eq!(first.len(), 12);
+        assert_eq!(first, replay);
+    }
+    // Different seeds explore different interleavings.
+    assert!(runs.iter().any(|(r, _)| *r != runs[0].0));
+}
@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: Apache-2.0 OR MIT
+
+// Tests for `--cfg portable_atomic_trace`.
+//
+// Run with:
+//   RUSTFLAGS="--cfg portable_atomic_trace" cargo test --features std --test trace
+
+#![cfg(portable_atomic_trace)]
+
+use portable_atomic::{
+    trace::{self, Operation},
+    AtomicU8, AtomicUsize, Ordering,
+};
+
+// The number of events recorded per thread.
+const CAPACITY: usize = 1024;
+
+// Each test runs on its own thread, so the buffers of other tests are not visible.
+
+#[test]
+fn record() {
+    trace::clear();
+    let a = AtomicUsize::new(1);
+    a.store(2, Ordering::Release);
+    assert_eq!(a.swap(3, Ordering::AcqRel), 2);
+    a.add(1, Ordering::Relaxed);
+    let events = trace::dump();
+    assert_eq!(events.len(), 3);
+    assert!(events.iter().all(|e| e.addr() == a.as_ptr() as usize));
+    assert_eq!(events[0].operation(), Operation::Store);
+    assert_eq!(events[0].ordering(), Ordering::Release);
+    assert_eq!(events[0].old_value(), None);
+    assert_eq!(events[0].new_value(), Some(2));
+    assert_eq!(events[1].operation(), Operation::Swap);
+    assert_eq!(events[1].old_value(), Some(2));
+    assert_eq!(events[1].new_value(), Some(3));
+    // Operations that do not return the previous value are recorded as fetch_*.
+    assert_eq!(events[2].operation(), Operation::FetchAdd);
+    assert_eq!(events[2].old_value(), Some(3));
+    assert_eq!(events[2].new_value(), Some(4));
+}
+
+#[test]
+fn compare_exchange() {
+    trace::clear();
+    let a = AtomicUsize::new(1);
+    assert_eq!(a.compare_exchange(1, 2, Ordering::AcqRel, Ordering::Acquire), Ok(1));
+    assert_eq!(a.compare_exchange(1, 3, Ordering::SeqCst, Ordering::Relaxed), Err(2));
+    let events = trace::dump();
+    assert_eq!(events.len(), 2);
+    // A successful CAS records the success ordering and the new value.
+    assert_eq!(events[0].operation(), Operation::CompareExchange);
+    assert_eq!(events[0].ordering(), Ordering::AcqRel);
+    assert_eq!(events[0].old_value(), Some(1));
+    assert_eq!(events[0].new_value(), Some(2));
+    // A failed CAS records the failure ordering and no new value.
+    assert_eq!(events[1].operation(), Operation::CompareExchange);
+    assert_eq!(events[1].ordering(), Ordering::Relaxed);
+    assert_eq!(events[1].old_value(), Some(2));
+    assert_eq!(events[1].new_value(), None);
+}
+
+#[test]
+fn wraparound() {
+    trace::clear();
+    let a = AtomicUsize::new(0);
+    for i in 0..CAPACITY + 10 {
+        a.store(i, Ordering::Relaxed);
+    }
+    // Only the last CAPACITY events are kept, oldest first.
+    let events = trace::dump();
+    assert_eq!(events.len(), CAPACITY);
+    for (i, e) in events.iter().enumerate() {
+        assert_eq!(e.new_value(), Some((i + 10) as u128));
+    }
+    a.store(CAPACITY + 10, Ordering::Relaxed);
+    let events = trace::dump();
+    assert_eq!(events.len(), CAPACITY);
+    assert_eq!(events[0].new_value(), Some(11));
+    assert_eq!(events[CAPACITY - 1].new_value(), Some((CAPACITY + 10) as u128));
+}
+
+#[test]
+fn clear() {
+    let a = AtomicUsize::new(0);
+    for i in 0..CAPACITY + 10 {
+        a.store(i, Ordering::Relaxed);
+    }
+    trace::clear();
+    assert!(trace::dump().is_empty());
+    // Recording restarts from an empty buffer.
+    a.store(1, Ordering::Relaxed);
+    let events = trace::dump();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].new_value(), Some(1));
+}
+
+#[test]
+fn slice() {
+    #[repr(align(16))]
+    struct Aligned([AtomicU8; 16]);
+
+    trace::clear();
+    let s = [AtomicU8::new(1), AtomicU8::new(2)];
+    let mut buf = [0; 2];
+    AtomicU8::load_slice(&s, &mut buf, Ordering::Relaxed);
+    let events = trace::dump();
+    assert_eq!(events.len(), 2);
+    for (e, a) in events.iter().zip(&s) {
+        assert_eq!(e.addr(), a.as_ptr() as usize);
+        assert_eq!(e.operation(), Operation::Load);
+    }
+
+    // The chunked variants record each usize-sized chunk.
+    trace::clear();
+    let s = Aligned(Default::default());
+    // SAFETY: s is not accessed by atomic operations of other sizes.
+    unsafe { AtomicU8::store_slice_chunked(&s.0, &[0xff; 16], Ordering::Relaxed) }
+    let events = trace::dump();
+    let chunk = core::mem::size_of::<usize>();
+    assert_eq!(events.len(), 16 / chunk);
+    for (i, e) in events.iter().enumerate() {
+        assert_eq!(e.addr(), s.0[i * chunk].as_ptr() as usize);
+        assert_eq!(e.operation(), Operation::Store);
+        assert_eq!(e.new_value(), Some(usize::MAX as u128));
+    }
+}
+
+#[test]
+fn dump_all() {
+    let spawn = |v: usize| {
+        std::thread::spawn(move || {
+            let a = AtomicUsize::new(0);
+            a.store(v, Ordering::Release);
+            (std::thread::current().id(), a.as_ptr() as usize)
+        })
+    };
+    let (t1, t2) = (spawn(1), spawn(2));
+    let (id1, addr1) = t1.join().unwrap();
+    let (id2, addr2) = t2.join().unwrap();
+    trace::clear();
+    let a = AtomicUsize::new(0);
+    a.store(3, Ordering::Relaxed);
+
+    let all = trace::dump_all();
+    // The events of threads that have exited are also returned.
+    for &(id, addr, v) in &[(id1, addr1, 1), (id2, addr2, 2)] {
+        let (_, events) = all.iter().find(|(thread, _)| *thread == id).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].addr(), addr);
+        assert_eq!(events[0].ordering(), Ordering::Release);
+        assert_eq!(events[0].new_value(), Some(v));
+    }
+    let (_, events) =
+        all.iter().find(|(thread, _)| *thread == std::thread::current().id()).unwrap();
+    assert_eq!(*events, trace::dump());
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].new_value(), Some(3));
+}
+
+// dump_all does not return events that are torn by concurrent recording.
+#[test]
+fn dump_all_concurrent() {
+    use std::sync::{atomic::AtomicBool, mpsc, Arc};
+
+    let done = Arc::new(AtomicBool::new(false));
+    let (tx, rx) = mpsc::channel();
+    let t = {
+        let done = done.clone();
+        std::thread::spawn(move || {
+            tx.send(std::thread::current().id()).unwrap();
+            let a = AtomicUsize::new(0);
+            let mut i: usize = 0;
+            while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                i = i.wrapping_add(1);
+                // Both halves of the value are the same.
+                a.store(i & 0xffff | (i & 0xffff) << 16, Ordering::Relaxed);
+            }
+        })
+    };
+    let id = rx.recv().unwrap();
+    // Keep reading until the writer has wrapped around the buffer a few times.
+    let mut full = 0;
+    while full < 10 {
+        let all = trace::dump_all();
+        let events = all.into_iter().find(|(thread, _)| *thread == id).map(|(_, e)| e);
+        let events = events.unwrap_or_default();
+        for e in &events {
+            assert_eq!(e.operation(), Operation::Store);
+            let v = e.new_value().unwrap();
+            assert_eq!(v >> 16, v & 0xffff);
+        }
+        if events.len() == CAPACITY {
+            full += 1;
+        }
+    }
+    done.store(true, std::sync::atomic::Ordering::Relaxed);
+    t.join().unwrap();
+    let (_, events) = trace::dump_all().into_iter().find(|(thread, _)| *thread == id).unwrap();
+    assert_eq!(events.len(), CAPACITY);
+}
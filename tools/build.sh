@@ -57,6 +57,8 @@ default_targets=(
     # x86
     i686-unknown-linux-gnu
     i586-unknown-linux-gnu
+    # no 64-bit atomics in core (CMPXCHG8B is detected at run-time)
+    i486-unknown-linux-gnu # custom target
 
     # aarch64
     # rustc --print target-list | grep -E '^(aarch64|arm64)' | grep -v arm64ec
@@ -93,6 +95,8 @@ default_targets=(
     armv4t-unknown-linux-gnueabi
     armv5te-unknown-linux-gnueabi
     arm-linux-androideabi
+    # arm v7 without 64-bit atomics in core
+    armv7a-none-eabi-atomic32 # custom target
 
     # riscv32
     # rustc --print target-list | grep -E '^riscv32'
@@ -455,6 +459,7 @@ build() {
 
         args+=(
             --feature-powerset --depth 2 --optional-deps --no-dev-deps
+            --mutually-exclusive-features critical-section,require-address-free
             ${exclude_features+"--exclude-features=${exclude_features}"}
             --workspace --no-private
         )
@@ -493,7 +498,7 @@ build() {
                                             x_cargo "${args[@]}" --exclude-features "critical-section" "$@"
                                         CARGO_TARGET_DIR="${target_dir}/zaamo" \
                                             RUSTFLAGS="${target_rustflags} -C target-feature=+zaamo" \
-                                            x_cargo "${args[@]}" --exclude-features "critical-section,require-cas" --exclude portable-atomic-util "$@"
+                                            x_cargo "${args[@]}" --exclude-features "critical-section,require-cas" "$@"
                                         # Support for Zabha extension requires LLVM 19+.
                                         if [[ "${llvm_version}" -ge 19 ]]; then
                                             CARGO_TARGET_DIR="${target_dir}/assume-single-core-zabha" \
@@ -501,7 +506,7 @@ build() {
                                                 x_cargo "${args[@]}" --exclude-features "critical-section" "$@"
                                             CARGO_TARGET_DIR="${target_dir}/zabha" \
                                                 RUSTFLAGS="${target_rustflags} -C target-feature=+zaamo,+zabha" \
-                                                x_cargo "${args[@]}" --exclude-features "critical-section,require-cas" --exclude portable-atomic-util "$@"
+                                                x_cargo "${args[@]}" --exclude-features "critical-section,require-cas" "$@"
                                         fi
                                     fi
                                     ;;
@@ -514,13 +519,11 @@ build() {
                 case "${target}" in
                     avr-* | msp430-*) ;; # always single-core
                     *)
-                        # portable-atomic-util crate and portable-atomic's require-cas feature require atomic CAS,
-                        # so doesn't work on this target without portable_atomic_unsafe_assume_single_core cfg
-                        # or critical-section feature.
-                        args+=(
-                            --exclude portable-atomic-util
-                            --exclude-features require-cas
-                        )
+                        # portable-atomic's require-cas feature requires atomic CAS, so doesn't work on
+                        # this target without portable_atomic_unsafe_assume_single_core cfg or
+                        # critical-section feature. portable-atomic-util is still built to check that
+                        # the primitives that don't require atomic CAS (spsc::Queue) work on this target.
+                        args+=(--exclude-features require-cas)
                         ;;
                 esac
             fi
@@ -586,6 +589,11 @@ build() {
                     x_cargo "${args[@]}" "$@"
             fi
             ;;
+        mipsisa32r6*)
+            CARGO_TARGET_DIR="${target_dir}/assume-llwp-scwp" \
+                RUSTFLAGS="${target_rustflags} --cfg portable_atomic_unsafe_assume_llwp_scwp" \
+                x_cargo "${args[@]}" "$@"
+            ;;
         aarch64* | arm64*)
             # macOS is +lse,+lse2 by default
             if ! grep -Eq '^target_feature="lse"' <<<"${cfgs}"; then
@@ -99,8 +99,9 @@ if [[ -n "${tags}" ]]; then
         bail "failed to update ${changelog}"
     fi
     prev_version="${prev_tag#"${tag_prefix}"}"
-    # Update version in Cargo.toml.
-    if ! grep -Eq "^version = \"${prev_version}\" #publish:version" "${manifest_path}"; then
+    # Update version in Cargo.toml. The version may have already been bumped
+    # so that other crates in this workspace can require the unreleased version.
+    if ! grep -Eq "^version = \"(${prev_version}|${version})\" #publish:version" "${manifest_path}"; then
         bail "not found '#publish:version' in version in ${manifest_path}"
     fi
     sed -E "${in_place[@]}" "s/^version = \"${prev_version}\" #publish:version/version = \"${version}\" #publish:version/g" "${manifest_path}"